@@ -0,0 +1,69 @@
+//! allocations per generation of the public operators (rank_selection_cdf, mate_population, mutate, update)
+//! compared with the double-buffered GA::step<BR>
+//! GA::step only allocates to evaluate new children, as fitness takes each individual as an owned Population<BR>
+//! run with cargo bench --bench allocations
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, TSPInitialization};
+
+struct CountingAllocator;
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+unsafe impl GlobalAlloc for CountingAllocator{
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+///allocations made by f
+fn count<T>(f: impl FnOnce()->T)->usize{
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    std::hint::black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - start
+}
+
+fn main() {
+    //a constant fitness isolates the allocations of selection, crossover and mutation from the ones of evaluation
+    fn fitness(_: Population) -> f64 {
+        EVALUATIONS.fetch_add(1, Ordering::Relaxed);
+        0.0
+    }
+    let generations = 10;
+    let mut rows = Vec::new();
+    for num_individuals in [100, 1000, 10000] {
+        let config = Config{num_individuals, num_genes:100, mutation_rate:0.0, verbose:false, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), fitness, config);
+        ga.step();
+        let operators = count(|| for _ in 0..generations {
+            let parents = ga.rank_selection_cdf();
+            ga.update(parents);
+            let children = ga.mate_population();
+            ga.update(children);
+            let mutated = ga.mutate();
+            ga.update(mutated);
+        });
+        let evaluations = EVALUATIONS.load(Ordering::Relaxed);
+        let step = count(|| for _ in 0..generations {
+            ga.step();
+        });
+        let evaluations = EVALUATIONS.load(Ordering::Relaxed) - evaluations;
+        //each evaluation allocates the individual and its genes
+        let breeding = step.saturating_sub(2 * evaluations);
+        rows.push((num_individuals, operators / generations, step / generations, evaluations / generations, breeding / generations));
+    }
+    println!();
+    println!("{:>12} {:>16} {:>12} {:>16} {:>22}", "individuals", "operators/gen", "step/gen", "evaluations/gen", "step-evaluations/gen");
+    for (individuals, operators, step, evaluations, breeding) in rows {
+        println!("{:>12} {:>16} {:>12} {:>16} {:>22}", individuals, operators, step, evaluations, breeding);
+    }
+}
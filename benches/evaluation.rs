@@ -0,0 +1,56 @@
+//! sequential versus parallel evaluation of the same population (Config::parallel)<BR>
+//! and sequential versus parallel island models: islands stepped one after the other or on their own threads (IslandConfig::parallel)<BR>
+//! run with cargo bench --bench evaluation
+use criterion::{criterion_group, criterion_main, Criterion};
+use rs_genetics::island::{IslandConfig, Islands};
+use rs_genetics::population::{Config, GA, Initialization, InitializationStrategy, Population, RandomInitialization};
+
+//Schwefel function (negated as fitness is maximized), repeated to mimic an expensive fitness
+fn schwefel(weights: Population) -> f64 {
+    match weights {
+        Population::F64(vec) => (0..20).map(|_| {
+            let sum: f64 = vec[0].iter().map(|x| x * x.abs().sqrt().sin()).sum();
+            -(418.9829 * vec[0].len() as f64 - sum)
+        }).sum::<f64>() / 20.0,
+        _ => panic!("Expected Population::F64"),
+    }
+}
+
+//the same individuals for every GA
+struct Fixed(Vec<Vec<f64>>);
+impl Initialization<Vec<Vec<f64>>> for Fixed{
+    fn initialize(&self, _config: Config) -> Vec<Vec<f64>> {
+        self.0.clone()
+    }
+}
+
+fn evaluation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluation");
+    let config = Config{num_individuals:1000, num_genes:100, range:-500.0..500.0, verbose:false, ..Config::default()};
+    let individuals = RandomInitialization.initialize(config.clone());
+    for parallel in [false, true] {
+        let config = Config{parallel, ..config.clone()};
+        let ga = GA::new(InitializationStrategy::F64(Box::new(Fixed(individuals.clone()))), schwefel as fn(Population)->f64, config);
+        let name = if parallel {"parallel"} else {"sequential"};
+        group.bench_function(name, |b| b.iter(|| ga.evaluate()));
+    }
+    group.finish();
+}
+
+fn islands(c: &mut Criterion) {
+    let mut group = c.benchmark_group("islands");
+    group.sample_size(20);
+    for parallel in [false, true] {
+        let islands = (0..4).map(|_| {
+            let config = Config{num_individuals:250, num_genes:100, range:-500.0..500.0, verbose:false, ..Config::default()};
+            GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), schwefel as fn(Population)->f64, config)
+        }).collect();
+        let mut model = Islands::new(islands, IslandConfig{parallel, ..IslandConfig::default()});
+        let name = if parallel {"parallel"} else {"sequential"};
+        group.bench_function(name, |b| b.iter(|| model.step()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, evaluation, islands);
+criterion_main!(benches);
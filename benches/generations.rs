@@ -0,0 +1,38 @@
+//! full GA generations (GA::step) at several population and genome sizes<BR>
+//! run with cargo bench --bench generations
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, RandomInitialization, TSPInitialization};
+
+//Rastrigin function (negated as fitness is maximized)
+fn rastrigin(weights: Population) -> f64 {
+    match weights {
+        Population::F64(vec) => -vec[0].iter().map(|x| x * x - 10.0 * (2.0 * std::f64::consts::PI * x).cos() + 10.0).sum::<f64>(),
+        _ => panic!("Expected Population::F64"),
+    }
+}
+//number of adjacent genes in increasing order
+fn sortedness(weights: Population) -> f64 {
+    match weights {
+        Population::Usize(vec) => vec[0].windows(2).filter(|pair| pair[0] < pair[1]).count() as f64,
+        _ => panic!("Expected Population::Usize"),
+    }
+}
+
+fn generations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    group.sample_size(20);
+    for num_individuals in [100, 1000] {
+        for num_genes in [10, 100] {
+            let size = format!("{}x{}", num_individuals, num_genes);
+            let config = Config{num_individuals, num_genes, range:-5.12..5.12, verbose:false, ..Config::default()};
+            let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), rastrigin, config.clone());
+            group.bench_function(BenchmarkId::new("f64", &size), |b| b.iter(|| ga.step()));
+            let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), sortedness, config);
+            group.bench_function(BenchmarkId::new("usize", &size), |b| b.iter(|| ga.step()));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, generations);
+criterion_main!(benches);
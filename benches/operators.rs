@@ -0,0 +1,84 @@
+//! selection, crossover and mutation operators for each kind of population<BR>
+//! run with cargo bench --bench operators
+use std::hint::black_box;
+use std::sync::Arc;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rs_genetics::gp::{Function, PrimitiveSet, RampedHalfAndHalf, Terminal};
+use rs_genetics::population::{Config, Crossover, GA, InitializationStrategy, Mutation, Population, RandomInitialization, TSPInitialization};
+
+fn sphere(weights: Population) -> f64 {
+    match weights {
+        Population::F64(vec) => -vec[0].iter().map(|x| x * x).sum::<f64>(),
+        _ => 0.0,
+    }
+}
+fn strategies() -> Vec<(&'static str, InitializationStrategy)> {
+    let functions = vec![
+        Function::new("add", 2, |args| args[0] + args[1]),
+        Function::new("mul", 2, |args| args[0] * args[1]),
+    ];
+    let terminals = vec![Terminal::variable("x", 0), Terminal::constant(1.0)];
+    let set = Arc::new(PrimitiveSet::new(functions, terminals));
+    vec![
+        ("f64", InitializationStrategy::F64(Box::new(RandomInitialization))),
+        ("usize", InitializationStrategy::Usize(Box::new(TSPInitialization))),
+        ("tree", InitializationStrategy::Tree(Box::new(RampedHalfAndHalf{set}))),
+    ]
+}
+
+fn selection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selection");
+    for num_individuals in [100, 1000, 10000] {
+        let config = Config{num_individuals, num_genes:10, ..Config::default()};
+        let ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), sphere, config);
+        group.bench_with_input(BenchmarkId::new("rank_selection_cdf", num_individuals), &ga, |b, ga| b.iter(|| ga.rank_selection_cdf()));
+    }
+    group.finish();
+}
+
+fn crossover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crossover");
+    for num_genes in [10, 100, 1000] {
+        let config = Config{num_individuals:2, num_genes, ..Config::default()};
+        let population = InitializationStrategy::F64(Box::new(RandomInitialization)).initialize(config.clone());
+        group.bench_with_input(BenchmarkId::new("f64", num_genes), &population, |b, population| {
+            b.iter(|| Crossover::<Vec<f64>>::crossover(population, black_box(0), black_box(1)))
+        });
+        let population = InitializationStrategy::Usize(Box::new(TSPInitialization)).initialize(config);
+        group.bench_with_input(BenchmarkId::new("usize", num_genes), &population, |b, population| {
+            b.iter(|| Crossover::<Vec<usize>>::crossover(population, black_box(0), black_box(1)))
+        });
+    }
+    let config = Config{num_individuals:2, ..Config::default()};
+    if let Population::Tree(trees) = strategies().remove(2).1.initialize(config) {
+        group.bench_function("tree", |b| b.iter(|| trees[0].crossover(&trees[1])));
+    }
+    //whole population mated in place into a reused buffer, as done by GA::step
+    for (name, strategy) in strategies() {
+        let config = Config{num_individuals:1000, num_genes:100, ..Config::default()};
+        let population = strategy.initialize(config);
+        let parents:Vec<usize> = (0..population.len()).collect();
+        let mut children = population.clone();
+        let mut counts = Vec::new();
+        group.bench_function(BenchmarkId::new("mate_into", name), |b| b.iter(|| population.mate_into(&parents, &mut children, &mut counts)));
+    }
+    group.finish();
+}
+
+fn mutation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mutation");
+    for (name, strategy) in strategies() {
+        let config = Config{num_individuals:1000, num_genes:100, mutation_rate:0.1, ..Config::default()};
+        let mut population = strategy.initialize(config.clone());
+        group.bench_function(BenchmarkId::new("mutate", name), |b| b.iter(|| population.mutate(&config)));
+        group.bench_function(BenchmarkId::new("mutate_at", name), |b| b.iter(|| {
+            for i in 0..population.len() {
+                population.mutate_at(i, &config);
+            }
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, selection, crossover, mutation);
+criterion_main!(benches);
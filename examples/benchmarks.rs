@@ -0,0 +1,25 @@
+use rs_genetics::population::{Config, GA, InitializationStrategy, RandomInitialization};
+use rs_genetics::problems::continuous::{Benchmark, Problem, Transformed};
+
+fn main() {
+    let dim = 5;
+    let mut results = Vec::new();
+    for benchmark in Benchmark::all() {
+        let variants = [("plain", None), ("shifted", Some(Transformed::shifted(benchmark, dim))), ("shifted rotated", Some(Transformed::shifted_rotated(benchmark, dim)))];
+        for (name, variant) in variants {
+            let config = Config{num_individuals:200, num_genes:dim, range:benchmark.bounds(), ..Config::default()};
+            let init_strategy = InitializationStrategy::F64(Box::new(RandomInitialization));
+            let (optimum, fitness) = match variant {
+                Some(variant) => (variant.optimum_value(dim), variant.fitness()),
+                None => (benchmark.optimum_value(dim), benchmark.fitness()),
+            };
+            let mut ga = GA::new(init_strategy, fitness, config);
+            let hist = ga.evolve(200);
+            results.push((benchmark, name, -hist[hist.len() - 1], optimum));
+        }
+    }
+    println!();
+    for (benchmark, name, best, optimum) in results {
+        println!("{:?} ({}): best = {:.4}, optimum = {:?}", benchmark, name, best, optimum);
+    }
+}
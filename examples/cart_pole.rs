@@ -0,0 +1,38 @@
+use rs_genetics::neat::Activation;
+use rs_genetics::neural::{DenseNetwork, Model};
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::{Config, GA, GetPopulation, InitializationStrategy, RandomInitialization};
+
+//classic cart-pole: returns the number of steps (up to 1000) the controller keeps the pole up
+fn balance(model: &Model) -> f64 {
+    let (gravity, cart_mass, pole_mass, length, dt) = (9.8, 1.0, 0.1, 0.5, 0.02);
+    let (mut x, mut x_dot, mut theta, mut theta_dot) = (0.0, 0.0, 0.05, 0.0);
+    for step in 0..1000 {
+        let force = 10.0 * model.predict(&[x / 2.4, x_dot, theta / 0.21, theta_dot])[0];
+        let total_mass = cart_mass + pole_mass;
+        let temp = (force + pole_mass * length * theta_dot * theta_dot * f64::sin(theta)) / total_mass;
+        let theta_acc = (gravity * f64::sin(theta) - f64::cos(theta) * temp)
+            / (length * (4.0 / 3.0 - pole_mass * f64::cos(theta).powi(2) / total_mass));
+        let x_acc = temp - pole_mass * length * theta_acc * f64::cos(theta) / total_mass;
+        x += dt * x_dot;
+        x_dot += dt * x_acc;
+        theta += dt * theta_dot;
+        theta_dot += dt * theta_acc;
+        if x.abs() > 2.4 || theta.abs() > 0.21 {
+            return step as f64;
+        }
+    }
+    1000.0
+}
+
+fn main() {
+    let network = DenseNetwork::new(4, vec![(4, Activation::Tanh), (1, Activation::Tanh)]);
+    let config = network.configure(Config{num_individuals:50, range:-2.0..2.0, ..Config::default()});
+    let init_strategy = InitializationStrategy::F64(Box::new(RandomInitialization));
+    let mut ga = GA::new(init_strategy, network.fitness(balance), config);
+
+    let hist = ga.evolve(30);
+    let weights:Vec<f64> = ga.population().get_individual(0).unwrap();
+    println!("Balanced for {} steps", balance(&network.model(&weights)));
+    draw_fitness(hist, "fitness_curve.png");
+}
@@ -0,0 +1,36 @@
+use rs_genetics::cellular::{CellularConfig, CellularGA, Neighborhood, UpdateMode};
+use rs_genetics::plot::{draw_fitness, draw_heatmap};
+use rs_genetics::population::{Config, InitializationStrategy, Population, TSPInitialization};
+
+fn main() {
+    //n-queens on a 20x20 grid of individuals, diversity is printed as a heatmap at the end
+    fn calculate_fitness(weights: Population) -> f64 {
+        match weights {
+            Population::Usize(vec) => {
+                let individual = &vec[0];
+                let mut fitness = 0;
+                for i in 0..individual.len() {
+                    for j in i + 1..individual.len() {
+                        if individual[i] != individual[j] &&
+                            individual[i] + i != individual[j] + j &&
+                            individual[i] as isize - i as isize != individual[j] as isize - j as isize {
+                            fitness += 1;
+                        }
+                    }
+                }
+                fitness as f64
+            }
+            _ => panic!("Expected Population::usize")
+        }
+    }
+    let init_strategy = InitializationStrategy::Usize(Box::new(TSPInitialization));
+    let config = Config{num_genes:12, mutation_rate:0.2, ..Config::default()};
+    let cellular_config = CellularConfig{width:20, height:20, neighborhood:Neighborhood::Moore, update:UpdateMode::Asynchronous};
+    let mut ga = CellularGA::new(init_strategy, calculate_fitness, config, cellular_config);
+
+    let hist = ga.evolve(50);
+    let (solution, score) = ga.best();
+    println!("Solution = {:?} with fitness {}", solution, score);
+    draw_fitness(hist, "fitness_curve.png");
+    draw_heatmap(ga.diversity_grid(), "diversity.png");
+}
@@ -0,0 +1,42 @@
+use rs_genetics::constraints::RepairStrategy;
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, RandomInitialization};
+use rs_genetics::problems::combinatorial::{bits, BinaryProblem, Knapsack, LeadingOnes, MaxSat, NKLandscape, OneMax, Trap};
+
+fn main() {
+    //bits are encoded as F64 genes in [0,1] so that GA mutation flips them
+    fn solve<P:BinaryProblem + Send + Sync + 'static>(problem:P, repair:Option<RepairStrategy>) -> (f64, Option<f64>) {
+        let optimum = problem.optimum();
+        let config = Config{num_individuals:200, num_genes:problem.num_bits(), range:0.0..1.0, mutation_rate:0.02, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), problem.fitness(), config);
+        if let Some(repair) = repair {
+            ga = ga.with_repair(repair);
+        }
+        let hist = ga.evolve(300);
+        (hist[hist.len() - 1], optimum)
+    }
+
+    //satisfiable formula in DIMACS CNF (x1 = 1, x2 = x3 = x4 = 0), the third clause spans two lines
+    let cnf = "c example formula\np cnf 4 5\n1 -2 0\n2 3 -4 0\n-1 -3\n4 0\n-2 -4 0\n1 2 3 4 0\n";
+    let mut sat = MaxSat::parse_dimacs(cnf).unwrap();
+    sat.optimum = Some(sat.clauses.len() as f64);
+
+    let knapsack = Knapsack::random(100, 5);
+    let results = vec![
+        ("OneMax", solve(OneMax{num_bits:100}, None)),
+        ("LeadingOnes", solve(LeadingOnes{num_bits:50}, None)),
+        ("Trap", solve(Trap::new(10, 4), None)),
+        ("NK landscape", solve(NKLandscape::new(16, 4, false), None)),
+        ("MAX-SAT DIMACS", solve(sat, None)),
+        ("MAX-SAT planted", solve(MaxSat::planted(100, 426, 3), None)),
+        ("Knapsack", solve(knapsack.clone(), None)),
+        ("Knapsack with repair", solve(knapsack.clone(), Some(RepairStrategy::F64(Box::new(knapsack))))),
+    ];
+    println!();
+    for (name, (best, optimum)) in results {
+        println!("{}: best = {}, optimum = {:?}", name, best, optimum);
+    }
+    //bits decodes individuals of any binary encoding
+    let onemax = OneMax{num_bits:8};
+    let individual = Population::Usize(vec![vec![1, 0, 1, 1, 0, 0, 0, 1]]);
+    println!("OneMax of {:?} = {}", bits(&individual), onemax.value(&bits(&individual)));
+}
@@ -0,0 +1,32 @@
+use rs_genetics::constraints::{constrained_ga, ConstraintHandling};
+use rs_genetics::population::{Config, GetPopulation, InitializationStrategy, Population, RandomInitialization};
+
+fn main() {
+    //minimize (x-2)^2 + (y-1)^2 subject to x^2 <= y and x + y <= 2, optimum at (1, 1) with objective 1
+    fn fitness(individual: Population) -> (f64, Vec<f64>) {
+        match individual {
+            Population::F64(vec) => {
+                let (x, y) = (vec[0][0], vec[0][1]);
+                let objective = -((x - 2.0).powi(2) + (y - 1.0).powi(2));
+                (objective, vec![x * x - y, x + y - 2.0])
+            }
+            _ => panic!("Expected Population::F64"),
+        }
+    }
+
+    let handlings = [
+        ConstraintHandling::StaticPenalty{weight:100.0},
+        ConstraintHandling::DynamicPenalty{c:0.5, alpha:2.0, beta:2.0},
+        ConstraintHandling::AdaptivePenalty{initial:1.0, increase:4.0, decrease:1.2, window:10},
+        ConstraintHandling::FeasibilityRules,
+        ConstraintHandling::EpsilonConstrained{epsilon:1.0, control_generations:100, exponent:2.0},
+    ];
+    for handling in handlings {
+        let config = Config{num_individuals:100, num_genes:2, range:-2.0..3.0, ..Config::default()};
+        let mut ga = constrained_ga(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config, handling.clone());
+        ga.evolve(200);
+        let solution:Vec<f64> = ga.population().get_individual(0).unwrap();
+        let (objective, violations) = fitness(Population::F64(vec![solution.clone()]));
+        println!("{:?}: solution = {:?}, objective = {}, violations = {:?}", handling, solution, -objective, violations);
+    }
+}
@@ -0,0 +1,29 @@
+use rs_genetics::eda::{EDA, EDAModel};
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::Config;
+use rs_genetics::problems::combinatorial::{BinaryProblem, OneMax};
+use rs_genetics::problems::continuous::{Benchmark, Problem};
+
+fn main() {
+    //OneMax: number of genes set to 1
+    let one_max = OneMax{num_bits:100};
+    let config = Config{num_individuals:50, num_genes:one_max.num_bits(), ..Config::default()};
+
+    let mut pbil = EDA::new(one_max.clone().fitness(), config.clone(), EDAModel::default());
+    pbil.evolve(200);
+    let mut umda = EDA::new(one_max.clone().fitness(), config.clone(), EDAModel::UMDA{selection_ratio:0.5, cardinality:2});
+    umda.evolve(50);
+    let mut cga = EDA::new(one_max.clone().fitness(), config.clone(), EDAModel::CompactGA{virtual_population:100});
+    cga.evolve(3000);
+    for (name, eda) in [("PBIL", &pbil), ("UMDA", &umda), ("cGA", &cga)] {
+        println!("{} best OneMax = {}", name, eda.best().unwrap().1);
+    }
+
+    //Sphere function, global optimum 0 at the origin
+    let config = Config{num_individuals:100, num_genes:10, range:-5.0..5.0, ..Config::default()};
+    let mut gaussian = EDA::new(Benchmark::Sphere.fitness(), config, EDAModel::GaussianUMDA{selection_ratio:0.3});
+    let hist = gaussian.evolve(100);
+    println!("Final normals = {:?}", gaussian.normals());
+    println!("Best sphere = {}", gaussian.best().unwrap().1);
+    draw_fitness(hist, "fitness_curve.png");
+}
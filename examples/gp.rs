@@ -0,0 +1,38 @@
+use std::sync::Arc;
+use rs_genetics::gp::{Function, PrimitiveSet, RampedHalfAndHalf, Terminal};
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population};
+
+fn main() {
+    //6-multiplexer: address bits a0,a1 select which of the data bits d0..d3 is returned
+    fn fitness(program: Population) -> f64 {
+        match program {
+            Population::Tree(trees) => {
+                (0..64).filter(|case| {
+                    let inputs:Vec<f64> = (0..6).map(|bit| ((case >> bit) & 1) as f64).collect();
+                    let expected = inputs[2 + (inputs[0] + 2.0 * inputs[1]) as usize];
+                    (trees[0].evaluate(&inputs) > 0.5) == (expected > 0.5)
+                }).count() as f64
+            }
+            _ => panic!("Expected Population::Tree"),
+        }
+    }
+
+    let functions = vec![
+        Function::new("and", 2, |args| args[0].min(args[1])),
+        Function::new("or", 2, |args| args[0].max(args[1])),
+        Function::new("not", 1, |args| 1.0 - args[0]),
+        Function::new("if", 3, |args| if args[0] > 0.5 {args[1]} else {args[2]}),
+    ];
+    let terminals = ["a0", "a1", "d0", "d1", "d2", "d3"].iter().enumerate()
+        .map(|(index, name)| Terminal::variable(name, index))
+        .collect();
+    let set = Arc::new(PrimitiveSet::new(functions, terminals));
+    let init_strategy = InitializationStrategy::Tree(Box::new(RampedHalfAndHalf{set}));
+    let config = Config{num_individuals:500, mutation_rate:0.2, ..Config::default()};
+    let mut ga = GA::new(init_strategy, fitness, config);
+
+    let hist = ga.evolve(50);
+    println!("Solution = {}", ga.population().get_tree(0).unwrap());
+    draw_fitness(hist, "fitness_curve.png");
+}
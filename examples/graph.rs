@@ -0,0 +1,58 @@
+use rs_genetics::constraints::RepairStrategy;
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, RandomInitialization};
+use rs_genetics::problems::combinatorial::{bits, BinaryProblem};
+use rs_genetics::problems::graph::{Graph, GraphColoring, MaxCut, Partitioning};
+
+fn main() {
+    //myciel3 from the DIMACS coloring benchmarks (chromatic number 4), use Graph::from_dimacs to load .col files
+    let myciel3 = Graph::parse_dimacs("c myciel3.col\np edge 11 20\ne 1 2\ne 1 4\ne 1 7\ne 1 9\ne 2 3\ne 2 6\ne 2 8\ne 3 5\ne 3 7\ne 3 10\n\
+        e 4 5\ne 4 6\ne 4 10\ne 5 8\ne 5 9\ne 6 11\ne 7 11\ne 8 11\ne 9 11\ne 10 11\n").unwrap();
+    println!("DSATUR uses {} colors", myciel3.dsatur().iter().max().unwrap() + 1);
+    let mut results = Vec::new();
+    for num_colors in [3, 4] {
+        let coloring = GraphColoring::new(myciel3.clone(), num_colors);
+        let config = Config{num_individuals:100, num_genes:myciel3.num_vertices, mutation_rate:0.05, ..Config::default()};
+        let mut ga = GA::new(coloring.initialization(), coloring.clone().fitness(), config)
+            .with_variation(coloring.variation())
+            .with_repair(RepairStrategy::Usize(Box::new(coloring.clone())));
+        ga.evolve(100);
+        let (colors, _) = ga.get_individual::<usize>(0).unwrap();
+        results.push(format!("myciel3 with {} colors: {:?}, conflicts = {}", num_colors, colors, coloring.conflicts(&colors)));
+    }
+
+    //max-cut of a weighted edge list (vertices from 0), bits encoded as F64 genes in [0,1] as for combinatorial problems
+    let graph = Graph::parse_edge_list("# u v weight\n0 1 3\n0 2 1\n1 2 2\n1 3 4\n2 3 1\n2 4 5\n3 4 2\n3 5 3\n4 5 1\n").unwrap();
+    let max_cut = MaxCut::new(graph);
+    let random = MaxCut::new(Graph::random(20, 0.3));
+    for (name, problem) in [("edge list", max_cut), ("random graph", random)] {
+        let config = Config{num_individuals:100, num_genes:problem.num_bits(), range:0.0..1.0, mutation_rate:0.05, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), problem.clone().fitness(), config)
+            .with_repair(RepairStrategy::F64(Box::new(problem.clone())));
+        ga.evolve(50);
+        let (genes, _) = ga.get_individual::<f64>(0).unwrap();
+        let cut = problem.value(&bits(&Population::F64(vec![genes])));
+        results.push(format!("max-cut of the {}: cut = {}, optimum = {:?}", name, cut, problem.optimum()));
+    }
+
+    //3-way partitioning of a random graph with parts of at most 22 vertices
+    let partitioning = Partitioning::new(Graph::random(60, 0.1), 3, 0.1);
+    let config = Config{num_individuals:100, num_genes:partitioning.graph.num_vertices, mutation_rate:0.2, ..Config::default()};
+    //balanced initialization with the built-in operators, which keep parts sizes
+    let mut ga = GA::new(partitioning.initialization(), partitioning.clone().fitness(), config.clone());
+    ga.evolve(200);
+    let (parts, _) = ga.get_individual::<usize>(0).unwrap();
+    results.push(format!("partitioning with swaps: cut = {}, sizes = {:?}", partitioning.cut(&parts), partitioning.part_sizes(&parts)));
+    //parts of any size, made feasible by the repair
+    let config = Config{mutation_rate:0.02, ..config};
+    let mut ga = GA::new(partitioning.initialization(), partitioning.clone().fitness(), config)
+        .with_variation(partitioning.variation())
+        .with_repair(RepairStrategy::Usize(Box::new(partitioning.clone())));
+    ga.evolve(200);
+    let (parts, _) = ga.get_individual::<usize>(0).unwrap();
+    results.push(format!("partitioning with repair: cut = {}, sizes = {:?}", partitioning.cut(&parts), partitioning.part_sizes(&parts)));
+
+    println!();
+    for result in results {
+        println!("{}", result);
+    }
+}
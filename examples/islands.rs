@@ -0,0 +1,18 @@
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::island::{IslandConfig, Islands, MigrationTopology};
+use rs_genetics::population::{Config, GA, InitializationStrategy, RandomInitialization};
+use rs_genetics::problems::continuous::{Benchmark, Problem};
+
+fn main() {
+    //Schwefel function, many local optima far from the global one at 420.9687
+    let islands = [0.05, 0.1, 0.2, 0.3].iter().map(|&mutation_rate| {
+        let config = Config{num_individuals:100, num_genes:4, range:Benchmark::Schwefel.bounds(), mutation_rate, ..Config::default()};
+        GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), Benchmark::Schwefel.fitness(), config)
+    }).collect();
+    let island_config = IslandConfig{topology:MigrationTopology::FullyConnected, parallel:true, ..IslandConfig::default()};
+    let mut model = Islands::new(islands, island_config);
+
+    let hist = model.evolve(100);
+    println!("Best score = {}", hist[hist.len() - 1]);
+    draw_fitness(hist, "fitness_curve.png");
+}
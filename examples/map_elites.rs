@@ -0,0 +1,46 @@
+use std::f64::consts::PI;
+use rs_genetics::map_elites::{Archive, MapElites, MapElitesConfig};
+use rs_genetics::population::{Config, InitializationStrategy, Population, RandomInitialization};
+
+fn main() {
+    //planar robotic arm with 10 joints of total length 1, genes are the joint angles
+    fn angles(individual: Population) -> Vec<f64> {
+        match individual {
+            Population::F64(vec) => vec[0].clone(),
+            _ => panic!("Expected Population::F64"),
+        }
+    }
+    //prefer smooth postures: opposite of the variance of the angles
+    fn fitness(individual: Population) -> f64 {
+        let angles = angles(individual);
+        let mean = angles.iter().sum::<f64>() / angles.len() as f64;
+        -angles.iter().map(|a| (a - mean).powi(2)).sum::<f64>() / angles.len() as f64
+    }
+    //behavior: position of the end effector
+    fn descriptor(individual: Population) -> Vec<f64> {
+        let angles = angles(individual);
+        let length = 1.0 / angles.len() as f64;
+        let (mut x, mut y, mut angle) = (0.0, 0.0, 0.0);
+        for a in angles {
+            angle += a;
+            x += length * angle.cos();
+            y += length * angle.sin();
+        }
+        vec![x, y]
+    }
+
+    let config = Config{num_individuals:100, num_genes:10, range:-PI..PI, ..Config::default()};
+    let ranges = vec![-1.0..1.0, -1.0..1.0];
+
+    let mut grid = MapElites::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, descriptor,
+        config.clone(), Archive::grid(vec![50, 50], ranges.clone()), MapElitesConfig::default());
+    grid.evolve(500);
+    println!("Grid archive: coverage = {}, QD-score = {}", grid.coverage(), grid.qd_score(-PI * PI));
+    grid.draw("map_elites_grid.png");
+
+    let mut cvt = MapElites::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, descriptor,
+        config, Archive::cvt(1000, ranges, 20000), MapElitesConfig::default());
+    cvt.evolve(500);
+    println!("CVT archive: coverage = {}, QD-score = {}", cvt.coverage(), cvt.qd_score(-PI * PI));
+    cvt.draw("map_elites_cvt.png");
+}
@@ -0,0 +1,25 @@
+use rs_genetics::memetic::{HillClimbing, Learning, LocalSearchStrategy};
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::{Config, GA, InitializationStrategy, RandomInitialization};
+use rs_genetics::problems::continuous::{Benchmark, Problem};
+
+fn main() {
+    //Rosenbrock function, global optimum 0 at (1,...,1)
+    let init_strategy = InitializationStrategy::F64(Box::new(RandomInitialization));
+    let config = Config{
+        num_individuals:100,
+        num_genes:4,
+        range:-2.0..2.0,
+        local_search_rate:0.2,
+        local_search_budget:100,
+        learning:Learning::Lamarckian,
+        ..Config::default()
+    };
+    let mut ga = GA::new(init_strategy, Benchmark::Rosenbrock.fitness(), config)
+        .with_local_search(LocalSearchStrategy::F64(Box::new(HillClimbing{step_size:0.05})));
+
+    let hist = ga.evolve(100);
+    let (solution, score):(Vec<f64>,f64) = ga.get_individual(0).unwrap();
+    println!("Solution = {:?} with fitness {}", solution, score);
+    draw_fitness(hist, "fitness_curve.png");
+}
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, RandomInitialization};
+use rs_genetics::problems::multi_objective::{igd, non_dominated, MOBenchmark, MultiObjective};
+
+fn main() {
+    //ZDT1 has a convex front, so every Pareto optimal solution minimizes some weighted sum of the objectives
+    let problem = MultiObjective::new(MOBenchmark::ZDT1, 2);
+    let reference = problem.pareto_front(100);
+    let objectives = Arc::new(problem.objectives());
+    let mut solutions = Vec::new();
+    for i in 0..=10 {
+        let weight = i as f64 / 10.0;
+        let scalarized = objectives.clone();
+        let fitness = move |individual: Population| {
+            let f = scalarized(individual);
+            -(weight * f[0] + (1.0 - weight) * f[1])
+        };
+        let config = Config{num_individuals:100, num_genes:30, range:0.0..1.0, mutation_rate:0.02, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config);
+        ga.evolve(500);
+        solutions.push(objectives(ga.population().select(&[0])));
+    }
+    let front = non_dominated(&solutions);
+    println!("Front = {:?}", front);
+    println!("IGD = {}", igd(&front, &reference));
+}
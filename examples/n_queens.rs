@@ -1,10 +1,10 @@
 use rs_genetics::plot::draw_fitness;
-use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization, GetPopulation, Population};
+use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization, Population};
 fn main() {
     fn calculate_fitness(weights: Population) -> f64 {
         match weights {
             Population::Usize(vec)=>{
-            if let Some(individual) = vec.get(0) {
+            if let Some(individual) = vec.first() {
                 let mut fitness = 0;
                 for i in 0..individual.len() {
                     for j in i + 1..individual.len() {
@@ -25,12 +25,10 @@ fn main() {
     }
     let num_queens = 9;
     let init_strategy = InitializationStrategy::Usize(Box::new(TSPInitialization));
-    let mut config = Config::default();
-    config.num_individuals=100;
-    config.num_genes = num_queens;
+    let config = Config{num_individuals:100, num_genes:num_queens, ..Config::default()};
     let mut ga = GA::new(init_strategy, calculate_fitness, config);
     let hist = ga.evolve(100);
-    let solution:Vec<usize> = ga.population.get_individual(0).unwrap();
-    println!("Solution = {:?}",solution);
+    let (solution, score):(Vec<usize>,f64) = ga.get_individual(0).unwrap();
+    println!("Solution = {:?} with fitness {}",solution, score);
     draw_fitness(hist, "fitness_curve.png");
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use rs_genetics::neat::{NEAT, NEATConfig, Network};
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::Config;
+
+fn main() {
+    //XOR: fitness is 4 minus the squared error over the truth table
+    fn fitness(network: &Network) -> f64 {
+        let cases = [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)];
+        4.0 - cases.iter().map(|(inputs, target)| (network.activate(inputs)[0] - target).powi(2)).sum::<f64>()
+    }
+
+    let config = Config{num_individuals:150, ..Config::default()};
+    let neat_config = NEATConfig{num_inputs:2, num_outputs:1, ..NEATConfig::default()};
+    let mut neat = NEAT::new(fitness, config, neat_config);
+
+    let hist = neat.evolve(150);
+    let (genome, score) = neat.best().unwrap();
+    let network = genome.network();
+    for inputs in [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]] {
+        println!("{:?} -> {:.3}", inputs, network.activate(&inputs)[0]);
+    }
+    println!("Fitness = {} with {} hidden nodes", score, genome.nodes.len() - 4);
+    draw_fitness(hist, "fitness_curve.png");
+}
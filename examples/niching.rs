@@ -0,0 +1,32 @@
+use rs_genetics::niching::{Distance, Niching};
+use rs_genetics::population::{Config, GA, GetPopulation, InitializationStrategy, Population, RandomInitialization};
+
+fn main() {
+    //Himmelblau function, four global optima (fitness kept positive for fitness sharing)
+    fn fitness(weights: Population) -> f64 {
+        match weights {
+            Population::F64(vec) => {
+                let (x, y) = (vec[0][0], vec[0][1]);
+                1.0 / (1.0 + (x * x + y - 11.0).powi(2) + (x + y * y - 7.0).powi(2))
+            }
+            _ => panic!("Expected Population::F64"),
+        }
+    }
+
+    let init_strategy = InitializationStrategy::F64(Box::new(RandomInitialization));
+    let config = Config{
+        num_individuals:200,
+        num_genes:2,
+        range:-5.0..5.0,
+        niching:Some(Niching::DeterministicCrowding{distance:Distance::Euclidean}),
+        ..Config::default()
+    };
+    let mut ga = GA::new(init_strategy, fitness, config);
+
+    ga.evolve(100);
+    let niches = ga.niches(1.0, &Distance::Euclidean);
+    for i in 0..niches.len().min(4) {
+        let optimum:Vec<f64> = niches.get_individual(i).unwrap();
+        println!("Optimum {} = {:?}", i, optimum);
+    }
+}
@@ -0,0 +1,32 @@
+use rs_genetics::novelty::Novelty;
+use rs_genetics::population::{Config, GA, InitializationStrategy, Population, RandomInitialization};
+
+fn main() {
+    //mean of the genes
+    fn mean(individual: Population) -> f64 {
+        match individual {
+            Population::F64(vec) => vec[0].iter().sum::<f64>() / vec[0].len() as f64,
+            _ => panic!("Expected Population::F64"),
+        }
+    }
+    //deceptive trap: fitness increases towards a mean of 0 (local optimum 0.8) but the global optimum 1 is at a mean of 1
+    fn fitness(individual: Population) -> f64 {
+        let u = mean(individual);
+        if u < 0.8 {0.8 - u} else {5.0 * (u - 0.8)}
+    }
+
+    let config = Config{num_individuals:100, num_genes:2, range:0.0..1.0, mutation_rate:0.05, ..Config::default()};
+
+    let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config.clone());
+    let objective = ga.evolve(200);
+
+    let mut novelty = Novelty::new(|individual| vec![mean(individual)]);
+    novelty.fitness_weight = 0.3;
+    let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config)
+        .with_novelty(novelty);
+    let novel = ga.evolve(200);
+
+    println!("Best fitness with objective search = {}", objective.iter().cloned().reduce(f64::max).unwrap());
+    println!("Best fitness with novelty search = {}", novel.iter().cloned().reduce(f64::max).unwrap());
+    println!("Archive size = {}", ga.novelty().unwrap().archive.len());
+}
@@ -0,0 +1,20 @@
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::Config;
+use rs_genetics::problems::continuous::{Benchmark, Problem};
+use rs_genetics::pso::{PSO, PSOConfig, Topology, VelocityUpdate};
+
+fn main() {
+    //Rastrigin function, global optimum 0 at the origin
+    let config = Config{num_individuals:50, num_genes:5, range:Benchmark::Rastrigin.bounds(), ..Config::default()};
+    let pso_config = PSOConfig{
+        topology:Topology::Ring(1),
+        velocity_update:VelocityUpdate::Constriction{cognitive:2.05, social:2.05},
+        max_velocity:Some(1.0),
+    };
+    let mut pso = PSO::new(Benchmark::Rastrigin.fitness(), config, pso_config);
+
+    let hist = pso.evolve(200);
+    let (solution, score) = pso.best();
+    println!("Solution = {:?} with fitness {}", solution, score);
+    draw_fitness(hist, "fitness_curve.png");
+}
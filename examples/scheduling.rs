@@ -0,0 +1,39 @@
+use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization};
+use rs_genetics::problems::scheduling::{FlowShop, JobShop, Objective, ScheduleBuilder};
+
+fn main() {
+    //ft06 (Fisher and Thompson) in OR-Library format, optimal makespan 55; use JobShop::from_orlib or from_taillard to load files
+    let ft06 = JobShop::parse_orlib(" instance ft06\n Fisher and Thompson 6x6 instance, alternate name (mt06)\n 6 6\n\
+        2 1 0 3 1 6 3 7 5 3 4 6\n1 8 2 5 4 10 5 10 0 10 3 4\n2 5 3 4 5 8 0 9 1 1 4 7\n\
+        1 5 0 5 2 5 3 3 4 8 5 9\n2 9 1 3 4 5 5 4 0 3 3 1\n1 3 3 3 5 9 0 10 4 4 2 1\n").unwrap().remove(0);
+    for builder in [ScheduleBuilder::SemiActive, ScheduleBuilder::Active] {
+        let config = Config{num_individuals:100, num_genes:ft06.num_operations(), mutation_rate:0.2, ..Config::default()};
+        let mut ga = GA::new(ft06.initialization(), ft06.clone().fitness(builder, Objective::Makespan), config);
+        ga.evolve(200);
+        let (solution, _) = ga.get_individual::<usize>(0).unwrap();
+        let schedule = ft06.schedule(&solution, builder);
+        println!("{} {:?}: makespan = {} (optimum 55)", ft06.name, builder, schedule.makespan());
+        if builder == ScheduleBuilder::Active {
+            schedule.draw("gantt_chart.png");
+        }
+    }
+
+    //same instance with due dates at 1.5 times the work content of each job
+    let ft06 = ft06.with_due_dates(1.5);
+    let config = Config{num_individuals:100, num_genes:ft06.num_operations(), mutation_rate:0.2, ..Config::default()};
+    let mut ga = GA::new(ft06.initialization(), ft06.clone().fitness(ScheduleBuilder::Active, Objective::TotalTardiness), config);
+    ga.evolve(200);
+    let (solution, _) = ga.get_individual::<usize>(0).unwrap();
+    let schedule = ft06.schedule(&solution, ScheduleBuilder::Active);
+    println!("{} total tardiness = {}, total flow time = {}", ft06.name, schedule.objective(Objective::TotalTardiness, ft06.due_dates.as_deref()),
+        schedule.objective(Objective::TotalFlowTime, None));
+
+    //small permutation flow shop in Taillard format (jobs machines seed upper_bound lower_bound, one row per machine)
+    let flow_shop = FlowShop::parse_taillard("number of jobs, number of machines, initial seed, upper bound and lower bound :\n\
+        8 3 0 0 0\nprocessing times :\n5 9 8 10 1 3 7 6\n9 3 5 4 8 6 2 9\n8 10 9 3 8 7 4 5\n").unwrap().remove(0);
+    let config = Config{num_individuals:100, num_genes:flow_shop.num_jobs(), ..Config::default()};
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), flow_shop.clone().fitness(Objective::Makespan), config);
+    ga.evolve(100);
+    let (solution, _) = ga.get_individual::<usize>(0).unwrap();
+    println!("{}: order = {:?}, makespan = {}", flow_shop.name, solution, flow_shop.schedule(&solution).makespan());
+}
@@ -1,5 +1,5 @@
 use rs_genetics::plot::draw_fitness;
-use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization, GetPopulation, Population, SudokuInitialization};
+use rs_genetics::population::{Config, GA, InitializationStrategy, GetPopulation, LockedGenes, Population, SudokuInitialization};
 fn main() {
     fn calculate_fitness(weights:Population) -> f64 {
         match weights {
@@ -32,7 +32,7 @@ fn main() {
                 }
                 // Penalize invalid numbers (outside 1-9)
                 for &value in vec[0].iter() {
-                    if value < 1 || value > 9 {
+                    if !(1..=9).contains(&value) {
                         score -= 5.0; // Arbitrary penalty
                     }
                 }
@@ -44,15 +44,31 @@ fn main() {
             _ => panic!("Expected Population::usize")
         }
     }
+    // Givens of the puzzle (0 for empty cells) are locked so that operators never change them
+    let puzzle:[usize;81] = [
+        5,3,0, 0,7,0, 0,0,0,
+        6,0,0, 1,9,5, 0,0,0,
+        0,9,8, 0,0,0, 0,6,0,
+        8,0,0, 0,6,0, 0,0,3,
+        4,0,0, 8,0,3, 0,0,1,
+        7,0,0, 0,2,0, 0,0,6,
+        0,6,0, 0,0,0, 2,8,0,
+        0,0,0, 4,1,9, 0,0,5,
+        0,0,0, 0,8,0, 0,7,9,
+    ];
+    let givens = puzzle.iter().map(|&value| if value != 0 {Some(value)} else {None}).collect();
     let init_strategy = InitializationStrategy::Usize(Box::new(SudokuInitialization));
-    let mut config = Config::default();
-    config.num_individuals=100;
-    config.num_genes = 81;
+    let config = Config{
+        num_individuals:100,
+        num_genes:81,
+        locked:Some(LockedGenes::Usize(givens)),
+        ..Config::default()
+    };
     let mut ga = GA::new(init_strategy, calculate_fitness, config);
     ga.inspect();
     //ga.evaluate();
     let hist = ga.evolve(100);
-    let solution:Vec<usize> = ga.population.get_individual(0).unwrap();
+    let solution:Vec<usize> = ga.population().get_individual(0).unwrap();
     println!("Solution = {:?}",solution);
     draw_fitness(hist, "fitness_curve.png");
 }
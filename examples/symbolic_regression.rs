@@ -0,0 +1,26 @@
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::Config;
+use rs_genetics::symbolic_regression::{Dataset, RegressionConfig, SymbolicRegression};
+
+fn main() {
+    //discover y = x0^2 + 2*x1 - 1 from samples (a CSV file can be loaded with Dataset::from_csv)
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for i in -5..=5 {
+        for j in -5..=5 {
+            let (x0, x1) = (i as f64 / 2.0, j as f64 / 2.0);
+            inputs.push(vec![x0, x1]);
+            targets.push(x0 * x0 + 2.0 * x1 - 1.0);
+        }
+    }
+    let dataset = Dataset::new(inputs, targets);
+    let config = Config{num_individuals:500, mutation_rate:0.2, local_search_rate:0.05, ..Config::default()};
+    let mut regression_config = RegressionConfig{constants:Some(-5.0..5.0), tune_constants:true, ..RegressionConfig::default()};
+    regression_config.gp_config.max_size = 40;
+    let mut regression = SymbolicRegression::new(dataset, config, regression_config);
+
+    let hist = regression.evolve(100);
+    let best = regression.best();
+    println!("Solution = {} (MSE = {})", regression.expression(), regression.loss(&best));
+    draw_fitness(hist, "fitness_curve.png");
+}
@@ -1,36 +1,28 @@
 use rs_genetics::plot::draw_fitness;
-use rs_genetics::population::{Config, GA, GetPopulation, InitializationStrategy, Population, TSPInitialization};
+use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization};
+use rs_genetics::problems::tsp::{parse_tour, EdgeWeightType, TSP};
 
 fn main() {
-    struct City{x:f64,y:f64,}
-    fn distance_(city1: &City, city2: &City) -> f64 {
-        ((city1.x - city2.x).powi(2) + (city1.y - city2.y).powi(2)).sqrt()
-    }
-    fn total_distance(weights: Population) -> f64 {
-        //solving for 5-points star
-        //historical europe tour: Rome, Paris, Athens, Berlin, London, Vienna
-        let cities = vec![City{x:41.9028,y:12.4964},City{x:48.8566,y:2.3522},City{x:37.9838,y:23.7275},City{x:52.5200,y:13.4050},City{x:51.5074,y:-0.1278},City{x:48.2082,y:16.3738}];
-        let mut distance = 0.0;
-        match weights {
-            Population::Usize(vec)=>{
-            for i in 0..cities.len() - 1 {
-                distance += distance_(&cities[vec[0][i]], &cities[vec[0][i + 1]]);
-            }
-            distance += distance_(&cities[cities.len() - 1], &cities[0]);
-            distance
-            }
-            _ => panic!("Expected Population::usize")
-        }
-    }
+    //historical europe tour: Rome, Paris, Athens, Berlin, London, Vienna
+    let cities = [(41.9028, 12.4964), (48.8566, 2.3522), (37.9838, 23.7275), (52.5200, 13.4050), (51.5074, -0.1278), (48.2082, 16.3738)];
+    let europe = TSP::from_coordinates(cities.to_vec(), EdgeWeightType::Euclidean);
+    let config = Config{num_individuals:100, num_genes:europe.num_cities(), ..Config::default()};
+    //only 720 tours exist, most individuals are evaluated from the cache
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), europe.clone().fitness(), config).with_cache(1000);
+    ga.evolve(100);
+    let (solution, score) = ga.get_individual::<usize>(0).unwrap();
+    println!("Solution = {:?} with length {}", solution, europe.tour_length(&solution));
+    println!("Fitness = {}, cache = {:?}", score, ga.cache_stats().unwrap());
 
-    let init_strategy = InitializationStrategy::Usize(Box::new(TSPInitialization));
-    let mut config = Config::default();
-    config.num_individuals=100;
-    config.num_genes = 6;
-    let mut ga = GA::new(init_strategy, total_distance, config);
-
-    let hist = ga.evolve(100);
-    let solution:Vec<usize> = ga.population.get_individual(0).unwrap();
-    println!("Solution = {:?}",solution);
+    //burma14 from TSPLIB, use TSP::from_tsplib and read_tour to load instances from files
+    let burma14 = TSP::parse_tsplib("NAME: burma14\nTYPE: TSP\nDIMENSION: 14\nEDGE_WEIGHT_TYPE: GEO\nNODE_COORD_SECTION\n\
+        1 16.47 96.10\n2 16.47 94.44\n3 20.09 92.54\n4 22.39 93.37\n5 25.23 97.24\n6 22.00 96.05\n7 20.47 97.02\n\
+        8 17.20 96.29\n9 16.30 97.38\n10 14.05 98.12\n11 16.53 97.38\n12 21.52 95.59\n13 19.41 97.13\n14 20.09 94.55\nEOF\n").unwrap();
+    let optimal = parse_tour("NAME: burma14.opt.tour\nTYPE: TOUR\nDIMENSION: 14\nTOUR_SECTION\n1 2 14 3 4 5 6 12 7 13 8 11 9 10\n-1\nEOF\n").unwrap();
+    let config = Config{num_individuals:200, num_genes:burma14.num_cities(), mutation_rate:0.2, ..Config::default()};
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), burma14.clone().fitness(), config);
+    let hist = ga.evolve(300);
+    let (solution, _) = ga.get_individual::<usize>(0).unwrap();
+    println!("{}: length = {}, optimal = {}, gap = {:.2}%", burma14.name, burma14.tour_length(&solution), burma14.tour_length(&optimal), 100.0 * burma14.gap(&solution, &optimal));
     draw_fitness(hist, "fitness_curve.png");
-}
\ No newline at end of file
+}
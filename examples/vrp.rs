@@ -0,0 +1,48 @@
+use rs_genetics::plot::draw_fitness;
+use rs_genetics::population::{Config, GA, InitializationStrategy, TSPInitialization};
+use rs_genetics::problems::tsp::{EdgeWeightType, TSP};
+use rs_genetics::problems::vrp::VRP;
+
+fn main() {
+    //40 customers on 4 rings around the depot, use VRP::from_cvrplib or VRP::from_solomon to load instances from files
+    let coordinates:Vec<(f64,f64)> = [(50.0, 50.0)].into_iter().chain((0..40).map(|i| {
+        let angle = i as f64 * 2.0 * std::f64::consts::PI / 10.0;
+        let radius = 10.0 + 10.0 * (i / 10) as f64;
+        (50.0 + radius * angle.cos(), 50.0 + radius * angle.sin())
+    })).collect();
+    let demands = [0.0].into_iter().chain((0..40).map(|i| (1 + i % 7) as f64)).collect();
+    let mut vrp = VRP::new(TSP::from_coordinates(coordinates, EdgeWeightType::Euc2D), demands, 30.0);
+    vrp.name = "rings-n41".to_string();
+
+    let config = Config{num_individuals:100, num_genes:vrp.num_customers(), mutation_rate:0.3, ..Config::default()};
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), vrp.clone().fitness(), config.clone());
+    let builtin = ga.evolve(200);
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), vrp.clone().fitness(), config.clone())
+        .with_variation(vrp.clone().operators());
+    ga.evolve(200);
+    let (tour, _) = ga.get_individual::<usize>(0).unwrap();
+    let solution = vrp.split(&tour);
+    println!("Built-in operators: distance = {}", -builtin[builtin.len() - 1]);
+    println!("Route-aware operators: distance = {}", solution.distance);
+    for route in &solution.routes {
+        println!("Route {:?} load = {}", route, vrp.load(route));
+    }
+
+    //the same customers with time windows: outer rings must be served late, inner rings early
+    let mut vrptw = vrp.clone();
+    vrptw.time_windows = Some((0..=40).map(|node| match node {
+        0 => (0.0, 1000.0),
+        _ => {
+            let ring = ((node - 1) / 10) as f64;
+            (ring * 100.0, ring * 100.0 + 150.0)
+        }
+    }).collect());
+    vrptw.service_times = (0..=40).map(|node| if node == 0 {0.0} else {10.0}).collect();
+    let mut ga = GA::new(InitializationStrategy::Usize(Box::new(TSPInitialization)), vrptw.clone().fitness(), config)
+        .with_variation(vrptw.clone().operators());
+    let hist = ga.evolve(200);
+    let (tour, _) = ga.get_individual::<usize>(0).unwrap();
+    let solution = vrptw.split(&tour);
+    println!("VRPTW: {} routes, distance = {}, lateness = {}", solution.routes.len(), solution.distance, solution.lateness);
+    draw_fitness(hist, "fitness_curve.png");
+}
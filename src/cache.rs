@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use crate::gp::{Node, PrimitiveSet};
+use crate::population::Population;
+
+///exact copy of the genome of an individual used as cache key (f64 genes are stored by their bits)<BR>
+/// trees are equal only if they have the same nodes and share the same primitive set
+#[derive(Clone, Debug)]
+pub enum Genome{
+    Usize(Vec<usize>),
+    F64(Vec<u64>),
+    Tree(Vec<Node>, Arc<PrimitiveSet>),
+}
+impl Genome{
+    ///genome of individual index of a population
+    pub fn of(population:&Population, index:usize)->Genome{
+        match population {
+            Population::Usize(vec) => Genome::Usize(vec[index].clone()),
+            Population::F64(vec) => Genome::F64(vec[index].iter().map(|gene| gene.to_bits()).collect()),
+            Population::Tree(vec) => Genome::Tree(vec[index].nodes.clone(), vec[index].set.clone()),
+        }
+    }
+}
+///nodes compared by their bits so that equality is consistent with Hash
+fn node_key(node:&Node)->(u8,u64,usize){
+    match *node {
+        Node::Function(f) => (0, f as u64, 0),
+        Node::Terminal(t) => (1, t as u64, 0),
+        Node::Constant(value, ret) => (2, value.to_bits(), ret),
+    }
+}
+impl PartialEq for Genome{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Genome::Usize(a), Genome::Usize(b)) => a == b,
+            (Genome::F64(a), Genome::F64(b)) => a == b,
+            (Genome::Tree(a, set_a), Genome::Tree(b, set_b)) => Arc::ptr_eq(set_a, set_b) && a.len() == b.len() && a.iter().zip(b).all(|(x, y)| node_key(x) == node_key(y)),
+            _ => false,
+        }
+    }
+}
+impl Eq for Genome{}
+impl Hash for Genome{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Genome::Usize(genes) => (0u8, genes).hash(state),
+            Genome::F64(genes) => (1u8, genes).hash(state),
+            Genome::Tree(nodes, set) => {
+                (2u8, Arc::as_ptr(set)).hash(state);
+                nodes.iter().for_each(|node| node_key(node).hash(state));
+            }
+        }
+    }
+}
+
+///hit statistics of a fitness cache
+#[derive(Clone, Copy, Debug)]
+pub struct CacheStats{
+    pub hits:usize,
+    pub misses:usize,
+    ///number of cached genomes
+    pub len:usize,
+}
+impl CacheStats{
+    ///fraction of lookups answered by the cache
+    pub fn hit_rate(&self)->f64{
+        self.hits as f64 / (self.hits + self.misses).max(1) as f64
+    }
+}
+
+///fitness (or any evaluation V, e.g. fitness and constraint violations) of already evaluated genomes,
+/// the least recently used entry is evicted when full
+pub struct FitnessCache<V=f64>{
+    capacity:usize,
+    ///fitness and last use of each genome
+    entries:HashMap<Genome,(V,u64)>,
+    ///genomes by last use
+    order:BTreeMap<u64,Genome>,
+    clock:u64,
+    hits:usize,
+    misses:usize,
+}
+impl<V:Clone> FitnessCache<V>{
+    pub fn new(capacity:usize) -> Self{
+        FitnessCache{capacity, entries:HashMap::with_capacity(capacity), order:BTreeMap::new(), clock:0, hits:0, misses:0}
+    }
+    ///cached fitness of a genome, counted as a hit or a miss
+    pub fn get(&mut self, genome:&Genome)->Option<V>{
+        self.clock += 1;
+        match self.entries.get_mut(genome) {
+            Some((fitness, used)) => {
+                let genome = self.order.remove(used).expect("Cached genome missing from use order");
+                *used = self.clock;
+                self.order.insert(self.clock, genome);
+                self.hits += 1;
+                Some(fitness.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+    ///store the fitness of a genome, evicting the least recently used one if the cache is full
+    pub fn insert(&mut self, genome:Genome, fitness:V){
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        if let Some((_, used)) = self.entries.insert(genome.clone(), (fitness, self.clock)) {
+            self.order.remove(&used);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.insert(self.clock, genome);
+    }
+    pub fn stats(&self)->CacheStats{
+        CacheStats{hits:self.hits, misses:self.misses, len:self.entries.len()}
+    }
+    ///remove all entries and reset statistics
+    pub fn clear(&mut self){
+        *self = FitnessCache::new(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::gp::{Terminal, Tree};
+
+    #[test]
+    fn distinct_genomes_never_share_an_entry(){
+        let mut cache = FitnessCache::new(10);
+        let population = Population::F64(vec![vec![0.0, 1.0], vec![-0.0, 1.0]]);
+        cache.insert(Genome::of(&population, 0), 1.0);
+        assert_eq!(cache.get(&Genome::of(&population, 0)), Some(1.0));
+        assert_eq!(cache.get(&Genome::of(&population, 1)), None);
+        assert_eq!(cache.get(&Genome::Usize(vec![0, 1])), None);
+    }
+
+    #[test]
+    fn trees_of_different_primitive_sets_are_distinct(){
+        let set = || Arc::new(PrimitiveSet::new(Vec::new(), vec![Terminal::variable("x", 0)]));
+        let (set1, set2) = (set(), set());
+        let population = Population::Tree(vec![Tree::new(vec![Node::Terminal(0)], set1.clone()), Tree::new(vec![Node::Terminal(0)], set2)]);
+        let mut cache = FitnessCache::new(10);
+        cache.insert(Genome::of(&population, 0), 1.0);
+        assert_eq!(cache.get(&Genome::Tree(vec![Node::Terminal(0)], set1)), Some(1.0));
+        assert_eq!(cache.get(&Genome::of(&population, 1)), None);
+    }
+
+    #[test]
+    fn least_recently_used_genome_is_evicted(){
+        let mut cache = FitnessCache::new(2);
+        cache.insert(Genome::Usize(vec![0]), 0.0);
+        cache.insert(Genome::Usize(vec![1]), 1.0);
+        assert_eq!(cache.get(&Genome::Usize(vec![0])), Some(0.0));
+        cache.insert(Genome::Usize(vec![2]), 2.0);
+        assert_eq!(cache.get(&Genome::Usize(vec![1])), None);
+        assert_eq!(cache.get(&Genome::Usize(vec![0])), Some(0.0));
+        assert_eq!(cache.stats().len, 2);
+    }
+}
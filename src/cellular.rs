@@ -0,0 +1,255 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::time::Instant;
+use crate::population::{Config, InitializationStrategy, Mutation, Population};
+
+///cells a grid position can mate with
+#[derive(Clone, Debug)]
+pub enum Neighborhood{
+    ///4 nearest cells (north, south, east, west)
+    VonNeumann,
+    ///8 surrounding cells
+    Moore,
+    ///all cells within the given Manhattan distance
+    Radius(usize),
+}
+///how the grid is updated during a step
+#[derive(Clone, Debug)]
+pub enum UpdateMode{
+    ///all children are computed from the current grid and then replace it at once
+    Synchronous,
+    ///cells are visited in a new random order every step and updated in place
+    Asynchronous,
+}
+///struct used to configure the cellular GA<BR>
+/// the grid replaces Config::num_individuals with width*height individuals
+#[derive(Clone, Debug)]
+pub struct CellularConfig{
+    pub width:usize,
+    pub height:usize,
+    pub neighborhood:Neighborhood,
+    pub update:UpdateMode,
+}
+///default cellular configuration
+impl Default for CellularConfig{
+    fn default() -> Self {
+        CellularConfig{
+            width:10,
+            height:10,
+            neighborhood:Neighborhood::VonNeumann,
+            update:UpdateMode::Synchronous,
+        }
+    }
+}
+
+///cellular (diffusion) GA: individuals live on a toroidal grid and only mate with their neighbours
+pub struct CellularGA<F>
+where F:Fn(Population)->f64{
+    ///individuals stored row by row (index = row*width + column)
+    pub population: Population,
+    scores: Vec<f64>,
+    fitness:F,
+    config: Config,
+    cellular_config: CellularConfig,
+}
+impl<F> CellularGA<F>
+where F:Fn(Population)->f64{
+    ///initialize the grid based on an initialization strategy and a fitness function
+    pub fn new(initialization:InitializationStrategy, fitness:F, config: Config, cellular_config: CellularConfig) -> Self{
+        let config = Config{num_individuals:cellular_config.width*cellular_config.height, ..config};
+        let population = initialization.initialize(config.clone());
+        let scores = (0..population.len()).map(|i| fitness(population.select(&[i]))).collect();
+        CellularGA{population, scores, fitness, config, cellular_config}
+    }
+    pub fn width(&self)->usize{
+        self.cellular_config.width
+    }
+    pub fn height(&self)->usize{
+        self.cellular_config.height
+    }
+    ///fitness of every cell, as rows of the grid
+    pub fn fitness_grid(&self)->Vec<Vec<f64>>{
+        self.scores.chunks(self.width()).map(|row| row.to_vec()).collect()
+    }
+    ///average fraction of genes differing between each cell and its neighbours, as rows of the grid<BR>
+    /// useful to visualize how diversity spreads (see plot::draw_heatmap)
+    pub fn diversity_grid(&self)->Vec<Vec<f64>>{
+        let diversity:Vec<f64> = (0..self.population.len()).map(|cell| {
+            let neighbours = self.neighbours(cell);
+            neighbours.iter().map(|&other| match &self.population {
+                Population::Usize(vec) => differing_genes(&vec[cell], &vec[other]),
+                Population::F64(vec) => differing_genes(&vec[cell], &vec[other]),
+                Population::Tree(vec) => differing_genes(&vec[cell].nodes, &vec[other].nodes),
+            }).sum::<f64>() / neighbours.len().max(1) as f64
+        }).collect();
+        diversity.chunks(self.width()).map(|row| row.to_vec()).collect()
+    }
+    ///indices of the cells in the neighbourhood of a cell (the cell itself excluded)
+    pub fn neighbours(&self, cell:usize)->Vec<usize>{
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let (row, col) = (cell as isize / width, cell as isize % width);
+        let radius = match self.cellular_config.neighborhood {
+            Neighborhood::VonNeumann | Neighborhood::Moore => 1,
+            Neighborhood::Radius(r) => r as isize,
+        };
+        let mut neighbours = Vec::new();
+        for dr in -radius..=radius {
+            for dc in -radius..=radius {
+                let inside = match self.cellular_config.neighborhood {
+                    Neighborhood::Moore => true,
+                    _ => dr.abs() + dc.abs() <= radius,
+                };
+                if (dr, dc) != (0, 0) && inside {
+                    let index = ((row + dr).rem_euclid(height) * width + (col + dc).rem_euclid(width)) as usize;
+                    if index != cell && !neighbours.contains(&index) {
+                        neighbours.push(index);
+                    }
+                }
+            }
+        }
+        neighbours
+    }
+    ///produce the candidate child of a cell: the cell mates with the winner of a binary tournament among its neighbours
+    fn breed(&self, cell:usize)->(Population,f64){
+        let mut rng = rand::thread_rng();
+        let neighbours = self.neighbours(cell);
+        let mate = if neighbours.is_empty() {cell} else {
+            let (a, b) = (neighbours[rng.gen_range(0..neighbours.len())], neighbours[rng.gen_range(0..neighbours.len())]);
+            if self.scores[a] >= self.scores[b] {a} else {b}
+        };
+        let child = self.population.select(&[cell, mate]).mate_pairs().select(&[0]).mutate(&self.config);
+        let score = (self.fitness)(child.select(&[0]));
+        (child, score)
+    }
+    ///evolve the grid forward by one step<BR>
+    /// every cell breeds with a neighbour and is replaced by its child if the child is not worse<BR>
+    /// returns best score on the grid
+    pub fn step(&mut self)->f64{
+        let mut cells:Vec<usize> = (0..self.population.len()).collect();
+        match self.cellular_config.update {
+            UpdateMode::Synchronous => {
+                let children:Vec<(Population,f64)> = cells.iter().map(|&cell| self.breed(cell)).collect();
+                for (cell, (child, score)) in children.into_iter().enumerate() {
+                    if score >= self.scores[cell] {
+                        self.population.replace(cell, &child, 0);
+                        self.scores[cell] = score;
+                    }
+                }
+            }
+            UpdateMode::Asynchronous => {
+                cells.shuffle(&mut rand::thread_rng());
+                for cell in cells {
+                    let (child, score) = self.breed(cell);
+                    if score >= self.scores[cell] {
+                        self.population.replace(cell, &child, 0);
+                        self.scores[cell] = score;
+                    }
+                }
+            }
+        }
+        let best = self.scores.iter().cloned().reduce(f64::max).unwrap();
+        if self.config.verbose {
+            print!("Best score = {}",best);
+        }
+        best
+    }
+    ///return the best individual on the grid and its fitness
+    pub fn best(&self)->(Population,f64){
+        let (index, score) = self.scores.iter().enumerate().max_by(|a,b| a.1.partial_cmp(b.1).unwrap()).unwrap();
+        (self.population.select(&[index]), *score)
+    }
+    ///execute num_steps forward of evolution, return a vector of best scores (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        for i in 0..num_steps{
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+///fraction of positions holding different genes
+fn differing_genes<T:PartialEq>(a:&[T], b:&[T])->f64{
+    a.iter().zip(b).filter(|(x, y)| x != y).count() as f64 / a.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::{GetPopulation, Initialization, RandomInitialization};
+
+    ///the first cell holds 1.0, every other cell 0.0
+    struct SingleSeed;
+    impl Initialization<Vec<Vec<f64>>> for SingleSeed{
+        fn initialize(&self, config:Config)->Vec<Vec<f64>>{
+            (0..config.num_individuals).map(|i| vec![if i == 0 {1.0} else {0.0}]).collect()
+        }
+    }
+
+    fn gene(population:Population)->f64{
+        GetPopulation::<f64>::get_individual(&population, 0).unwrap()[0]
+    }
+
+    fn grid(initialization:InitializationStrategy, cellular_config:CellularConfig)->CellularGA<fn(Population)->f64>{
+        let config = Config{num_genes:1, mutation_rate:0.0, verbose:false, ..Config::default()};
+        CellularGA::new(initialization, gene as fn(Population)->f64, config, cellular_config)
+    }
+
+    fn neighbours(width:usize, height:usize, neighborhood:Neighborhood, cell:usize)->Vec<usize>{
+        let cellular_config = CellularConfig{width, height, neighborhood, ..CellularConfig::default()};
+        let mut neighbours = grid(InitializationStrategy::F64(Box::new(RandomInitialization)), cellular_config).neighbours(cell);
+        neighbours.sort_unstable();
+        neighbours
+    }
+
+    #[test]
+    fn von_neumann_and_moore_neighbourhoods(){
+        assert_eq!(neighbours(5, 5, Neighborhood::VonNeumann, 12), vec![7, 11, 13, 17]);
+        assert_eq!(neighbours(5, 5, Neighborhood::Moore, 12), vec![6, 7, 8, 11, 13, 16, 17, 18]);
+    }
+
+    #[test]
+    fn neighbourhoods_wrap_around_the_grid_edges(){
+        assert_eq!(neighbours(5, 5, Neighborhood::VonNeumann, 0), vec![1, 4, 5, 20]);
+        assert_eq!(neighbours(5, 5, Neighborhood::Moore, 0), vec![1, 4, 5, 6, 9, 20, 21, 24]);
+        assert_eq!(neighbours(5, 4, Neighborhood::VonNeumann, 19), vec![4, 14, 15, 18]);
+    }
+
+    #[test]
+    fn radius_neighbourhood_uses_manhattan_distance(){
+        let cells = neighbours(7, 7, Neighborhood::Radius(2), 24);
+        assert_eq!(cells, vec![10, 16, 17, 18, 22, 23, 25, 26, 30, 31, 32, 38]);
+        assert_eq!(neighbours(7, 7, Neighborhood::Radius(1), 24), neighbours(7, 7, Neighborhood::VonNeumann, 24));
+        //a radius larger than the grid reaches every other cell once
+        assert_eq!(neighbours(3, 3, Neighborhood::Radius(3), 4), vec![0, 1, 2, 3, 5, 6, 7, 8]);
+    }
+
+    ///distance along the ring of the farthest cell holding the seed after one step
+    fn spread(update:UpdateMode)->usize{
+        let cellular_config = CellularConfig{width:9, height:1, update, ..CellularConfig::default()};
+        let mut ring = grid(InitializationStrategy::F64(Box::new(SingleSeed)), cellular_config);
+        ring.step();
+        (0..9).filter(|&cell| gene(ring.population.select(&[cell])) == 1.0).map(|cell| cell.min(9 - cell)).max().unwrap()
+    }
+
+    #[test]
+    fn synchronous_update_breeds_from_the_previous_grid(){
+        //a one gene child is a copy of its mate, so the seed moves at most one cell per step
+        assert!((0..200).all(|_| spread(UpdateMode::Synchronous) <= 1));
+    }
+
+    #[test]
+    fn asynchronous_update_breeds_from_already_updated_cells(){
+        assert!((0..200).any(|_| spread(UpdateMode::Asynchronous) >= 2));
+    }
+}
@@ -0,0 +1,279 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::sync::Arc;
+use crate::gp::Tree;
+use crate::population::{Config, Evaluation, GA, InitializationStrategy, Population};
+
+///how constraint violations are combined with the objective to rank individuals<BR>
+/// an individual is feasible when all its violations are 0
+#[derive(Clone, Debug)]
+pub enum ConstraintHandling{
+    ///objective - weight * total violation
+    StaticPenalty{weight:f64},
+    ///objective - (c * generation)^alpha * sum of violation^beta, the penalty grows along the run
+    DynamicPenalty{c:f64, alpha:f64, beta:f64},
+    ///objective - lambda * total violation<BR>
+    /// lambda is multiplied by increase when the best individual was infeasible during the last window generations,
+    /// and divided by decrease when it was feasible during all of them (different factors avoid cycling)
+    AdaptivePenalty{initial:f64, increase:f64, decrease:f64, window:usize},
+    ///Deb's feasibility rules: feasible individuals rank above infeasible ones,
+    /// feasible individuals are compared by objective and infeasible ones by total violation
+    FeasibilityRules,
+    ///ε-constrained comparison: individuals whose total violation is below ε are compared by objective, others by violation<BR>
+    /// ε decreases from epsilon to 0 over control_generations as (1 - generation/control_generations)^exponent
+    EpsilonConstrained{epsilon:f64, control_generations:usize, exponent:f64},
+}
+
+///objective and constraint violations computed together from one evaluation
+type CombinedFitness=Arc<dyn Fn(Population)->(f64,Vec<f64>) + Send + Sync>;
+
+///constraint handling used by GA to rank individuals (see constrained_ga)
+pub struct Constraints{
+    ///violation of each constraint, 0 when satisfied
+    violations:Box<dyn Fn(Population)->Vec<f64> + Send + Sync>,
+    ///objective and violations computed by a single function, used instead of the GA fitness (see Constraints::combined)
+    combined:Option<CombinedFitness>,
+    pub handling:ConstraintHandling,
+    generation:usize,
+    ///current penalty weight of the adaptive penalty
+    lambda:f64,
+    ///feasibility of the best individual of the last generations (adaptive penalty)
+    best_feasible:Vec<bool>,
+}
+impl Constraints{
+    pub fn new<V>(violations:V, handling:ConstraintHandling) -> Self
+    where V:Fn(Population)->Vec<f64> + Send + Sync + 'static{
+        let lambda = match handling {
+            ConstraintHandling::AdaptivePenalty{initial, ..} => initial,
+            _ => 0.0,
+        };
+        Constraints{violations:Box::new(violations), combined:None, handling, generation:0, lambda, best_feasible:Vec::new()}
+    }
+    ///constraints computed along with the objective by a single function returning (objective, violations),
+    /// GA then evaluates each individual once with it instead of its own fitness
+    pub fn combined<C>(fitness:C, handling:ConstraintHandling) -> Self
+    where C:Fn(Population)->(f64,Vec<f64>) + Send + Sync + 'static{
+        let fitness = Arc::new(fitness);
+        let violations = fitness.clone();
+        Constraints{combined:Some(fitness), ..Constraints::new(move |individual| violations(individual).1, handling)}
+    }
+    ///objective and violations of individual index, the objective is computed by fitness unless the constraints are combined
+    pub fn evaluate<F>(&self, fitness:&F, population:&Population, index:usize)->Evaluation
+    where F:Fn(Population)->f64{
+        match &self.combined {
+            Some(combined) => {
+                let (fitness, violations) = combined(population.select(&[index]));
+                Evaluation{fitness, violations}
+            }
+            None => Evaluation{fitness:fitness(population.select(&[index])), violations:self.violations_of(population, index)},
+        }
+    }
+    ///violation of each constraint of individual index
+    pub fn violations_of(&self, population:&Population, index:usize)->Vec<f64>{
+        (self.violations)(population.select(&[index]))
+    }
+    ///total violation of each individual (negative violations count as satisfied)
+    pub fn violation(&self, population:&Population)->Vec<f64>{
+        (0..population.len()).map(|i| total_violation(&self.violations_of(population, i))).collect()
+    }
+    ///advance to the next generation, updating the adaptive penalty from the ranking of the evaluated population
+    pub fn next_generation(&mut self, evaluations:&[&Evaluation]){
+        self.generation += 1;
+        if let ConstraintHandling::AdaptivePenalty{increase, decrease, window, ..} = self.handling {
+            let scores = self.score(evaluations);
+            if let Some(best) = (0..scores.len()).max_by(|&a, &b| scores[a].partial_cmp(&scores[b]).unwrap()) {
+                self.best_feasible.push(total_violation(&evaluations[best].violations) <= 0.0);
+            }
+            if self.best_feasible.len() >= window {
+                let recent = &self.best_feasible[self.best_feasible.len() - window..];
+                if recent.iter().all(|&feasible| feasible) {
+                    self.lambda /= decrease;
+                    self.best_feasible.clear();
+                } else if recent.iter().all(|&feasible| !feasible) {
+                    self.lambda *= increase;
+                    self.best_feasible.clear();
+                }
+            }
+        }
+    }
+    ///score used to rank a population given the objective and violations of each individual
+    pub fn score(&self, evaluations:&[&Evaluation])->Vec<f64>{
+        let evals:Vec<f64> = evaluations.iter().map(|evaluation| evaluation.fitness).collect();
+        let violation:Vec<f64> = evaluations.iter().map(|evaluation| total_violation(&evaluation.violations)).collect();
+        match self.handling {
+            ConstraintHandling::StaticPenalty{weight} => evals.iter().zip(&violation).map(|(f, v)| f - weight * v).collect(),
+            ConstraintHandling::DynamicPenalty{c, alpha, beta} => {
+                let weight = (c * self.generation.max(1) as f64).powf(alpha);
+                evaluations.iter().map(|evaluation| {
+                    let penalty:f64 = evaluation.violations.iter().map(|v| v.max(0.0).powf(beta)).sum();
+                    evaluation.fitness - weight * penalty
+                }).collect()
+            }
+            ConstraintHandling::AdaptivePenalty{..} => evals.iter().zip(&violation).map(|(f, v)| f - self.lambda * v).collect(),
+            ConstraintHandling::FeasibilityRules => {
+                //infeasible individuals score below the worst objective, by their violation
+                let worst = evals.iter().cloned().filter(|f| f.is_finite()).reduce(f64::min).unwrap_or(0.0);
+                evals.iter().zip(&violation).map(|(&f, &v)| if v <= 0.0 {f} else {worst - v}).collect()
+            }
+            ConstraintHandling::EpsilonConstrained{epsilon, control_generations, exponent} => {
+                let epsilon = if self.generation < control_generations {
+                    epsilon * (1.0 - self.generation as f64 / control_generations as f64).powf(exponent)
+                } else {0.0};
+                let mut order:Vec<usize> = (0..evals.len()).collect();
+                order.sort_by(|&a, &b| epsilon_compare((evals[b], violation[b]), (evals[a], violation[a]), epsilon));
+                let mut scores = vec![0.0; evals.len()];
+                for (rank, &i) in order.iter().enumerate() {
+                    scores[i] = -(rank as f64);
+                }
+                scores
+            }
+        }
+    }
+    ///current penalty weight of the adaptive penalty
+    pub fn lambda(&self)->f64{
+        self.lambda
+    }
+}
+///sum of the violations (negative violations count as satisfied)
+pub fn total_violation(violations:&[f64])->f64{
+    violations.iter().map(|v| v.max(0.0)).sum()
+}
+///ε-constrained comparison of (objective, total violation) pairs, Greater means a is better
+pub fn epsilon_compare(a:(f64,f64), b:(f64,f64), epsilon:f64)->Ordering{
+    if (a.1 <= epsilon && b.1 <= epsilon) || a.1 == b.1 {
+        a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal)
+    } else {
+        b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal)
+    }
+}
+
+///build a GA from a fitness returning the objective and the violation of each constraint<BR>
+/// individuals are evaluated once by fitness (see Constraints::combined) and ranked with the constraint handling,
+/// the GA fitness function (objective only) is used by local search
+pub fn constrained_ga<C>(initialization:InitializationStrategy, fitness:C, config:Config, handling:ConstraintHandling)
+    -> GA<Box<dyn Fn(Population)->f64 + Send + Sync>>
+where C:Fn(Population)->(f64,Vec<f64>) + Send + Sync + 'static{
+    let fitness = Arc::new(fitness);
+    let objective = fitness.clone();
+    let constraints = Constraints::combined(move |individual| fitness(individual), handling);
+    let objective:Box<dyn Fn(Population)->f64 + Send + Sync> = Box::new(move |individual| objective(individual).0);
+    GA::new(initialization, objective, config).with_constraints(constraints)
+}
+
+///repair applied by GA to offspring after mutation (see GA::with_repair)
+pub trait Repair<T>{
+    fn repair(&self, individual:T)->T;
+}
+pub enum RepairStrategy{
+    Usize(Box<dyn Repair<Vec<usize>> + Send>),
+    F64(Box<dyn Repair<Vec<f64>> + Send>),
+    Tree(Box<dyn Repair<Tree> + Send>),
+}
+impl RepairStrategy{
+    ///return a repaired copy of the population
+    pub fn apply(&self, population:&Population)->Population{
+        let mut repaired = population.clone();
+        for index in 0..repaired.len() {
+            self.repair_at(&mut repaired, index);
+        }
+        repaired
+    }
+    ///repair individual at index in place
+    pub fn repair_at(&self, population:&mut Population, index:usize){
+        match (self, population) {
+            (RepairStrategy::Usize(repair), Population::Usize(vec)) => vec[index] = repair.repair(mem::take(&mut vec[index])),
+            (RepairStrategy::F64(repair), Population::F64(vec)) => vec[index] = repair.repair(mem::take(&mut vec[index])),
+            (RepairStrategy::Tree(repair), Population::Tree(vec)) => vec[index] = repair.repair(vec[index].clone()),
+            _ => panic!("Repair and population must be of the same type"),
+        }
+    }
+}
+
+///turn a Vec<usize> back into a permutation of 0..len: duplicated genes are replaced by the missing ones in increasing order
+pub struct PermutationRepair;
+impl Repair<Vec<usize>> for PermutationRepair{
+    fn repair(&self, mut individual: Vec<usize>) -> Vec<usize> {
+        let mut seen = vec![false; individual.len()];
+        let mut duplicates = Vec::new();
+        for (i, &gene) in individual.iter().enumerate() {
+            if gene < seen.len() && !seen[gene] {
+                seen[gene] = true;
+            } else {
+                duplicates.push(i);
+            }
+        }
+        let missing = (0..seen.len()).filter(|&gene| !seen[gene]);
+        for (i, gene) in duplicates.into_iter().zip(missing) {
+            individual[i] = gene;
+        }
+        individual
+    }
+}
+///clamp Vec<f64> genes into a range (e.g. after a mutation that may leave Config::range)
+pub struct ClampRepair{
+    pub min:f64,
+    pub max:f64,
+}
+impl Repair<Vec<f64>> for ClampRepair{
+    fn repair(&self, individual: Vec<f64>) -> Vec<f64> {
+        individual.into_iter().map(|gene| gene.clamp(self.min, self.max)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use crate::population::RandomInitialization;
+
+    #[test]
+    fn combined_fitness_is_called_once_per_evaluation(){
+        static CALLS:AtomicUsize = AtomicUsize::new(0);
+        let fitness = |individual:Population| {
+            CALLS.fetch_add(1, AtomicOrdering::SeqCst);
+            match individual {
+                Population::F64(vec) => (-vec[0][0].abs(), vec![vec[0][1]]),
+                _ => unreachable!(),
+            }
+        };
+        let config = Config{num_individuals:10, num_genes:2, ..Config::default()};
+        let handling = ConstraintHandling::DynamicPenalty{c:0.5, alpha:2.0, beta:2.0};
+        let mut ga = constrained_ga(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config, handling);
+        ga.step();
+        //the initial population and at most one new child per individual
+        assert!(CALLS.load(AtomicOrdering::SeqCst) <= 20);
+    }
+
+    #[test]
+    fn feasibility_rules_rank_feasible_individuals_first(){
+        let evaluations = [(5.0, 1.0), (-1.0, 0.0), (-3.0, 0.0), (10.0, 3.0), (2.0, -1.0)]
+            .map(|(fitness, violation)| Evaluation{fitness, violations:vec![violation]});
+        let constraints = Constraints::new(|_| Vec::new(), ConstraintHandling::FeasibilityRules);
+        let scores = constraints.score(&evaluations.iter().collect::<Vec<&Evaluation>>());
+        let mut order:Vec<usize> = (0..scores.len()).collect();
+        order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap());
+        //feasible individuals by objective, then infeasible ones by violation
+        assert_eq!(order, vec![4, 1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn best_score_respects_feasibility_rules(){
+        //the objective rewards large genes but only non positive ones are feasible
+        let fitness = |individual:Population| match individual {
+            Population::F64(vec) => (vec[0][0], vec![vec[0][0]]),
+            _ => unreachable!(),
+        };
+        let config = Config{num_individuals:20, num_genes:1, verbose:false, ..Config::default()};
+        let mut ga = constrained_ga(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, config, ConstraintHandling::FeasibilityRules);
+        for _ in 0..10 {
+            let best = ga.step();
+            //the evaluated population is sorted by rank: the best is feasible whenever a feasible individual exists
+            let evaluations:Vec<&Evaluation> = ga.scores().iter().map(|evaluation| evaluation.as_ref().unwrap()).collect();
+            assert_eq!(best, evaluations[0].fitness);
+            if let Some(feasible) = evaluations.iter().filter(|e| total_violation(&e.violations) <= 0.0).map(|e| e.fitness).reduce(f64::max) {
+                assert!(total_violation(&evaluations[0].violations) <= 0.0);
+                assert_eq!(best, feasible);
+            }
+        }
+    }
+}
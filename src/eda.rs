@@ -0,0 +1,268 @@
+use std::time::Instant;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use crate::population::{Config, Population};
+
+///probabilistic model used by an estimation-of-distribution algorithm<BR>
+/// discrete models sample Population::Usize individuals, GaussianUMDA samples Population::F64 individuals within Config::range
+#[derive(Clone, Debug)]
+pub enum EDAModel{
+    ///Population-Based Incremental Learning on binary genes<BR>
+    /// probabilities move towards the best sample (and away from the worst one where they differ), then are randomly mutated
+    PBIL{learning_rate:f64, negative_learning_rate:f64, mutation_rate:f64, mutation_shift:f64},
+    ///Univariate Marginal Distribution Algorithm on genes taking values 0..cardinality<BR>
+    /// marginal frequencies are re-estimated from the best selection_ratio of the samples
+    UMDA{selection_ratio:f64, cardinality:usize},
+    ///continuous UMDA: independent normal distributions re-estimated from the best selection_ratio of the samples
+    GaussianUMDA{selection_ratio:f64},
+    ///compact GA on binary genes: two samples compete and probabilities move by 1/virtual_population towards the winner
+    CompactGA{virtual_population:usize},
+}
+///default model (PBIL with standard parameters)
+impl Default for EDAModel{
+    fn default() -> Self {
+        EDAModel::PBIL{learning_rate:0.1, negative_learning_rate:0.075, mutation_rate:0.02, mutation_shift:0.05}
+    }
+}
+
+pub struct EDA<F>
+where F:Fn(Population)->f64{
+    ///last sampled population, sorted best first
+    pub population: Population,
+    ///probability of each value of each gene (discrete models)
+    marginals: Vec<Vec<f64>>,
+    ///mean and standard deviation of each gene (GaussianUMDA)
+    normals: Vec<(f64,f64)>,
+    fitness:F,
+    config: Config,
+    model: EDAModel,
+    best: Option<(Population,f64)>,
+}
+impl<F> EDA<F>
+where F:Fn(Population)->f64{
+    ///initialize uniform distributions over Config::num_genes genes
+    pub fn new(fitness:F, config:Config, model:EDAModel) -> Self{
+        let cardinality = match model {
+            EDAModel::UMDA{cardinality, ..} => cardinality,
+            _ => 2,
+        };
+        let marginals = vec![vec![1.0 / cardinality as f64; cardinality]; config.num_genes];
+        let (start, end) = (config.range.start, config.range.end);
+        let normals = vec![((start + end) / 2.0, (end - start) / 2.0); config.num_genes];
+        let population = match model {
+            EDAModel::GaussianUMDA{..} => Population::F64(Vec::new()),
+            _ => Population::Usize(Vec::new()),
+        };
+        EDA{population, marginals, normals, fitness, config, model, best:None}
+    }
+    ///probability of each value of each gene (discrete models)
+    pub fn marginals(&self)->&[Vec<f64>]{
+        &self.marginals
+    }
+    ///mean and standard deviation of each gene (GaussianUMDA)
+    pub fn normals(&self)->&[(f64,f64)]{
+        &self.normals
+    }
+    ///best individual sampled so far and its fitness
+    pub fn best(&self)->Option<(Population,f64)>{
+        self.best.as_ref().map(|(individual, score)| (individual.select(&[0]), *score))
+    }
+    ///sample num individuals from the current model
+    fn sample(&self, num:usize)->Population{
+        let mut rng = rand::thread_rng();
+        match self.model {
+            EDAModel::GaussianUMDA{..} => Population::F64((0..num).map(|_| self.normals.iter().map(|&(mean, std)| {
+                Normal::new(mean, std).unwrap().sample(&mut rng).clamp(self.config.range.start, self.config.range.end)
+            }).collect()).collect()),
+            _ => Population::Usize((0..num).map(|_| self.marginals.iter().map(|probabilities| {
+                let r = rng.gen::<f64>();
+                let mut sum = 0.0;
+                probabilities.iter().position(|p| {sum += p; r < sum}).unwrap_or(probabilities.len() - 1)
+            }).collect()).collect()),
+        }
+    }
+    ///move the probabilities of binary genes towards (rate > 0) or away from (rate < 0) an individual
+    fn shift(&mut self, individual:&[usize], rate:f64){
+        for (probabilities, &gene) in self.marginals.iter_mut().zip(individual) {
+            let one = (probabilities[1] * (1.0 - rate) + gene as f64 * rate).clamp(0.0, 1.0);
+            *probabilities = vec![1.0 - one, one];
+        }
+    }
+    ///evolve the model forward by one step<BR>
+    /// in particular:<BR>
+    /// sample Config::num_individuals individuals (2 for the compact GA)<BR>
+    /// evaluate and sort them<BR>
+    /// update the model<BR>
+    /// returns best score of the sampled individuals
+    pub fn step(&mut self)->f64{
+        let num = match self.model {
+            EDAModel::CompactGA{..} => 2,
+            _ => self.config.num_individuals,
+        };
+        let samples = self.sample(num);
+        let evals:Vec<f64> = (0..samples.len()).map(|i| (self.fitness)(samples.select(&[i]))).collect();
+        let mut order:Vec<usize> = (0..evals.len()).collect();
+        order.sort_by(|&a, &b| evals[b].partial_cmp(&evals[a]).unwrap());
+        self.population = samples.select(&order);
+        let best = evals[order[0]];
+        if self.best.as_ref().map_or(true, |(_, score)| best > *score) {
+            self.best = Some((self.population.select(&[0]), best));
+        }
+        let selected = |ratio:f64| ((num as f64 * ratio).ceil() as usize).clamp(1, num);
+        match (self.model.clone(), &self.population) {
+            (EDAModel::PBIL{learning_rate, negative_learning_rate, mutation_rate, mutation_shift}, Population::Usize(vec)) => {
+                let (best, worst) = (vec[0].clone(), vec[vec.len() - 1].clone());
+                self.shift(&best, learning_rate);
+                for (i, probabilities) in self.marginals.iter_mut().enumerate() {
+                    if best[i] != worst[i] {
+                        let one = probabilities[1] * (1.0 - negative_learning_rate) + best[i] as f64 * negative_learning_rate;
+                        *probabilities = vec![1.0 - one, one];
+                    }
+                }
+                let mut rng = rand::thread_rng();
+                for probabilities in self.marginals.iter_mut() {
+                    if rng.gen::<f64>() < mutation_rate {
+                        let one = probabilities[1] * (1.0 - mutation_shift) + rng.gen_range(0..2) as f64 * mutation_shift;
+                        *probabilities = vec![1.0 - one, one];
+                    }
+                }
+            }
+            (EDAModel::UMDA{selection_ratio, cardinality}, Population::Usize(vec)) => {
+                let m = selected(selection_ratio);
+                for (gene, probabilities) in self.marginals.iter_mut().enumerate() {
+                    //Laplace correction keeps every value reachable
+                    *probabilities = (0..cardinality)
+                        .map(|value| (vec[..m].iter().filter(|individual| individual[gene] == value).count() as f64 + 1.0) / (m + cardinality) as f64)
+                        .collect();
+                }
+            }
+            (EDAModel::GaussianUMDA{selection_ratio}, Population::F64(vec)) => {
+                let m = selected(selection_ratio);
+                for (gene, normal) in self.normals.iter_mut().enumerate() {
+                    let mean = vec[..m].iter().map(|individual| individual[gene]).sum::<f64>() / m as f64;
+                    let variance = vec[..m].iter().map(|individual| (individual[gene] - mean).powi(2)).sum::<f64>() / m as f64;
+                    *normal = (mean, variance.sqrt().max(1e-12));
+                }
+            }
+            (EDAModel::CompactGA{virtual_population}, Population::Usize(vec)) => {
+                let (winner, loser) = (vec[0].clone(), vec[1].clone());
+                let step = 1.0 / virtual_population as f64;
+                for (i, probabilities) in self.marginals.iter_mut().enumerate() {
+                    if winner[i] != loser[i] {
+                        let one = (probabilities[1] + if winner[i] == 1 {step} else {-step}).clamp(0.0, 1.0);
+                        *probabilities = vec![1.0 - one, one];
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        if self.config.verbose {
+            print!("Best score = {}",best);
+        }
+        best
+    }
+    ///execute num_steps forward of evolution, return a vector of scores (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        for i in 0..num_steps{
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::GetPopulation;
+
+    fn one_max(population:Population)->f64{
+        let genes:Vec<usize> = population.get_individual(0).unwrap();
+        genes.iter().sum::<usize>() as f64
+    }
+
+    fn sphere(population:Population)->f64{
+        let genes:Vec<f64> = population.get_individual(0).unwrap();
+        -genes.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    ///average probability of a gene being 1 after num_steps on OneMax
+    fn one_probability(model:EDAModel, num_steps:usize)->f64{
+        let config = Config{num_individuals:30, num_genes:10, verbose:false, ..Config::default()};
+        let mut eda = EDA::new(one_max as fn(Population)->f64, config, model);
+        assert!(eda.marginals().iter().all(|probabilities| probabilities[1] == 0.5));
+        eda.evolve(num_steps);
+        eda.marginals().iter().map(|probabilities| probabilities[1]).sum::<f64>() / 10.0
+    }
+
+    #[test]
+    fn pbil_moves_towards_one_max(){
+        assert!(one_probability(EDAModel::default(), 50) > 0.8);
+    }
+
+    #[test]
+    fn umda_moves_towards_one_max(){
+        assert!(one_probability(EDAModel::UMDA{selection_ratio:0.3, cardinality:2}, 20) > 0.8);
+    }
+
+    #[test]
+    fn compact_ga_moves_towards_one_max(){
+        assert!(one_probability(EDAModel::CompactGA{virtual_population:20}, 500) > 0.8);
+    }
+
+    #[test]
+    fn compact_ga_steps_by_one_over_virtual_population(){
+        let config = Config{num_genes:10, verbose:false, ..Config::default()};
+        let mut eda = EDA::new(one_max as fn(Population)->f64, config, EDAModel::CompactGA{virtual_population:4});
+        eda.step();
+        for probabilities in eda.marginals() {
+            assert!([0.25, 0.5, 0.75].contains(&probabilities[1]), "{:?}", probabilities);
+            assert_eq!(probabilities[0] + probabilities[1], 1.0);
+        }
+    }
+
+    #[test]
+    fn gaussian_umda_moves_towards_sphere_optimum(){
+        let config = Config{num_individuals:100, num_genes:3, range:-5.0..5.0, verbose:false, ..Config::default()};
+        let mut eda = EDA::new(sphere as fn(Population)->f64, config, EDAModel::GaussianUMDA{selection_ratio:0.3});
+        assert!(eda.normals().iter().all(|&normal| normal == (0.0, 5.0)));
+        eda.evolve(30);
+        for &(mean, std) in eda.normals() {
+            assert!(mean.abs() < 0.1, "{}", mean);
+            assert!(std < 0.1, "{}", std);
+        }
+        assert!(eda.best().unwrap().1 > -0.01);
+    }
+
+    #[test]
+    fn umda_laplace_correction_bounds_probabilities(){
+        //value 2 is always preferred
+        let twos = |population:Population| {
+            let genes:Vec<usize> = population.get_individual(0).unwrap();
+            genes.iter().filter(|&&gene| gene == 2).count() as f64
+        };
+        let config = Config{num_individuals:20, num_genes:5, verbose:false, ..Config::default()};
+        let mut eda = EDA::new(twos, config, EDAModel::UMDA{selection_ratio:0.5, cardinality:3});
+        //10 selected individuals and 3 values
+        let (low, high) = (1.0 / 13.0, 11.0 / 13.0);
+        for _ in 0..30 {
+            eda.step();
+            for probabilities in eda.marginals() {
+                assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+                assert!(probabilities.iter().all(|&p| p >= low - 1e-12 && p <= high + 1e-12), "{:?}", probabilities);
+            }
+        }
+        //the preferred value dominates but never reaches probability 1
+        assert!(eda.marginals().iter().map(|probabilities| probabilities[2]).sum::<f64>() / 5.0 > 0.6);
+    }
+}
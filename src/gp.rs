@@ -0,0 +1,451 @@
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::population::{Config, Initialization};
+
+///function of the primitive set (an internal node of a tree)
+#[derive(Clone, Debug)]
+pub struct Function{
+    pub name:String,
+    ///type of each argument (the arity is args.len())
+    pub args:Vec<usize>,
+    ///type of the returned value
+    pub ret:usize,
+    pub op:fn(&[f64])->f64,
+}
+impl Function{
+    ///untyped function: arguments and returned value are all of type 0
+    pub fn new(name:&str, arity:usize, op:fn(&[f64])->f64) -> Self{
+        Function{name:name.to_string(), args:vec![0; arity], ret:0, op}
+    }
+    ///strongly typed function
+    pub fn typed(name:&str, ret:usize, args:Vec<usize>, op:fn(&[f64])->f64) -> Self{
+        Function{name:name.to_string(), args, ret, op}
+    }
+    pub fn arity(&self)->usize{
+        self.args.len()
+    }
+}
+///terminal of the primitive set (a leaf of a tree)
+#[derive(Clone, Debug)]
+pub enum Terminal{
+    ///input of the program, read from inputs[index] by Tree::evaluate
+    Variable{name:String, index:usize, ret:usize},
+    Constant{value:f64, ret:usize},
+    ///ephemeral random constant: every time it is picked, a new Node::Constant of type ret is drawn uniformly from range
+    Ephemeral{range:Range<f64>, ret:usize},
+}
+impl Terminal{
+    ///untyped variable reading inputs[index]
+    pub fn variable(name:&str, index:usize) -> Self{
+        Terminal::Variable{name:name.to_string(), index, ret:0}
+    }
+    ///untyped constant
+    pub fn constant(value:f64) -> Self{
+        Terminal::Constant{value, ret:0}
+    }
+    ///untyped ephemeral random constant
+    pub fn ephemeral(range:Range<f64>) -> Self{
+        Terminal::Ephemeral{range, ret:0}
+    }
+    pub fn ret(&self)->usize{
+        match self {
+            Terminal::Variable{ret, ..} | Terminal::Constant{ret, ..} | Terminal::Ephemeral{ret, ..} => *ret,
+        }
+    }
+}
+///mutation operators applied to trees
+#[derive(Clone, Debug)]
+pub enum TreeMutation{
+    ///replace a random subtree with a newly grown one
+    Subtree,
+    ///replace a random node with another primitive of the same arity and types
+    Point,
+    ///replace the tree with one of its own subtrees
+    Hoist,
+}
+///struct used to configure tree sizes and operators<BR>
+/// there is a Default configuration
+#[derive(Clone, Debug)]
+pub struct GPConfig{
+    ///depths used by ramped half-and-half initialization
+    pub init_depth:RangeInclusive<usize>,
+    ///offspring deeper than max_depth are discarded (the parent is kept)
+    pub max_depth:usize,
+    ///offspring with more than max_size nodes are discarded (the parent is kept)
+    pub max_size:usize,
+    ///probability of choosing a function rather than a terminal as crossover point
+    pub function_point_rate:f64,
+    ///one of these mutations is picked at random for each mutated tree
+    pub mutations:Vec<TreeMutation>,
+}
+///default GP configuration
+impl Default for GPConfig{
+    fn default() -> Self {
+        GPConfig{
+            init_depth:2..=6,
+            max_depth:17,
+            max_size:200,
+            function_point_rate:0.9,
+            mutations:vec![TreeMutation::Subtree, TreeMutation::Point, TreeMutation::Hoist],
+        }
+    }
+}
+///functions and terminals trees are built from, shared by all the trees of a population
+#[derive(Clone, Debug)]
+pub struct PrimitiveSet{
+    pub functions:Vec<Function>,
+    pub terminals:Vec<Terminal>,
+    ///type returned by whole trees
+    pub root_type:usize,
+    pub config:GPConfig,
+}
+impl PrimitiveSet{
+    ///build a primitive set with root type 0 and default GPConfig
+    pub fn new(functions:Vec<Function>, terminals:Vec<Terminal>) -> Self{
+        PrimitiveSet{functions, terminals, root_type:0, config:GPConfig::default()}
+    }
+    ///height of the smallest tree returning each type (None if no finite tree returns it)
+    fn min_heights(&self)->Vec<Option<usize>>{
+        let num_types = self.functions.iter().flat_map(|f| f.args.iter().cloned().chain([f.ret]))
+            .chain(self.terminals.iter().map(Terminal::ret))
+            .fold(self.root_type, usize::max) + 1;
+        let mut heights:Vec<Option<usize>> = vec![None; num_types];
+        self.terminals.iter().for_each(|t| heights[t.ret()] = Some(0));
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for function in &self.functions {
+                let height = function.args.iter().try_fold(0, |max, &arg| heights[arg].map(|h| max.max(h + 1))).map(|h| h.max(1));
+                if let Some(height) = height {
+                    if heights[function.ret].map_or(true, |h| height < h) {
+                        heights[function.ret] = Some(height);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        heights
+    }
+    ///check that every type needed by the root or by a function argument is returned by some finite tree,
+    /// otherwise trees of that type could never be completed
+    pub fn validate(&self)->Result<(),String>{
+        let heights = self.min_heights();
+        let needed = std::iter::once(self.root_type).chain(self.functions.iter().flat_map(|f| f.args.iter().cloned()));
+        for ret in needed {
+            if heights[ret].is_none() {
+                return Err(format!("No finite tree returns type {}, add a terminal of this type", ret));
+            }
+        }
+        Ok(())
+    }
+    ///random node of a given type, a terminal is forced when terminal is true<BR>
+    /// if no terminal returns the type, a function leading to the smallest subtrees is picked instead
+    fn random_node(&self, ret:usize, terminal:bool)->Node{
+        let mut rng = rand::thread_rng();
+        let mut functions:Vec<usize> = (0..self.functions.len()).filter(|&i| self.functions[i].ret == ret).collect();
+        let terminals:Vec<usize> = (0..self.terminals.len()).filter(|&i| self.terminals[i].ret() == ret).collect();
+        if (terminal || functions.is_empty()) && !terminals.is_empty() {
+            let t = *terminals.choose(&mut rng).unwrap();
+            match &self.terminals[t] {
+                Terminal::Ephemeral{range, ret} => Node::Constant(rng.gen_range(range.clone()), *ret),
+                _ => Node::Terminal(t),
+            }
+        } else {
+            if terminal {
+                //keep only the functions whose arguments get closer to terminals, so that generation ends
+                let heights = self.min_heights();
+                let shortest = heights.get(ret).copied().flatten().expect("No finite tree returns the requested type (see PrimitiveSet::validate)");
+                functions.retain(|&f| self.functions[f].args.iter().all(|&arg| heights[arg].is_some_and(|h| h < shortest)));
+            }
+            Node::Function(*functions.choose(&mut rng).expect("No primitive returns the requested type"))
+        }
+    }
+    ///generate a random subtree in prefix order<BR>
+    /// full: every branch reaches max_depth, otherwise (grow) terminals may appear at any depth
+    pub fn generate(&self, ret:usize, max_depth:usize, full:bool)->Vec<Node>{
+        let mut nodes = Vec::new();
+        self.generate_into(&mut nodes, ret, 0, max_depth, full);
+        nodes
+    }
+    fn generate_into(&self, nodes:&mut Vec<Node>, ret:usize, depth:usize, max_depth:usize, full:bool){
+        let share = self.terminals.len() as f64 / (self.terminals.len() + self.functions.len()) as f64;
+        let terminal = depth >= max_depth || (!full && depth > 0 && rand::random::<f64>() < share);
+        let node = self.random_node(ret, terminal);
+        nodes.push(node);
+        if let Node::Function(f) = node {
+            for &arg in &self.functions[f].args {
+                self.generate_into(nodes, arg, depth + 1, max_depth, full);
+            }
+        }
+    }
+}
+
+///node of a tree stored in prefix order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Node{
+    ///index into PrimitiveSet::functions
+    Function(usize),
+    ///index into PrimitiveSet::terminals
+    Terminal(usize),
+    ///constant (value, type) drawn from a Terminal::Ephemeral
+    Constant(f64, usize),
+}
+
+///program represented as a tree of nodes stored in prefix order
+#[derive(Clone, Debug)]
+pub struct Tree{
+    pub nodes:Vec<Node>,
+    pub set:Arc<PrimitiveSet>,
+}
+impl PartialEq for Tree{
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+impl Tree{
+    pub fn new(nodes:Vec<Node>, set:Arc<PrimitiveSet>) -> Self{
+        Tree{nodes, set}
+    }
+    ///number of nodes
+    pub fn size(&self)->usize{
+        self.nodes.len()
+    }
+    ///depth of the tree (a single terminal has depth 0)
+    pub fn depth(&self)->usize{
+        let mut depth = 0;
+        let mut stack:Vec<usize> = Vec::new();
+        for node in &self.nodes {
+            let current = stack.pop().unwrap_or(0);
+            depth = depth.max(current);
+            if let Node::Function(f) = node {
+                stack.extend(std::iter::repeat(current + 1).take(self.set.functions[*f].arity()));
+            }
+        }
+        depth
+    }
+    ///index one past the end of the subtree starting at index
+    pub fn subtree_end(&self, index:usize)->usize{
+        let mut open = 1;
+        let mut end = index;
+        while open > 0 {
+            if let Node::Function(f) = self.nodes[end] {
+                open += self.set.functions[f].arity();
+            }
+            open -= 1;
+            end += 1;
+        }
+        end
+    }
+    ///type returned by the node at index
+    pub fn node_type(&self, index:usize)->usize{
+        match self.nodes[index] {
+            Node::Function(f) => self.set.functions[f].ret,
+            Node::Terminal(t) => self.set.terminals[t].ret(),
+            Node::Constant(_, ret) => ret,
+        }
+    }
+    ///run the program on the given inputs<BR>
+    /// nodes are visited from last to first so that arguments are always on top of the stack
+    pub fn evaluate(&self, inputs:&[f64])->f64{
+        let mut stack:Vec<f64> = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.iter().rev() {
+            match node {
+                Node::Function(f) => {
+                    let function = &self.set.functions[*f];
+                    let start = stack.len() - function.arity();
+                    stack[start..].reverse();
+                    let value = (function.op)(&stack[start..]);
+                    stack.truncate(start);
+                    stack.push(value);
+                }
+                Node::Terminal(t) => match &self.set.terminals[*t] {
+                    Terminal::Variable{index, ..} => stack.push(inputs[*index]),
+                    Terminal::Constant{value, ..} => stack.push(*value),
+                    Terminal::Ephemeral{..} => unreachable!("ephemeral constants are stored as Node::Constant"),
+                },
+                Node::Constant(value, _) => stack.push(*value),
+            }
+        }
+        stack[0]
+    }
+    ///true if the tree respects GPConfig::max_depth and GPConfig::max_size
+    pub fn within_limits(&self)->bool{
+        self.size() <= self.set.config.max_size && self.depth() <= self.set.config.max_depth
+    }
+    ///copy of the tree with the subtree at index replaced by the given nodes
+    pub fn with_subtree(&self, index:usize, subtree:&[Node])->Tree{
+        let end = self.subtree_end(index);
+        let mut nodes = Vec::with_capacity(self.nodes.len() - (end - index) + subtree.len());
+        nodes.extend_from_slice(&self.nodes[..index]);
+        nodes.extend_from_slice(subtree);
+        nodes.extend_from_slice(&self.nodes[end..]);
+        Tree{nodes, set:self.set.clone()}
+    }
+    ///random node index, a function node is preferred with probability GPConfig::function_point_rate
+    fn random_point(&self)->usize{
+        let mut rng = rand::thread_rng();
+        let functions:Vec<usize> = (0..self.nodes.len()).filter(|&i| matches!(self.nodes[i], Node::Function(_))).collect();
+        if !functions.is_empty() && rng.gen::<f64>() < self.set.config.function_point_rate {
+            *functions.choose(&mut rng).unwrap()
+        } else {
+            rng.gen_range(0..self.nodes.len())
+        }
+    }
+    ///subtree crossover: swap two random subtrees of the same type<BR>
+    /// children breaking the size or depth limits are replaced by their parent
+    pub fn crossover(&self, other:&Tree)->(Tree,Tree){
+        let mut rng = rand::thread_rng();
+        let point1 = self.random_point();
+        let ret = self.node_type(point1);
+        let candidates:Vec<usize> = (0..other.nodes.len()).filter(|&i| other.node_type(i) == ret).collect();
+        let Some(&point2) = candidates.choose(&mut rng) else {
+            return (self.clone(), other.clone());
+        };
+        let child1 = self.with_subtree(point1, &other.nodes[point2..other.subtree_end(point2)]);
+        let child2 = other.with_subtree(point2, &self.nodes[point1..self.subtree_end(point1)]);
+        (
+            if child1.within_limits() {child1} else {self.clone()},
+            if child2.within_limits() {child2} else {other.clone()},
+        )
+    }
+    ///apply one of the configured mutations, a child breaking the limits is replaced by its parent
+    pub fn mutate(&self)->Tree{
+        let mut rng = rand::thread_rng();
+        let child = match self.set.config.mutations.choose(&mut rng) {
+            Some(TreeMutation::Subtree) => self.subtree_mutation(),
+            Some(TreeMutation::Point) => self.point_mutation(),
+            Some(TreeMutation::Hoist) => self.hoist_mutation(),
+            None => self.clone(),
+        };
+        if child.within_limits() {child} else {self.clone()}
+    }
+    fn subtree_mutation(&self)->Tree{
+        let point = rand::thread_rng().gen_range(0..self.nodes.len());
+        let depth = rand::thread_rng().gen_range(self.set.config.init_depth.clone());
+        let subtree = self.set.generate(self.node_type(point), depth, false);
+        self.with_subtree(point, &subtree)
+    }
+    fn point_mutation(&self)->Tree{
+        let mut rng = rand::thread_rng();
+        let point = rng.gen_range(0..self.nodes.len());
+        let set = &self.set;
+        let replacement = match self.nodes[point] {
+            Node::Function(f) => (0..set.functions.len())
+                .filter(|&g| set.functions[g].ret == set.functions[f].ret && set.functions[g].args == set.functions[f].args)
+                .collect::<Vec<usize>>()
+                .choose(&mut rng)
+                .map(|&g| Node::Function(g)),
+            _ => Some(set.random_node(self.node_type(point), true)),
+        };
+        let mut child = self.clone();
+        if let Some(node) = replacement {
+            child.nodes[point] = node;
+        }
+        child
+    }
+    fn hoist_mutation(&self)->Tree{
+        let root_type = self.set.root_type;
+        let candidates:Vec<usize> = (0..self.nodes.len()).filter(|&i| self.node_type(i) == root_type).collect();
+        let point = *candidates.choose(&mut rand::thread_rng()).unwrap_or(&0);
+        Tree{nodes:self.nodes[point..self.subtree_end(point)].to_vec(), set:self.set.clone()}
+    }
+}
+///trees are printed as s-expressions, e.g. (add x (mul 2 y))
+impl fmt::Display for Tree{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut open:Vec<usize> = Vec::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match node {
+                Node::Function(g) => {
+                    write!(f, "({}", self.set.functions[*g].name)?;
+                    if self.set.functions[*g].arity() > 0 {
+                        open.push(self.set.functions[*g].arity());
+                        continue;
+                    }
+                    write!(f, ")")?;
+                }
+                Node::Terminal(t) => match &self.set.terminals[*t] {
+                    Terminal::Variable{name, ..} => write!(f, "{}", name)?,
+                    Terminal::Constant{value, ..} => write!(f, "{}", value)?,
+                    Terminal::Ephemeral{range, ..} => write!(f, "erc[{:?}]", range)?,
+                },
+                Node::Constant(value, _) => write!(f, "{}", value)?,
+            }
+            while let Some(remaining) = open.pop() {
+                if remaining > 1 {
+                    open.push(remaining - 1);
+                    break;
+                }
+                write!(f, ")")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+///ramped half-and-half initialization: depths are spread over GPConfig::init_depth,<BR>
+/// half of the trees of each depth are generated with the full method and half with the grow method
+pub struct RampedHalfAndHalf{
+    pub set:Arc<PrimitiveSet>,
+}
+impl Initialization<Vec<Tree>> for RampedHalfAndHalf{
+    fn initialize(&self, config: Config) -> Vec<Tree> {
+        let depths:Vec<usize> = self.set.config.init_depth.clone().collect();
+        (0..config.num_individuals).map(|i| {
+            let depth = depths[(i / 2) % depths.len()];
+            Tree::new(self.set.generate(self.set.root_type, depth, i % 2 == 0), self.set.clone())
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    ///type 0: numbers, type 1: booleans only built from comparisons of numbers
+    fn typed_set()->PrimitiveSet{
+        let functions = vec![
+            Function::new("add", 2, |args| args[0] + args[1]),
+            Function::typed("lt", 1, vec![0, 0], |args| f64::from(u8::from(args[0] < args[1]))),
+            Function::typed("and", 1, vec![1, 1], |args| args[0].min(args[1])),
+        ];
+        let terminals = vec![Terminal::variable("x", 0), Terminal::Ephemeral{range:0.0..1.0, ret:0}];
+        PrimitiveSet{root_type:1, ..PrimitiveSet::new(functions, terminals)}
+    }
+
+    #[test]
+    fn types_without_terminals_are_completed_within_bounded_depth(){
+        let set = Arc::new(typed_set());
+        assert!(set.validate().is_ok());
+        for max_depth in 0..6 {
+            for full in [false, true] {
+                let tree = Tree::new(set.generate(1, max_depth, full), set.clone());
+                assert_eq!(tree.node_type(0), 1);
+                //a boolean needs at least one comparison above the number terminals
+                assert!(tree.depth() <= max_depth.max(1) + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_terminal_types_are_reported(){
+        let functions = vec![Function::typed("not", 1, vec![1], |args| 1.0 - args[0])];
+        let set = PrimitiveSet{root_type:1, ..PrimitiveSet::new(functions, vec![Terminal::variable("x", 0)])};
+        assert!(set.validate().is_err());
+    }
+
+    #[test]
+    fn ephemeral_constants_keep_their_type(){
+        let terminals = vec![Terminal::Ephemeral{range:1.0..2.0, ret:3}];
+        let set = Arc::new(PrimitiveSet{root_type:3, ..PrimitiveSet::new(Vec::new(), terminals)});
+        let tree = Tree::new(set.generate(3, 2, false), set.clone());
+        assert!(matches!(tree.nodes[..], [Node::Constant(value, 3)] if (1.0..2.0).contains(&value)));
+        assert_eq!(tree.node_type(0), 3);
+    }
+}
@@ -0,0 +1,265 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::thread;
+use std::time::Instant;
+use crate::population::{Evaluation, GA, Population};
+
+///which islands receive migrants from each island
+#[derive(Clone, Debug)]
+pub enum MigrationTopology{
+    ///island i sends to island i+1 (last one sends to the first)
+    Ring,
+    ///every island sends to every other island
+    FullyConnected,
+    ///every island sends to another island chosen at random
+    Random,
+}
+///how individuals are picked, either to emigrate or to be replaced
+#[derive(Clone, Debug)]
+pub enum MigrantPolicy{
+    Best,
+    Worst,
+    Random,
+}
+///struct used to configure migration between islands
+#[derive(Clone, Debug)]
+pub struct IslandConfig{
+    pub topology:MigrationTopology,
+    ///number of steps between two migrations
+    pub migration_interval:usize,
+    ///number of individuals sent by each island to each destination
+    pub num_migrants:usize,
+    ///individuals chosen to emigrate
+    pub emigrants:MigrantPolicy,
+    ///individuals replaced by immigrants
+    pub replaced:MigrantPolicy,
+    ///evolve each island on its own thread
+    pub parallel:bool,
+}
+///default island configuration
+impl Default for IslandConfig{
+    fn default() -> Self {
+        IslandConfig{
+            topology:MigrationTopology::Ring,
+            migration_interval:10,
+            num_migrants:2,
+            emigrants:MigrantPolicy::Best,
+            replaced:MigrantPolicy::Worst,
+            parallel:false,
+        }
+    }
+}
+
+///island model: several GA evolving independently and periodically exchanging individuals
+pub struct Islands<F>
+where F:Fn(Population)->f64 + Send + Sync{
+    pub islands: Vec<GA<F>>,
+    config: IslandConfig,
+    generation: usize,
+}
+impl<F> Islands<F>
+where F:Fn(Population)->f64 + Send + Sync{
+    ///build an island model from already initialized GA (all of the same Population type)
+    pub fn new(islands:Vec<GA<F>>, config:IslandConfig) -> Self{
+        assert!(islands.len() > 1, "Island model needs at least two islands");
+        Islands{islands, config, generation:0}
+    }
+    ///indices of the individuals chosen by a policy<BR>
+    /// populations are sorted best first after GA::step
+    fn pick(policy:&MigrantPolicy, population_len:usize, num:usize)->Vec<usize>{
+        let num = num.min(population_len);
+        match policy {
+            MigrantPolicy::Best => (0..num).collect(),
+            MigrantPolicy::Worst => (population_len - num..population_len).collect(),
+            MigrantPolicy::Random => rand::seq::index::sample(&mut rand::thread_rng(), population_len, num).into_vec(),
+        }
+    }
+    ///destinations of the migrants sent by every island
+    fn destinations(&self)->Vec<Vec<usize>>{
+        let n = self.islands.len();
+        let mut rng = rand::thread_rng();
+        (0..n).map(|i| match self.config.topology {
+            MigrationTopology::Ring => vec![(i + 1) % n],
+            MigrationTopology::FullyConnected => (0..n).filter(|&j| j != i).collect(),
+            MigrationTopology::Random => {
+                let j = rng.gen_range(0..n - 1);
+                vec![if j >= i {j + 1} else {j}]
+            }
+        }).collect()
+    }
+    ///exchange individuals between islands according to the configured topology and policies
+    pub fn migrate(&mut self){
+        let destinations = self.destinations();
+        let emigrants:Vec<(Population,Vec<Option<Evaluation>>)> = self.islands.iter()
+            .map(|ga| {
+                let picked = Self::pick(&self.config.emigrants, ga.population().len(), self.config.num_migrants);
+                (ga.population().select(&picked), picked.iter().map(|&i| ga.scores().get(i).cloned().flatten()).collect())
+            })
+            .collect();
+        //every island gathers all its arrivals first so that the replaced slots are picked once and migrants from different sources do not overwrite each other
+        let mut incoming:Vec<Vec<(usize,usize)>> = vec![Vec::new(); self.islands.len()];
+        for (source, targets) in destinations.iter().enumerate() {
+            for &target in targets {
+                incoming[target].extend((0..emigrants[source].0.len()).map(|migrant| (source, migrant)));
+            }
+        }
+        let mut rng = rand::thread_rng();
+        for (island, mut arrivals) in self.islands.iter_mut().zip(incoming) {
+            arrivals.shuffle(&mut rng);
+            let mut replaced = Self::pick(&self.config.replaced, island.population().len(), arrivals.len());
+            replaced.shuffle(&mut rng);
+            for (index, (source, migrant)) in replaced.into_iter().zip(arrivals) {
+                let (migrants, scores) = &emigrants[source];
+                island.replace_individual(index, migrants, migrant, scores[migrant].clone());
+            }
+        }
+    }
+    ///evolve every island by one step (in parallel if configured) and migrate when due<BR>
+    /// returns best score across islands
+    pub fn step(&mut self)->f64{
+        let scores:Vec<f64> = if self.config.parallel {
+            thread::scope(|scope| {
+                let handles:Vec<_> = self.islands.iter_mut().map(|ga| scope.spawn(move || ga.step())).collect();
+                handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+            })
+        } else {
+            self.islands.iter_mut().map(|ga| ga.step()).collect()
+        };
+        self.generation += 1;
+        if self.config.migration_interval > 0 && self.generation % self.config.migration_interval == 0 {
+            self.migrate();
+        }
+        scores.into_iter().reduce(f64::max).unwrap()
+    }
+    ///execute num_steps forward of evolution, return a vector of best scores across islands (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        let verbose = self.islands.iter().any(|ga| ga.config().verbose);
+        for i in 0..num_steps{
+            if verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::{Config, GetPopulation, Initialization, InitializationStrategy};
+
+    type Fitness = fn(Population)->f64;
+
+    ///island i starts with individuals [i*100], [i*100+1], ... so that the origin of every individual is known
+    struct Numbered(usize);
+    impl Initialization<Vec<Vec<usize>>> for Numbered{
+        fn initialize(&self, config:Config)->Vec<Vec<usize>>{
+            (0..config.num_individuals).map(|k| vec![self.0 * 100 + k]).collect()
+        }
+    }
+
+    fn islands(num_islands:usize, config:IslandConfig)->Islands<Fitness>{
+        let ga_config = Config{num_individuals:10, num_genes:1, verbose:false, ..Config::default()};
+        let islands = (0..num_islands)
+            .map(|i| GA::new(InitializationStrategy::Usize(Box::new(Numbered(i))), (|_| 0.0) as Fitness, ga_config.clone()))
+            .collect();
+        Islands::new(islands, config)
+    }
+
+    fn genes(islands:&Islands<Fitness>, island:usize)->Vec<usize>{
+        let population = islands.islands[island].population();
+        (0..population.len()).map(|i| GetPopulation::<usize>::get_individual(population, i).unwrap()[0]).collect()
+    }
+
+    ///number of individuals of an island that come from island source
+    fn count_from(islands:&Islands<Fitness>, island:usize, source:usize)->usize{
+        genes(islands, island).iter().filter(|&&gene| gene / 100 == source).count()
+    }
+
+    #[test]
+    fn ring_sends_migrants_to_the_next_island_only(){
+        let mut model = islands(3, IslandConfig{num_migrants:3, ..IslandConfig::default()});
+        model.migrate();
+        for island in 0..3 {
+            let previous = (island + 2) % 3;
+            assert_eq!(count_from(&model, island, previous), 3);
+            assert_eq!(count_from(&model, island, island), 7);
+        }
+    }
+
+    #[test]
+    fn fully_connected_islands_keep_migrants_from_every_source(){
+        for replaced in [MigrantPolicy::Worst, MigrantPolicy::Best, MigrantPolicy::Random] {
+            let config = IslandConfig{topology:MigrationTopology::FullyConnected, num_migrants:2, replaced, ..IslandConfig::default()};
+            let mut model = islands(3, config);
+            model.migrate();
+            for island in 0..3 {
+                for source in (0..3).filter(|&source| source != island) {
+                    assert_eq!(count_from(&model, island, source), 2);
+                }
+                assert_eq!(count_from(&model, island, island), 6);
+            }
+        }
+    }
+
+    #[test]
+    fn random_topology_sends_to_one_other_island(){
+        let mut model = islands(4, IslandConfig{topology:MigrationTopology::Random, num_migrants:2, ..IslandConfig::default()});
+        model.migrate();
+        let immigrants:usize = (0..4).map(|island| 10 - count_from(&model, island, island)).sum();
+        assert_eq!(immigrants, 8);
+    }
+
+    #[test]
+    fn emigrant_policies_pick_the_expected_individuals(){
+        let best = IslandConfig{num_migrants:2, emigrants:MigrantPolicy::Best, ..IslandConfig::default()};
+        let mut model = islands(2, best);
+        model.migrate();
+        let mut arrived:Vec<usize> = genes(&model, 1).into_iter().filter(|gene| gene / 100 == 0).collect();
+        arrived.sort_unstable();
+        assert_eq!(arrived, vec![0, 1]);
+
+        let worst = IslandConfig{num_migrants:2, emigrants:MigrantPolicy::Worst, ..IslandConfig::default()};
+        let mut model = islands(2, worst);
+        model.migrate();
+        let mut arrived:Vec<usize> = genes(&model, 1).into_iter().filter(|gene| gene / 100 == 0).collect();
+        arrived.sort_unstable();
+        assert_eq!(arrived, vec![8, 9]);
+    }
+
+    #[test]
+    fn replacement_policies_overwrite_the_expected_slots(){
+        let worst = IslandConfig{num_migrants:2, replaced:MigrantPolicy::Worst, ..IslandConfig::default()};
+        let mut model = islands(2, worst);
+        model.migrate();
+        assert_eq!(genes(&model, 1)[..8], (100..108).collect::<Vec<usize>>());
+
+        let best = IslandConfig{num_migrants:2, replaced:MigrantPolicy::Best, ..IslandConfig::default()};
+        let mut model = islands(2, best);
+        model.migrate();
+        assert_eq!(genes(&model, 1)[2..], (102..110).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn pick_policies(){
+        assert_eq!(Islands::<Fitness>::pick(&MigrantPolicy::Best, 10, 3), vec![0, 1, 2]);
+        assert_eq!(Islands::<Fitness>::pick(&MigrantPolicy::Worst, 10, 3), vec![7, 8, 9]);
+        assert_eq!(Islands::<Fitness>::pick(&MigrantPolicy::Worst, 2, 3), vec![0, 1]);
+        for _ in 0..100 {
+            let mut random = Islands::<Fitness>::pick(&MigrantPolicy::Random, 10, 4);
+            random.sort_unstable();
+            random.dedup();
+            assert_eq!(random.len(), 4);
+            assert!(random.iter().all(|&index| index < 10));
+        }
+    }
+}
@@ -1,2 +1,17 @@
 pub mod population;
-pub mod plot;
\ No newline at end of file
+pub mod plot;
+pub mod pso;
+pub mod island;
+pub mod cellular;
+pub mod memetic;
+pub mod niching;
+pub mod gp;
+pub mod symbolic_regression;
+pub mod neat;
+pub mod neural;
+pub mod eda;
+pub mod map_elites;
+pub mod novelty;
+pub mod constraints;
+pub mod cache;
+pub mod problems;
\ No newline at end of file
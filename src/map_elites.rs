@@ -0,0 +1,265 @@
+use std::ops::Range;
+use std::time::Instant;
+use rand::Rng;
+use crate::plot::draw_heatmap;
+use crate::population::{Config, InitializationStrategy, Mutation, Population};
+
+///partition of the behavior space into cells, each holding at most one elite
+#[derive(Clone, Debug)]
+pub enum Archive{
+    ///fixed number of bins along each descriptor dimension, descriptors outside the ranges fall in the border bins
+    Grid{bins:Vec<usize>, ranges:Vec<Range<f64>>},
+    ///centroidal Voronoi tessellation: each descriptor belongs to the cell of its closest centroid
+    CVT{centroids:Vec<Vec<f64>>, ranges:Vec<Range<f64>>},
+}
+impl Archive{
+    pub fn grid(bins:Vec<usize>, ranges:Vec<Range<f64>>) -> Self{
+        assert_eq!(bins.len(), ranges.len(), "One range is needed per descriptor dimension");
+        assert!(bins.iter().all(|&bin| bin > 0), "Every descriptor dimension needs at least one bin");
+        Archive::Grid{bins, ranges}
+    }
+    ///compute num_cells centroids with k-means (Lloyd iterations) on num_samples points drawn uniformly within the ranges
+    pub fn cvt(num_cells:usize, ranges:Vec<Range<f64>>, num_samples:usize) -> Self{
+        assert!(num_cells > 0, "A CVT archive needs at least one cell");
+        let mut rng = rand::thread_rng();
+        let samples:Vec<Vec<f64>> = (0..num_samples.max(num_cells))
+            .map(|_| ranges.iter().map(|range| rng.gen_range(range.clone())).collect())
+            .collect();
+        let mut centroids:Vec<Vec<f64>> = samples[..num_cells].to_vec();
+        for _ in 0..20 {
+            let mut sums = vec![vec![0.0; ranges.len()]; num_cells];
+            let mut counts = vec![0; num_cells];
+            for sample in &samples {
+                let cell = closest(&centroids, sample);
+                counts[cell] += 1;
+                for (sum, x) in sums[cell].iter_mut().zip(sample) {
+                    *sum += x;
+                }
+            }
+            for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+                if count > 0 {
+                    *centroid = sum.into_iter().map(|x| x / count as f64).collect();
+                }
+            }
+        }
+        Archive::CVT{centroids, ranges}
+    }
+    pub fn num_cells(&self)->usize{
+        match self {
+            Archive::Grid{bins, ..} => bins.iter().product(),
+            Archive::CVT{centroids, ..} => centroids.len(),
+        }
+    }
+    ///range of each descriptor dimension
+    pub fn ranges(&self)->&[Range<f64>]{
+        match self {
+            Archive::Grid{ranges, ..} | Archive::CVT{ranges, ..} => ranges,
+        }
+    }
+    ///index of the cell a descriptor belongs to (the first dimension varies fastest in grids)
+    pub fn cell(&self, descriptor:&[f64])->usize{
+        match self {
+            Archive::Grid{bins, ranges} => {
+                let mut cell = 0;
+                for ((&bin, range), &x) in bins.iter().zip(ranges).zip(descriptor).rev() {
+                    let position = ((x - range.start) / (range.end - range.start) * bin as f64).floor();
+                    cell = cell * bin + (position.max(0.0) as usize).min(bin - 1);
+                }
+                cell
+            }
+            Archive::CVT{centroids, ..} => closest(centroids, descriptor),
+        }
+    }
+}
+///index of the point closest to x
+fn closest(points:&[Vec<f64>], x:&[f64])->usize{
+    let distance = |point:&Vec<f64>| point.iter().zip(x).map(|(a, b)| (a - b).powi(2)).sum::<f64>();
+    (0..points.len()).min_by(|&a, &b| distance(&points[a]).partial_cmp(&distance(&points[b])).unwrap()).unwrap()
+}
+
+///struct used to configure MAP-Elites<BR>
+/// there is a Default configuration
+#[derive(Clone, Debug)]
+pub struct MapElitesConfig{
+    ///number of offspring produced and inserted at each step
+    pub batch_size:usize,
+}
+///default MAP-Elites configuration
+impl Default for MapElitesConfig{
+    fn default() -> Self {
+        MapElitesConfig{batch_size:100}
+    }
+}
+
+///MAP-Elites quality-diversity search<BR>
+/// the descriptor function returns the behavior of an individual, one value per archive dimension<BR>
+/// Config::num_individuals random individuals seed the archive, offspring are drawn at random while it is empty
+pub struct MapElites<F,D>
+where F:Fn(Population)->f64, D:Fn(Population)->Vec<f64>{
+    pub archive:Archive,
+    ///one individual per filled cell
+    pub elites:Population,
+    ///index in elites of the elite of each cell
+    cells:Vec<Option<usize>>,
+    ///fitness of each elite
+    scores:Vec<f64>,
+    initialization:InitializationStrategy,
+    fitness:F,
+    descriptor:D,
+    config:Config,
+    map_config:MapElitesConfig,
+}
+impl<F,D> MapElites<F,D>
+where F:Fn(Population)->f64, D:Fn(Population)->Vec<f64>{
+    pub fn new(initialization:InitializationStrategy, fitness:F, descriptor:D, config:Config, archive:Archive, map_config:MapElitesConfig) -> Self{
+        let population = initialization.initialize(config.clone());
+        let cells = vec![None; archive.num_cells()];
+        let mut map_elites = MapElites{archive, elites:population.select(&[]), cells, scores:Vec::new(), initialization, fitness, descriptor, config, map_config};
+        map_elites.insert(&population);
+        map_elites
+    }
+    ///evaluate individuals and keep those better than the elite of their cell
+    fn insert(&mut self, individuals:&Population){
+        for i in 0..individuals.len() {
+            let score = (self.fitness)(individuals.select(&[i]));
+            let cell = self.archive.cell(&(self.descriptor)(individuals.select(&[i])));
+            match self.cells[cell] {
+                Some(elite) if self.scores[elite] >= score => {}
+                Some(elite) => {
+                    self.elites.replace(elite, individuals, i);
+                    self.scores[elite] = score;
+                }
+                None => {
+                    self.elites.push(individuals, i);
+                    self.cells[cell] = Some(self.scores.len());
+                    self.scores.push(score);
+                }
+            }
+        }
+    }
+    ///fraction of cells holding an elite
+    pub fn coverage(&self)->f64{
+        self.scores.len() as f64 / self.cells.len() as f64
+    }
+    ///sum over filled cells of the elite fitness minus offset<BR>
+    /// offset should be a lower bound of the fitness so that every elite contributes positively
+    pub fn qd_score(&self, offset:f64)->f64{
+        self.scores.iter().map(|score| score - offset).sum()
+    }
+    ///best elite of the archive and its fitness
+    pub fn best(&self)->Option<(Population,f64)>{
+        (0..self.scores.len())
+            .max_by(|&a, &b| self.scores[a].partial_cmp(&self.scores[b]).unwrap())
+            .map(|best| (self.elites.select(&[best]), self.scores[best]))
+    }
+    ///elite of a cell and its fitness, None if the cell is empty
+    pub fn elite(&self, cell:usize)->Option<(Population,f64)>{
+        self.cells[cell].map(|elite| (self.elites.select(&[elite]), self.scores[elite]))
+    }
+    ///fitness of the elites of a 2-D archive as rows of the second dimension, NaN for empty cells<BR>
+    /// CVT archives are sampled on a resolution x resolution grid (grid archives ignore resolution)
+    pub fn fitness_grid(&self, resolution:usize)->Vec<Vec<f64>>{
+        let ranges = self.archive.ranges();
+        assert_eq!(ranges.len(), 2, "Only 2-D archives can be drawn");
+        let score = |cell:usize| self.cells[cell].map_or(f64::NAN, |elite| self.scores[elite]);
+        match &self.archive {
+            Archive::Grid{bins, ..} => (0..bins[1]).map(|row| (0..bins[0]).map(|col| score(row * bins[0] + col)).collect()).collect(),
+            Archive::CVT{..} => {
+                let center = |range:&Range<f64>, i:usize| range.start + (i as f64 + 0.5) / resolution as f64 * (range.end - range.start);
+                (0..resolution).map(|row| (0..resolution)
+                    .map(|col| score(self.archive.cell(&[center(&ranges[0], col), center(&ranges[1], row)])))
+                    .collect()).collect()
+            }
+        }
+    }
+    ///draw the fitness of the elites of a 2-D archive as a heatmap (see fitness_grid)
+    pub fn draw(&self, filename:&str){
+        draw_heatmap(self.fitness_grid(200), filename);
+    }
+    ///evolve the archive forward by one step<BR>
+    /// in particular:<BR>
+    /// pick MapElitesConfig::batch_size random elites<BR>
+    /// mate and mutate them (or draw random individuals if the archive is empty)<BR>
+    /// insert offspring in the archive<BR>
+    /// returns best score of the archive
+    pub fn step(&mut self)->f64{
+        let offspring = if self.elites.is_empty() {
+            self.initialization.initialize(Config{num_individuals:self.map_config.batch_size, ..self.config.clone()})
+        } else {
+            let mut rng = rand::thread_rng();
+            let parents:Vec<usize> = (0..self.map_config.batch_size).map(|_| rng.gen_range(0..self.elites.len())).collect();
+            self.elites.select(&parents).mate_pairs().mutate(&self.config)
+        };
+        self.insert(&offspring);
+        let best = self.scores.iter().cloned().reduce(f64::max).unwrap_or(f64::NEG_INFINITY);
+        if self.config.verbose {
+            print!("Coverage = {} .... best score = {}",self.coverage(),best);
+        }
+        best
+    }
+    ///execute num_steps forward of evolution, return a vector of scores (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        for i in 0..num_steps{
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::RandomInitialization;
+
+    #[test]
+    fn grid_cells_clamp_to_border_bins(){
+        let archive = Archive::grid(vec![4, 2], vec![0.0..1.0, 0.0..1.0]);
+        assert_eq!(archive.num_cells(), 8);
+        assert_eq!(archive.cell(&[0.3, 0.8]), 5);
+        assert_eq!(archive.cell(&[-1.0, 2.0]), 4);
+        assert_eq!(archive.cell(&[1.0, 0.0]), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_rejects_empty_dimensions(){
+        Archive::grid(vec![3, 0], vec![0.0..1.0, 0.0..1.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "A CVT archive needs at least one cell")]
+    fn cvt_rejects_zero_cells(){
+        Archive::cvt(0, vec![0.0..1.0], 100);
+    }
+
+    #[test]
+    fn step_seeds_an_empty_archive_at_random(){
+        let config = Config{num_individuals:0, num_genes:2, range:0.0..1.0, verbose:false, ..Config::default()};
+        let fitness = |individual:Population| match individual {
+            Population::F64(vec) => vec[0].iter().sum::<f64>(),
+            _ => unreachable!(),
+        };
+        let descriptor = |individual:Population| match individual {
+            Population::F64(vec) => vec[0].clone(),
+            _ => unreachable!(),
+        };
+        let archive = Archive::grid(vec![5, 5], vec![0.0..1.0, 0.0..1.0]);
+        let mut map_elites = MapElites::new(InitializationStrategy::F64(Box::new(RandomInitialization)), fitness, descriptor, config, archive, MapElitesConfig{batch_size:20});
+        assert!(map_elites.best().is_none());
+        let best = map_elites.step();
+        assert!(map_elites.coverage() > 0.0);
+        assert_eq!(map_elites.best().unwrap().1, best);
+        map_elites.step();
+    }
+}
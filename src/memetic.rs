@@ -0,0 +1,218 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use crate::gp::{Node, Tree};
+use crate::population::{Config, Population};
+
+///local search used by GA to refine offspring (memetic algorithm)<BR>
+/// Config::local_search_budget is the maximum number of fitness evaluations, returns the improved individual and its fitness
+pub trait LocalSearch<T>{
+    fn improve(&self, individual:T, fitness:&dyn Fn(Population)->f64, config:&Config)->(T,f64);
+}
+pub enum LocalSearchStrategy{
+    Usize(Box<dyn LocalSearch<Vec<usize>> + Send>),
+    F64(Box<dyn LocalSearch<Vec<f64>> + Send>),
+    Tree(Box<dyn LocalSearch<Tree> + Send>),
+}
+///how the result of local search is used
+#[derive(Clone, Debug)]
+pub enum Learning{
+    ///improved genomes replace the offspring
+    Lamarckian,
+    ///offspring keep their genomes but are ranked with the fitness reached after local search
+    Baldwinian,
+}
+
+///stochastic hill climbing for Vec<f64> individuals<BR>
+/// perturbs one gene at a time with gaussian noise (clamped to Config::range) and keeps the change if fitness improves
+pub struct HillClimbing{
+    pub step_size:f64,
+}
+impl LocalSearch<Vec<f64>> for HillClimbing{
+    fn improve(&self, individual: Vec<f64>, fitness: &dyn Fn(Population) -> f64, config: &Config) -> (Vec<f64>, f64) {
+        let mut rng = rand::thread_rng();
+        let normal = Normal::new(0.0, self.step_size).unwrap();
+        let mut best_score = fitness(Population::F64(vec![individual.clone()]));
+        let mut best = individual;
+        if best.is_empty() {
+            return (best, best_score);
+        }
+        for _ in 1..config.local_search_budget {
+            let mut candidate = best.clone();
+            let gene = rng.gen_range(0..candidate.len());
+            candidate[gene] = (candidate[gene] + normal.sample(&mut rng)).clamp(config.range.start, config.range.end);
+            let score = fitness(Population::F64(vec![candidate.clone()]));
+            if score > best_score {
+                best = candidate;
+                best_score = score;
+            }
+        }
+        (best, best_score)
+    }
+}
+
+///2-opt for Vec<usize> permutations<BR>
+/// reverses the segment between two positions, accepting the first improving move, until no move improves or budget is exhausted
+pub struct TwoOpt;
+impl LocalSearch<Vec<usize>> for TwoOpt{
+    fn improve(&self, individual: Vec<usize>, fitness: &dyn Fn(Population) -> f64, config: &Config) -> (Vec<usize>, f64) {
+        let budget = config.local_search_budget;
+        let mut best_score = fitness(Population::Usize(vec![individual.clone()]));
+        let mut best = individual;
+        let mut evaluations = 1;
+        let mut improved = true;
+        while improved && evaluations < budget {
+            improved = false;
+            'moves: for i in 0..best.len() {
+                for j in i + 1..best.len() {
+                    if evaluations >= budget {
+                        break 'moves;
+                    }
+                    let mut candidate = best.clone();
+                    candidate[i..=j].reverse();
+                    let score = fitness(Population::Usize(vec![candidate.clone()]));
+                    evaluations += 1;
+                    if score > best_score {
+                        best = candidate;
+                        best_score = score;
+                        improved = true;
+                        break 'moves;
+                    }
+                }
+            }
+        }
+        (best, best_score)
+    }
+}
+
+///Or-opt for Vec<usize> permutations<BR>
+/// moves segments of 1 to 3 consecutive genes to another position, accepting the first improving move, until no move improves or budget is exhausted
+pub struct OrOpt;
+impl LocalSearch<Vec<usize>> for OrOpt{
+    fn improve(&self, individual: Vec<usize>, fitness: &dyn Fn(Population) -> f64, config: &Config) -> (Vec<usize>, f64) {
+        let budget = config.local_search_budget;
+        let mut best_score = fitness(Population::Usize(vec![individual.clone()]));
+        let mut best = individual;
+        let mut evaluations = 1;
+        let mut improved = true;
+        while improved && evaluations < budget {
+            improved = false;
+            'moves: for length in 1..=3.min(best.len().saturating_sub(1)) {
+                for start in 0..=best.len() - length {
+                    for target in 0..=best.len() - length {
+                        if target == start {
+                            continue;
+                        }
+                        if evaluations >= budget {
+                            break 'moves;
+                        }
+                        let mut candidate = best.clone();
+                        let segment:Vec<usize> = candidate.drain(start..start + length).collect();
+                        candidate.splice(target..target, segment);
+                        let score = fitness(Population::Usize(vec![candidate.clone()]));
+                        evaluations += 1;
+                        if score > best_score {
+                            best = candidate;
+                            best_score = score;
+                            improved = true;
+                            break 'moves;
+                        }
+                    }
+                }
+            }
+        }
+        (best, best_score)
+    }
+}
+
+///constant tuning for trees<BR>
+/// perturbs one Node::Constant at a time with gaussian noise and keeps the change if fitness improves
+pub struct ConstantTuning{
+    pub step_size:f64,
+}
+impl LocalSearch<Tree> for ConstantTuning{
+    fn improve(&self, individual: Tree, fitness: &dyn Fn(Population) -> f64, config: &Config) -> (Tree, f64) {
+        let mut rng = rand::thread_rng();
+        let normal = Normal::new(0.0, self.step_size).unwrap();
+        let constants:Vec<usize> = (0..individual.nodes.len()).filter(|&i| matches!(individual.nodes[i], Node::Constant(..))).collect();
+        let mut best_score = fitness(Population::Tree(vec![individual.clone()]));
+        let mut best = individual;
+        if constants.is_empty() {
+            return (best, best_score);
+        }
+        for _ in 1..config.local_search_budget {
+            let mut candidate = best.clone();
+            let index = constants[rng.gen_range(0..constants.len())];
+            if let Node::Constant(value, ret) = candidate.nodes[index] {
+                candidate.nodes[index] = Node::Constant(value + normal.sample(&mut rng), ret);
+            }
+            let score = fitness(Population::Tree(vec![candidate.clone()]));
+            if score > best_score {
+                best = candidate;
+                best_score = score;
+            }
+        }
+        (best, best_score)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::{GA, GetPopulation, InitializationStrategy, RandomInitialization};
+
+    fn sphere(population:Population)->f64{
+        let genes:Vec<f64> = population.get_individual(0).unwrap();
+        -genes.iter().map(|x| x * x).sum::<f64>()
+    }
+
+    fn ga(learning:Learning)->GA<fn(Population)->f64>{
+        let config = Config{num_individuals:20, num_genes:3, range:1.0..2.0, local_search_rate:1.0, local_search_budget:50, learning, verbose:false, ..Config::default()};
+        GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), sphere as fn(Population)->f64, config)
+            .with_local_search(LocalSearchStrategy::F64(Box::new(HillClimbing{step_size:0.1})))
+    }
+
+    fn individuals(ga:&GA<fn(Population)->f64>)->Vec<Vec<f64>>{
+        (0..ga.population().len()).map(|i| ga.population().get_individual(i).unwrap()).collect()
+    }
+
+    #[test]
+    fn hill_climbing_stays_in_range(){
+        let config = Config{range:-1.0..1.0, local_search_budget:200, ..Config::default()};
+        let sum = |population:Population| GetPopulation::<f64>::get_individual(&population, 0).unwrap().iter().sum::<f64>();
+        let (individual, score) = HillClimbing{step_size:0.5}.improve(vec![0.9, -0.9], &sum, &config);
+        assert!(individual.iter().all(|gene| (-1.0..=1.0).contains(gene)), "{:?}", individual);
+        assert!(score > 0.0);
+    }
+
+    #[test]
+    fn hill_climbing_returns_empty_individuals(){
+        let (individual, score) = HillClimbing{step_size:0.5}.improve(Vec::new(), &|_| 1.0, &Config::default());
+        assert!(individual.is_empty());
+        assert_eq!(score, 1.0);
+    }
+
+    #[test]
+    fn lamarckian_learning_writes_improved_genomes_back(){
+        let mut ga = ga(Learning::Lamarckian);
+        let before = individuals(&ga);
+        ga.refine();
+        let after = individuals(&ga);
+        assert_ne!(before, after);
+        for (i, score) in ga.evaluate().into_iter().enumerate() {
+            assert_eq!(score, sphere(Population::F64(vec![after[i].clone()])));
+            assert!(score >= sphere(Population::F64(vec![before[i].clone()])));
+        }
+    }
+
+    #[test]
+    fn baldwinian_learning_only_changes_scores(){
+        let ga = ga(Learning::Baldwinian);
+        let before = individuals(&ga);
+        let scores = ga.evaluate();
+        assert_eq!(individuals(&ga), before);
+        for (individual, score) in before.iter().zip(&scores) {
+            assert!(*score >= sphere(Population::F64(vec![individual.clone()])));
+        }
+        assert!(before.iter().zip(&scores).any(|(individual, score)| *score > sphere(Population::F64(vec![individual.clone()]))));
+    }
+}
@@ -0,0 +1,612 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Instant;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use rand_distr::{Distribution, Normal};
+use crate::population::Config;
+
+///activation function of a neuron
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activation{
+    ///1/(1+exp(-4.9x)) as in the original NEAT paper
+    SteepenedSigmoid,
+    Sigmoid,
+    Tanh,
+    ReLU,
+    Identity,
+}
+impl Activation{
+    pub fn apply(&self, x:f64)->f64{
+        match self {
+            Activation::SteepenedSigmoid => 1.0 / (1.0 + (-4.9 * x).exp()),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(0.0),
+            Activation::Identity => x,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeKind{
+    Input,
+    ///always outputs 1
+    Bias,
+    Hidden,
+    Output,
+}
+#[derive(Clone, Debug)]
+pub struct NodeGene{
+    pub id:usize,
+    pub kind:NodeKind,
+    pub activation:Activation,
+}
+#[derive(Clone, Debug)]
+pub struct ConnectionGene{
+    pub input:usize,
+    pub output:usize,
+    pub weight:f64,
+    pub enabled:bool,
+    ///historical marking used to align genes during crossover and speciation
+    pub innovation:usize,
+}
+///genome made of node and connection genes (connections sorted by innovation number)
+#[derive(Clone, Debug)]
+pub struct Genome{
+    pub nodes:Vec<NodeGene>,
+    pub connections:Vec<ConnectionGene>,
+}
+
+///computed node of a network with its incoming (source, weight) links
+#[derive(Clone, Debug)]
+struct Neuron{
+    node:usize,
+    activation:Activation,
+    links:Vec<(usize,f64)>,
+}
+///feed-forward network decoded from a genome
+#[derive(Clone, Debug)]
+pub struct Network{
+    inputs:Vec<usize>,
+    bias:Vec<usize>,
+    outputs:Vec<usize>,
+    ///neurons in topological order
+    order:Vec<Neuron>,
+    num_nodes:usize,
+}
+impl Network{
+    ///compute outputs for the given inputs (one value per input node)
+    pub fn activate(&self, inputs:&[f64])->Vec<f64>{
+        let mut values = vec![0.0; self.num_nodes];
+        for (&node, &value) in self.inputs.iter().zip(inputs) {
+            values[node] = value;
+        }
+        for &node in &self.bias {
+            values[node] = 1.0;
+        }
+        for neuron in &self.order {
+            let sum:f64 = neuron.links.iter().map(|&(source, weight)| values[source] * weight).sum();
+            values[neuron.node] = neuron.activation.apply(sum);
+        }
+        self.outputs.iter().map(|&node| values[node]).collect()
+    }
+}
+
+impl Genome{
+    ///decode the genome into a feed-forward network, nodes unreachable from the inputs output activation(0)
+    pub fn network(&self)->Network{
+        let position:HashMap<usize,usize> = self.nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect();
+        let mut incoming:Vec<Vec<(usize,f64)>> = vec![Vec::new(); self.nodes.len()];
+        for connection in self.connections.iter().filter(|c| c.enabled) {
+            incoming[position[&connection.output]].push((position[&connection.input], connection.weight));
+        }
+        //Kahn's algorithm on enabled connections
+        let mut pending:Vec<usize> = incoming.iter().map(|links| links.len()).collect();
+        let mut ready:Vec<usize> = (0..self.nodes.len()).filter(|&i| pending[i] == 0).collect();
+        let mut order = Vec::new();
+        while let Some(node) = ready.pop() {
+            if matches!(self.nodes[node].kind, NodeKind::Hidden | NodeKind::Output) {
+                order.push(Neuron{node, activation:self.nodes[node].activation, links:incoming[node].clone()});
+            }
+            for connection in self.connections.iter().filter(|c| c.enabled && position[&c.input] == node) {
+                let target = position[&connection.output];
+                pending[target] -= 1;
+                if pending[target] == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+        let select = |kind:NodeKind| (0..self.nodes.len()).filter(|&i| self.nodes[i].kind == kind).collect::<Vec<usize>>();
+        Network{inputs:select(NodeKind::Input), bias:select(NodeKind::Bias), outputs:select(NodeKind::Output), order, num_nodes:self.nodes.len()}
+    }
+    ///true if target can be reached from source following enabled or disabled connections
+    fn reaches(&self, source:usize, target:usize)->bool{
+        let mut stack = vec![source];
+        let mut seen = vec![source];
+        while let Some(node) = stack.pop() {
+            if node == target {
+                return true;
+            }
+            for connection in self.connections.iter().filter(|c| c.input == node) {
+                if !seen.contains(&connection.output) {
+                    seen.push(connection.output);
+                    stack.push(connection.output);
+                }
+            }
+        }
+        false
+    }
+    ///compatibility distance c1*E/N + c2*D/N + c3*W between two genomes
+    pub fn distance(&self, other:&Genome, config:&NEATConfig)->f64{
+        let (mut i, mut j) = (0, 0);
+        let (mut disjoint, mut matching, mut weight_difference) = (0, 0, 0.0);
+        let (a, b) = (&self.connections, &other.connections);
+        while i < a.len() && j < b.len() {
+            if a[i].innovation == b[j].innovation {
+                matching += 1;
+                weight_difference += (a[i].weight - b[j].weight).abs();
+                i += 1;
+                j += 1;
+            } else if a[i].innovation < b[j].innovation {
+                disjoint += 1;
+                i += 1;
+            } else {
+                disjoint += 1;
+                j += 1;
+            }
+        }
+        let excess = (a.len() - i) + (b.len() - j);
+        let n = a.len().max(b.len());
+        let n = if n < 20 {1.0} else {n as f64};
+        let average_weight = if matching > 0 {weight_difference / matching as f64} else {0.0};
+        config.excess_coefficient * excess as f64 / n + config.disjoint_coefficient * disjoint as f64 / n + config.weight_coefficient * average_weight
+    }
+}
+
+///struct used to configure NEAT<BR>
+/// population size is taken from Config::num_individuals
+#[derive(Clone, Debug)]
+pub struct NEATConfig{
+    pub num_inputs:usize,
+    pub num_outputs:usize,
+    pub hidden_activation:Activation,
+    pub output_activation:Activation,
+    ///range of initial and replaced weights
+    pub weight_range:Range<f64>,
+    pub excess_coefficient:f64,
+    pub disjoint_coefficient:f64,
+    pub weight_coefficient:f64,
+    ///genomes closer than this distance to a species representative belong to the species
+    pub compatibility_threshold:f64,
+    ///probability of mutating the weights of a genome
+    pub weight_mutation_rate:f64,
+    ///probability for a mutated weight of being replaced rather than perturbed
+    pub weight_replace_rate:f64,
+    ///standard deviation of weight perturbations
+    pub weight_power:f64,
+    pub add_connection_rate:f64,
+    pub add_node_rate:f64,
+    ///probability of producing an offspring by crossover rather than by mutation only
+    pub crossover_rate:f64,
+    ///species not improving for this many generations stop reproducing (unless it is the best one)
+    pub stagnation_limit:usize,
+    ///fraction of each species allowed to reproduce
+    pub survival_threshold:f64,
+}
+///default NEAT configuration
+impl Default for NEATConfig{
+    fn default() -> Self {
+        NEATConfig{
+            num_inputs:2,
+            num_outputs:1,
+            hidden_activation:Activation::SteepenedSigmoid,
+            output_activation:Activation::SteepenedSigmoid,
+            weight_range:-1.0..1.0,
+            excess_coefficient:1.0,
+            disjoint_coefficient:1.0,
+            weight_coefficient:0.4,
+            compatibility_threshold:3.0,
+            weight_mutation_rate:0.8,
+            weight_replace_rate:0.1,
+            weight_power:0.5,
+            add_connection_rate:0.05,
+            add_node_rate:0.03,
+            crossover_rate:0.75,
+            stagnation_limit:15,
+            survival_threshold:0.2,
+        }
+    }
+}
+
+struct Species{
+    representative:Genome,
+    members:Vec<usize>,
+    best_fitness:f64,
+    stagnation:usize,
+}
+
+///NeuroEvolution of Augmenting Topologies: evolves weights and structure of feed-forward networks
+pub struct NEAT<F>
+where F:Fn(&Network)->f64{
+    pub genomes:Vec<Genome>,
+    scores:Vec<f64>,
+    species:Vec<Species>,
+    fitness:F,
+    config:Config,
+    neat_config:NEATConfig,
+    ///innovation number of each (input, output) connection created so far
+    innovations:HashMap<(usize,usize),usize>,
+    ///node created by splitting a connection innovation during the current generation
+    splits:HashMap<usize,usize>,
+    next_node:usize,
+    best:Option<(Genome,f64)>,
+}
+impl<F> NEAT<F>
+where F:Fn(&Network)->f64{
+    ///initialize a population of minimal genomes: every input and the bias connected to every output
+    pub fn new(fitness:F, config:Config, neat_config:NEATConfig) -> Self{
+        let mut nodes = Vec::new();
+        for id in 0..neat_config.num_inputs {
+            nodes.push(NodeGene{id, kind:NodeKind::Input, activation:Activation::Identity});
+        }
+        nodes.push(NodeGene{id:neat_config.num_inputs, kind:NodeKind::Bias, activation:Activation::Identity});
+        for k in 0..neat_config.num_outputs {
+            nodes.push(NodeGene{id:neat_config.num_inputs + 1 + k, kind:NodeKind::Output, activation:neat_config.output_activation});
+        }
+        let mut neat = NEAT{genomes:Vec::new(), scores:Vec::new(), species:Vec::new(), fitness, config, neat_config,
+            innovations:HashMap::new(), splits:HashMap::new(), next_node:nodes.len(), best:None};
+        let mut rng = rand::thread_rng();
+        for _ in 0..neat.config.num_individuals {
+            let mut connections = Vec::new();
+            for input in 0..=neat.neat_config.num_inputs {
+                for k in 0..neat.neat_config.num_outputs {
+                    let output = neat.neat_config.num_inputs + 1 + k;
+                    let weight = rng.gen_range(neat.neat_config.weight_range.clone());
+                    connections.push(ConnectionGene{input, output, weight, enabled:true, innovation:neat.innovation(input, output)});
+                }
+            }
+            neat.genomes.push(Genome{nodes:nodes.clone(), connections});
+        }
+        neat
+    }
+    ///innovation number of a connection, a new one is assigned the first time a connection appears
+    fn innovation(&mut self, input:usize, output:usize)->usize{
+        let next = self.innovations.len();
+        *self.innovations.entry((input, output)).or_insert(next)
+    }
+    ///best genome found so far and its fitness
+    pub fn best(&self)->Option<(Genome,f64)>{
+        self.best.clone()
+    }
+    fn mutate_weights(&self, genome:&mut Genome){
+        let mut rng = rand::thread_rng();
+        let normal = Normal::new(0.0, self.neat_config.weight_power).unwrap();
+        for connection in genome.connections.iter_mut() {
+            if rng.gen::<f64>() < self.neat_config.weight_replace_rate {
+                connection.weight = rng.gen_range(self.neat_config.weight_range.clone());
+            } else {
+                connection.weight += normal.sample(&mut rng);
+            }
+        }
+    }
+    ///add a connection between two unconnected nodes, never creating a cycle
+    fn add_connection(&mut self, genome:&mut Genome){
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let source = genome.nodes.choose(&mut rng).unwrap();
+            let target = genome.nodes.choose(&mut rng).unwrap();
+            if matches!(target.kind, NodeKind::Input | NodeKind::Bias) || source.kind == NodeKind::Output || source.id == target.id {
+                continue;
+            }
+            let (input, output) = (source.id, target.id);
+            if genome.connections.iter().any(|c| c.input == input && c.output == output) || genome.reaches(output, input) {
+                continue;
+            }
+            let innovation = self.innovation(input, output);
+            let weight = rng.gen_range(self.neat_config.weight_range.clone());
+            genome.connections.push(ConnectionGene{input, output, weight, enabled:true, innovation});
+            genome.connections.sort_by_key(|c| c.innovation);
+            return;
+        }
+    }
+    ///split an enabled connection: input->new node with weight 1, new node->output with the old weight
+    fn add_node(&mut self, genome:&mut Genome){
+        let enabled:Vec<usize> = (0..genome.connections.len()).filter(|&i| genome.connections[i].enabled).collect();
+        let Some(&index) = enabled.choose(&mut rand::thread_rng()) else {
+            return;
+        };
+        let old = genome.connections[index].clone();
+        let next = self.next_node;
+        let node = *self.splits.entry(old.innovation).or_insert(next);
+        if node == next {
+            self.next_node += 1;
+        }
+        if genome.nodes.iter().any(|n| n.id == node) {
+            return;
+        }
+        genome.connections[index].enabled = false;
+        genome.nodes.push(NodeGene{id:node, kind:NodeKind::Hidden, activation:self.neat_config.hidden_activation});
+        let first = self.innovation(old.input, node);
+        let second = self.innovation(node, old.output);
+        genome.connections.push(ConnectionGene{input:old.input, output:node, weight:1.0, enabled:true, innovation:first});
+        genome.connections.push(ConnectionGene{input:node, output:old.output, weight:old.weight, enabled:true, innovation:second});
+        genome.connections.sort_by_key(|c| c.innovation);
+    }
+    ///matching genes are inherited at random, disjoint and excess genes from the fitter parent<BR>
+    /// a gene disabled in either parent is disabled with probability 0.75
+    fn crossover(&self, fitter:&Genome, other:&Genome)->Genome{
+        let mut rng = rand::thread_rng();
+        let others:HashMap<usize,&ConnectionGene> = other.connections.iter().map(|c| (c.innovation, c)).collect();
+        let connections = fitter.connections.iter().map(|gene| {
+            let mut child_gene = match others.get(&gene.innovation) {
+                Some(&matching) if rng.gen::<bool>() => matching.clone(),
+                _ => gene.clone(),
+            };
+            if let Some(matching) = others.get(&gene.innovation) {
+                child_gene.enabled = (gene.enabled && matching.enabled) || rng.gen::<f64>() >= 0.75;
+            }
+            child_gene
+        }).collect();
+        Genome{nodes:fitter.nodes.clone(), connections}
+    }
+    ///assign every genome to the first species whose representative is close enough, or to a new species
+    fn speciate(&mut self){
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+        for (i, genome) in self.genomes.iter().enumerate() {
+            match self.species.iter_mut().find(|s| genome.distance(&s.representative, &self.neat_config) < self.neat_config.compatibility_threshold) {
+                Some(species) => species.members.push(i),
+                None => self.species.push(Species{representative:genome.clone(), members:vec![i], best_fitness:f64::MIN, stagnation:0}),
+            }
+        }
+        self.species.retain(|s| !s.members.is_empty());
+        let mut rng = rand::thread_rng();
+        for species in self.species.iter_mut() {
+            let best = species.members.iter().map(|&m| self.scores[m]).fold(f64::MIN, f64::max);
+            if best > species.best_fitness {
+                species.best_fitness = best;
+                species.stagnation = 0;
+            } else {
+                species.stagnation += 1;
+            }
+            species.representative = self.genomes[*species.members.choose(&mut rng).unwrap()].clone();
+        }
+    }
+    ///evolve population forward by one step<BR>
+    /// in particular:<BR>
+    /// evaluate genomes<BR>
+    /// speciate and drop stagnant species<BR>
+    /// share fitness within species and allocate offspring to species<BR>
+    /// reproduce each species (champion copy, crossover and mutations)<BR>
+    /// returns best score of the evaluated generation
+    pub fn step(&mut self)->f64{
+        self.scores = self.genomes.iter().map(|genome| (self.fitness)(&genome.network())).collect();
+        let (champion, best) = self.scores.iter().enumerate().max_by(|a,b| a.1.partial_cmp(b.1).unwrap()).map(|(i, &s)| (i, s)).unwrap();
+        if self.best.as_ref().map_or(true, |(_, score)| best > *score) {
+            self.best = Some((self.genomes[champion].clone(), best));
+        }
+        self.speciate();
+        let best_species = self.species.iter().position(|s| s.members.contains(&champion)).unwrap();
+        let limit = self.neat_config.stagnation_limit;
+        let mut index = 0;
+        self.species.retain(|s| {
+            index += 1;
+            s.stagnation < limit || index - 1 == best_species
+        });
+        //explicit fitness sharing: offspring proportional to the sum of member fitness divided by species size
+        let min = self.scores.iter().cloned().fold(f64::MAX, f64::min);
+        let adjusted:Vec<f64> = self.species.iter()
+            .map(|s| s.members.iter().map(|&m| self.scores[m] - min + 1e-9).sum::<f64>() / s.members.len() as f64)
+            .collect();
+        let total:f64 = adjusted.iter().sum();
+        let population = self.config.num_individuals;
+        let mut offspring:Vec<usize> = adjusted.iter().map(|a| (a / total * population as f64).floor() as usize).collect();
+        let mut assigned:usize = offspring.iter().sum();
+        let mut k = 0;
+        let num_species = offspring.len();
+        while assigned < population {
+            offspring[k % num_species] += 1;
+            assigned += 1;
+            k += 1;
+        }
+        self.splits.clear();
+        let mut rng = rand::thread_rng();
+        let mut next_generation = Vec::with_capacity(population);
+        let species_members:Vec<Vec<usize>> = self.species.iter().map(|s| {
+            let mut members = s.members.clone();
+            members.sort_by(|&a, &b| self.scores[b].partial_cmp(&self.scores[a]).unwrap());
+            members.truncate(((members.len() as f64 * self.neat_config.survival_threshold).ceil() as usize).max(1));
+            members
+        }).collect();
+        for (members, &count) in species_members.iter().zip(&offspring) {
+            for n in 0..count {
+                if n == 0 && count > 5 {
+                    next_generation.push(self.genomes[members[0]].clone());
+                    continue;
+                }
+                let parent1 = *members.choose(&mut rng).unwrap();
+                let mut child = if rng.gen::<f64>() < self.neat_config.crossover_rate && members.len() > 1 {
+                    let parent2 = *members.choose(&mut rng).unwrap();
+                    let (fitter, other) = if self.scores[parent1] >= self.scores[parent2] {(parent1, parent2)} else {(parent2, parent1)};
+                    self.crossover(&self.genomes[fitter], &self.genomes[other])
+                } else {
+                    self.genomes[parent1].clone()
+                };
+                if rng.gen::<f64>() < self.neat_config.weight_mutation_rate {
+                    self.mutate_weights(&mut child);
+                }
+                if rng.gen::<f64>() < self.neat_config.add_connection_rate {
+                    self.add_connection(&mut child);
+                }
+                if rng.gen::<f64>() < self.neat_config.add_node_rate {
+                    self.add_node(&mut child);
+                }
+                next_generation.push(child);
+            }
+        }
+        self.genomes = next_generation;
+        if self.config.verbose {
+            print!("Best score = {} with {} species",best,self.species.len());
+        }
+        best
+    }
+    ///execute num_steps forward of evolution, return a vector of scores (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        for i in 0..num_steps{
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn node(id:usize, kind:NodeKind)->NodeGene{
+        NodeGene{id, kind, activation:Activation::Identity}
+    }
+
+    fn connection(input:usize, output:usize, weight:f64, innovation:usize)->ConnectionGene{
+        ConnectionGene{input, output, weight, enabled:true, innovation}
+    }
+
+    ///genome with the given connection innovations and weights (all between nodes 0 and 1)
+    fn genome(genes:&[(usize,f64)])->Genome{
+        Genome{nodes:vec![node(0, NodeKind::Input), node(1, NodeKind::Output)],
+            connections:genes.iter().map(|&(innovation, weight)| connection(0, 1, weight, innovation)).collect()}
+    }
+
+    fn neat(num_inputs:usize)->NEAT<fn(&Network)->f64>{
+        let config = Config{num_individuals:10, verbose:false, ..Config::default()};
+        NEAT::new((|_| 0.0) as fn(&Network)->f64, config, NEATConfig{num_inputs, num_outputs:1, ..NEATConfig::default()})
+    }
+
+    #[test]
+    fn compatibility_distance_counts_excess_disjoint_and_weights(){
+        let a = genome(&[(0, 0.5), (1, 1.0), (2, 0.0), (5, 0.0)]);
+        let b = genome(&[(0, 0.0), (1, 2.0), (3, 0.0)]);
+        let config = NEATConfig{excess_coefficient:2.0, disjoint_coefficient:3.0, weight_coefficient:0.5, ..NEATConfig::default()};
+        //1 excess gene (5), 2 disjoint genes (2 and 3), average weight difference 0.75 over 2 matching genes
+        assert!((a.distance(&b, &config) - 8.375).abs() < 1e-12);
+        assert!((b.distance(&a, &config) - 8.375).abs() < 1e-12);
+        assert_eq!(a.distance(&a, &config), 0.0);
+        //genomes of 20 genes or more are normalized by their size
+        let long:Vec<(usize,f64)> = (0..20).map(|i| (i, 0.0)).collect();
+        let longer:Vec<(usize,f64)> = (0..40).map(|i| (i, 0.0)).collect();
+        assert!((genome(&long).distance(&genome(&longer), &config) - 2.0 * 20.0 / 40.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn innovation_numbers_are_reused_within_a_generation(){
+        let mut neat = neat(1);
+        //disable the bias connection so that both genomes split the input connection
+        let mut first = neat.genomes[0].clone();
+        first.connections.retain(|c| c.input == 0);
+        let mut second = first.clone();
+        let mut third = first.clone();
+        neat.add_node(&mut first);
+        neat.add_node(&mut second);
+        assert_eq!(first.nodes.last().unwrap().id, second.nodes.last().unwrap().id);
+        let innovations = |genome:&Genome| genome.connections.iter().map(|c| c.innovation).collect::<Vec<usize>>();
+        assert_eq!(innovations(&first), innovations(&second));
+        //a new generation splitting the same connection gets a new node
+        neat.splits.clear();
+        neat.add_node(&mut third);
+        assert_ne!(third.nodes.last().unwrap().id, first.nodes.last().unwrap().id);
+        //the same connection always gets the same innovation number
+        assert_eq!(neat.innovation(0, 2), first.connections[0].innovation);
+        let next = neat.innovations.len();
+        assert_eq!(neat.innovation(1, 2), neat.genomes[0].connections[1].innovation);
+        assert_eq!(neat.innovations.len(), next);
+    }
+
+    #[test]
+    fn crossover_aligns_matching_genes(){
+        let neat = neat(1);
+        let fitter = genome(&[(0, 1.0), (1, 1.0), (2, 1.0)]);
+        let other = genome(&[(0, 2.0), (1, 2.0), (3, 2.0), (4, 2.0)]);
+        let mut inherited = [false, false];
+        for _ in 0..100 {
+            let child = neat.crossover(&fitter, &other);
+            //genes of the fitter parent only, disjoint and excess genes of the other parent are dropped
+            assert_eq!(child.connections.iter().map(|c| c.innovation).collect::<Vec<usize>>(), vec![0, 1, 2]);
+            assert_eq!(child.connections[2].weight, 1.0);
+            for gene in &child.connections[..2] {
+                inherited[(gene.weight == 2.0) as usize] = true;
+            }
+        }
+        assert_eq!(inherited, [true, true]);
+    }
+
+    #[test]
+    fn crossover_disables_genes_disabled_in_a_parent(){
+        let neat = neat(1);
+        let mut fitter = genome(&[(0, 1.0)]);
+        fitter.connections[0].enabled = false;
+        let other = genome(&[(0, 1.0)]);
+        let disabled = (0..1000).filter(|_| !neat.crossover(&fitter, &other).connections[0].enabled).count();
+        assert!((650..850).contains(&disabled), "{}", disabled);
+    }
+
+    #[test]
+    fn network_is_evaluated_in_topological_order(){
+        //inputs 0 and 1, bias 2, output 3, hidden 4 and 5 with connections listed out of order
+        let mut genome = Genome{
+            nodes:vec![node(0, NodeKind::Input), node(1, NodeKind::Input), node(2, NodeKind::Bias), node(3, NodeKind::Output), node(4, NodeKind::Hidden), node(5, NodeKind::Hidden)],
+            connections:vec![connection(5, 3, 2.0, 0), connection(4, 5, 1.0, 1), connection(0, 4, 1.0, 2), connection(1, 4, 1.0, 3), connection(2, 3, -1.0, 4), connection(0, 3, 10.0, 5)],
+        };
+        genome.connections[5].enabled = false;
+        assert_eq!(genome.network().activate(&[1.0, 2.0]), vec![5.0]);
+        genome.nodes[4].activation = Activation::ReLU;
+        assert_eq!(genome.network().activate(&[1.0, -3.0]), vec![-1.0]);
+    }
+
+    #[test]
+    fn added_connections_keep_networks_acyclic(){
+        let mut neat = neat(3);
+        let mut genome = neat.genomes[0].clone();
+        for _ in 0..5 {
+            neat.add_node(&mut genome);
+        }
+        for _ in 0..50 {
+            neat.add_connection(&mut genome);
+        }
+        //Kahn's algorithm orders every hidden and output node only if there is no cycle
+        let computed = genome.nodes.iter().filter(|n| matches!(n.kind, NodeKind::Hidden | NodeKind::Output)).count();
+        assert_eq!(genome.network().order.len(), computed);
+        assert!(genome.connections.windows(2).all(|pair| pair[0].innovation < pair[1].innovation));
+    }
+
+    #[test]
+    fn xor_smoke(){
+        fn xor(network:&Network)->f64{
+            let cases = [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)];
+            4.0 - cases.iter().map(|(inputs, target)| (network.activate(inputs)[0] - target).powi(2)).sum::<f64>()
+        }
+        //a run can stall in the local optimum of XOR, one of a few independent runs is expected to solve it
+        let solved = (0..3).any(|_| {
+            let config = Config{num_individuals:150, verbose:false, ..Config::default()};
+            let mut neat = NEAT::new(xor, config, NEATConfig::default());
+            neat.evolve(100);
+            let (genome, score) = neat.best().unwrap();
+            let network = genome.network();
+            score > 3.5 && [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)].iter()
+                .all(|(inputs, target)| (network.activate(inputs)[0] - target).abs() < 0.5)
+        });
+        assert!(solved);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::neat::Activation;
+use crate::population::{Config, Population};
+
+///dense feed-forward network with fixed topology whose weights are evolved as a Population::F64 genome<BR>
+/// genome layout: for each layer, for each neuron, the bias followed by one weight per input of the layer
+#[derive(Clone, Debug)]
+pub struct DenseNetwork{
+    pub num_inputs:usize,
+    ///size and activation of each layer, the last one being the output layer
+    pub layers:Vec<(usize, Activation)>,
+}
+impl DenseNetwork{
+    pub fn new(num_inputs:usize, layers:Vec<(usize, Activation)>) -> Self{
+        DenseNetwork{num_inputs, layers}
+    }
+    ///number of weights and biases, to be used as Config::num_genes
+    pub fn num_genes(&self)->usize{
+        let mut inputs = self.num_inputs;
+        let mut genes = 0;
+        for &(size, _) in &self.layers {
+            genes += (inputs + 1) * size;
+            inputs = size;
+        }
+        genes
+    }
+    ///copy of a configuration with num_genes matching the network
+    pub fn configure(&self, config:Config)->Config{
+        Config{num_genes:self.num_genes(), ..config}
+    }
+    ///network whose weights are read from an individual
+    pub fn model<'a>(&'a self, weights:&'a [f64])->Model<'a>{
+        assert_eq!(weights.len(), self.num_genes(), "Individual does not match the network size");
+        Model{network:self, weights}
+    }
+    ///build a GA fitness function from an evaluation of the decoded network<BR>
+    /// e.g. network.fitness(|model| -mse(model, &inputs, &targets))
+    pub fn fitness<E>(&self, evaluate:E)->impl Fn(Population)->f64 + Send + Sync
+    where E:Fn(&Model)->f64 + Send + Sync{
+        let network = self.clone();
+        move |weights| match weights {
+            Population::F64(vec) => evaluate(&network.model(&vec[0])),
+            _ => panic!("Expected Population::F64"),
+        }
+    }
+}
+
+///network decoded from an individual
+pub struct Model<'a>{
+    network:&'a DenseNetwork,
+    weights:&'a [f64],
+}
+impl Model<'_>{
+    ///forward pass
+    pub fn predict(&self, inputs:&[f64])->Vec<f64>{
+        let mut values = inputs.to_vec();
+        let mut offset = 0;
+        for &(size, activation) in &self.network.layers {
+            let width = values.len() + 1;
+            values = (0..size).map(|neuron| {
+                let weights = &self.weights[offset + neuron * width..offset + (neuron + 1) * width];
+                let sum = weights[0] + weights[1..].iter().zip(&values).map(|(w, x)| w * x).sum::<f64>();
+                activation.apply(sum)
+            }).collect();
+            offset += size * width;
+        }
+        values
+    }
+}
+
+///mean squared error of the model over a set of samples
+pub fn mse(model:&Model, inputs:&[Vec<f64>], targets:&[Vec<f64>])->f64{
+    let total:f64 = inputs.iter().zip(targets)
+        .map(|(input, target)| model.predict(input).iter().zip(target).map(|(y, t)| (y - t).powi(2)).sum::<f64>())
+        .sum();
+    total / inputs.len().max(1) as f64
+}
+///fraction of samples whose label is the index of the largest output
+pub fn accuracy(model:&Model, inputs:&[Vec<f64>], labels:&[usize])->f64{
+    let correct = inputs.iter().zip(labels).filter(|(input, &label)| {
+        let outputs = model.predict(input);
+        (0..outputs.len()).max_by(|&a, &b| outputs[a].partial_cmp(&outputs[b]).unwrap()) == Some(label)
+    }).count();
+    correct as f64 / inputs.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    //hidden neurons 1 + 2*x0 and x0 - x1, output 0.5 + h0 + 2*h1
+    const WEIGHTS:[f64; 9] = [1.0, 2.0, 0.0, 0.0, 1.0, -1.0, 0.5, 1.0, 2.0];
+
+    fn network(hidden:Activation)->DenseNetwork{
+        DenseNetwork::new(2, vec![(2, hidden), (1, Activation::Identity)])
+    }
+
+    #[test]
+    fn genome_length_counts_weights_and_biases(){
+        assert_eq!(network(Activation::Identity).num_genes(), WEIGHTS.len());
+        let network = DenseNetwork::new(4, vec![(3, Activation::ReLU), (5, Activation::Tanh), (2, Activation::Sigmoid)]);
+        assert_eq!(network.num_genes(), (4 + 1) * 3 + (3 + 1) * 5 + (5 + 1) * 2);
+        assert_eq!(network.configure(Config::default()).num_genes, 47);
+        assert_eq!(DenseNetwork::new(3, Vec::new()).num_genes(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Individual does not match the network size")]
+    fn model_rejects_wrong_genome_length(){
+        network(Activation::Identity).model(&WEIGHTS[1..]);
+    }
+
+    #[test]
+    fn forward_pass_with_known_weights(){
+        let linear = network(Activation::Identity);
+        assert_eq!(linear.model(&WEIGHTS).predict(&[1.0, 3.0]), vec![-0.5]);
+        assert_eq!(linear.model(&WEIGHTS).predict(&[0.0, 0.0]), vec![1.5]);
+        //the negative hidden neuron is cut by ReLU
+        let relu = network(Activation::ReLU);
+        assert_eq!(relu.model(&WEIGHTS).predict(&[1.0, 3.0]), vec![3.5]);
+    }
+
+    #[test]
+    fn mse_accuracy_and_fitness(){
+        let network = network(Activation::Identity);
+        let model = network.model(&WEIGHTS);
+        let inputs = vec![vec![1.0, 3.0], vec![0.0, 0.0]];
+        assert_eq!(mse(&model, &inputs, &[vec![0.5], vec![1.5]]), 0.5);
+        let classifier = DenseNetwork::new(1, vec![(2, Activation::Identity)]);
+        //outputs x and -x
+        let weights = [0.0, 1.0, 0.0, -1.0];
+        assert_eq!(accuracy(&classifier.model(&weights), &[vec![1.0], vec![-1.0], vec![2.0]], &[0, 1, 1]), 2.0 / 3.0);
+        let fitness = network.fitness(|model| model.predict(&[1.0, 3.0])[0]);
+        assert_eq!(fitness(Population::F64(vec![WEIGHTS.to_vec()])), -0.5);
+    }
+}
@@ -0,0 +1,198 @@
+use rand::seq::index::sample;
+use crate::population::Population;
+
+///distance between two individuals
+#[derive(Clone, Debug)]
+pub enum Distance{
+    ///euclidean distance between genes (usize genes are converted to f64)
+    Euclidean,
+    ///number of positions holding different genes (for trees, nodes compared in prefix order plus difference in size)
+    Hamming,
+}
+impl Distance{
+    ///distance between individual i of population a and individual j of population b
+    pub fn between(&self, a:&Population, i:usize, b:&Population, j:usize)->f64{
+        match (self, a, b) {
+            (Distance::Euclidean, Population::F64(a), Population::F64(b)) =>
+                a[i].iter().zip(&b[j]).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt(),
+            (Distance::Euclidean, Population::Usize(a), Population::Usize(b)) =>
+                a[i].iter().zip(&b[j]).map(|(&x, &y)| (x as f64 - y as f64).powi(2)).sum::<f64>().sqrt(),
+            (Distance::Hamming, Population::F64(a), Population::F64(b)) =>
+                a[i].iter().zip(&b[j]).filter(|(x, y)| x != y).count() as f64,
+            (Distance::Hamming, Population::Usize(a), Population::Usize(b)) =>
+                a[i].iter().zip(&b[j]).filter(|(x, y)| x != y).count() as f64,
+            (Distance::Hamming, Population::Tree(a), Population::Tree(b)) =>
+                (a[i].nodes.iter().zip(&b[j].nodes).filter(|(x, y)| x != y).count() + a[i].size().abs_diff(b[j].size())) as f64,
+            _ => panic!("Populations must be of the same type"),
+        }
+    }
+}
+
+///niching method used by GA to keep several optima in the population (see Config::niching)
+#[derive(Clone, Debug)]
+pub enum Niching{
+    ///fitness is divided by the niche count sum(1-(d/radius)^alpha) over individuals closer than radius<BR>
+    /// requires non negative fitness and radius > 0
+    FitnessSharing{radius:f64, alpha:f64, distance:Distance},
+    ///only the best capacity individuals (at least 1) of each niche of given radius keep their fitness, the others are cleared
+    Clearing{radius:f64, capacity:usize, distance:Distance},
+    ///random pairs of parents mate and each child replaces its closest parent if not worse
+    DeterministicCrowding{distance:Distance},
+    ///each child replaces the closest among window random individuals if better
+    RestrictedTournament{window:usize, distance:Distance},
+}
+impl Niching{
+    ///fitness used for ranking: shared or cleared fitness, raw fitness for replacement based methods
+    pub fn adjust(&self, population:&Population, scores:Vec<f64>)->Vec<f64>{
+        match self {
+            Niching::FitnessSharing{radius, alpha, distance} => shared_fitness(population, &scores, *radius, *alpha, distance),
+            Niching::Clearing{radius, capacity, distance} => clearing(population, &scores, *radius, *capacity, distance),
+            _ => scores,
+        }
+    }
+}
+
+///fitness sharing: each score is divided by the niche count of its individual
+pub fn shared_fitness(population:&Population, scores:&[f64], radius:f64, alpha:f64, distance:&Distance)->Vec<f64>{
+    assert!(radius > 0.0, "Fitness sharing radius must be positive");
+    (0..scores.len()).map(|i| {
+        let niche_count:f64 = (0..scores.len())
+            .map(|j| distance.between(population, i, population, j))
+            .filter(|&d| d < radius)
+            .map(|d| 1.0 - (d / radius).powf(alpha))
+            .sum();
+        scores[i] / niche_count
+    }).collect()
+}
+
+///clearing: best individuals become winners of their niche, all but the first capacity individuals of a niche get f64::NEG_INFINITY
+pub fn clearing(population:&Population, scores:&[f64], radius:f64, capacity:usize, distance:&Distance)->Vec<f64>{
+    assert!(capacity > 0, "Clearing capacity must be at least 1");
+    let mut order:Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap());
+    let mut cleared = scores.to_vec();
+    for (position, &winner) in order.iter().enumerate() {
+        if cleared[winner] == f64::NEG_INFINITY {
+            continue;
+        }
+        let mut winners = 1;
+        for &other in &order[position + 1..] {
+            if cleared[other] != f64::NEG_INFINITY && distance.between(population, winner, population, other) < radius {
+                if winners < capacity {
+                    winners += 1;
+                } else {
+                    cleared[other] = f64::NEG_INFINITY;
+                }
+            }
+        }
+    }
+    cleared
+}
+
+///deterministic crowding: children 2k and 2k+1 come from parents 2k and 2k+1<BR>
+/// each child is paired with its closest parent and replaces it if not worse<BR>
+/// returns the new population and its scores
+pub fn deterministic_crowding(parents:&Population, parent_scores:&[f64], children:&Population, child_scores:&[f64], distance:&Distance)->(Population,Vec<f64>){
+    let mut survivors = parents.select(&(0..parents.len()).collect::<Vec<usize>>());
+    let mut scores = parent_scores.to_vec();
+    for pair in (0..children.len().min(parents.len())).step_by(2) {
+        if pair + 1 >= children.len().min(parents.len()) {
+            if child_scores[pair] >= scores[pair] {
+                survivors.replace(pair, children, pair);
+                scores[pair] = child_scores[pair];
+            }
+            continue;
+        }
+        let (p1, p2, c1, c2) = (pair, pair + 1, pair, pair + 1);
+        let straight = distance.between(parents, p1, children, c1) + distance.between(parents, p2, children, c2);
+        let crossed = distance.between(parents, p1, children, c2) + distance.between(parents, p2, children, c1);
+        let matches = if straight <= crossed {[(p1, c1), (p2, c2)]} else {[(p1, c2), (p2, c1)]};
+        for (parent, child) in matches {
+            if child_scores[child] >= scores[parent] {
+                survivors.replace(parent, children, child);
+                scores[parent] = child_scores[child];
+            }
+        }
+    }
+    (survivors, scores)
+}
+
+///restricted tournament selection: each child replaces the closest of window random individuals if it is better
+pub fn restricted_tournament(population:&mut Population, scores:&mut [f64], children:&Population, child_scores:&[f64], window:usize, distance:&Distance){
+    if population.is_empty() {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    for (child, &child_score) in child_scores.iter().enumerate().take(children.len()) {
+        let closest = sample(&mut rng, population.len(), window.clamp(1, population.len())).into_iter()
+            .min_by(|&a, &b| distance.between(population, a, children, child).partial_cmp(&distance.between(population, b, children, child)).unwrap())
+            .unwrap();
+        if child_score > scores[closest] {
+            population.replace(closest, children, child);
+            scores[closest] = child_score;
+        }
+    }
+}
+
+///one representative per niche: individuals are visited from best to worst and kept if farther than radius from every kept one
+pub fn representatives(population:&Population, scores:&[f64], radius:f64, distance:&Distance)->Population{
+    let mut order:Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].partial_cmp(&scores[a]).unwrap());
+    let mut kept:Vec<usize> = Vec::new();
+    for candidate in order {
+        if kept.iter().all(|&k| distance.between(population, k, population, candidate) >= radius) {
+            kept.push(candidate);
+        }
+    }
+    population.select(&kept)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::population::GetPopulation;
+
+    #[test]
+    fn shared_fitness_divides_by_niche_count(){
+        let population = Population::F64(vec![vec![0.0], vec![0.0], vec![10.0]]);
+        let shared = shared_fitness(&population, &[4.0, 4.0, 4.0], 1.0, 1.0, &Distance::Euclidean);
+        assert_eq!(shared, vec![2.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn shared_fitness_rejects_zero_radius(){
+        shared_fitness(&Population::F64(vec![vec![0.0]]), &[1.0], 0.0, 1.0, &Distance::Euclidean);
+    }
+
+    #[test]
+    fn clearing_keeps_capacity_winners_per_niche(){
+        let population = Population::F64(vec![vec![0.0], vec![0.1], vec![0.2], vec![5.0]]);
+        let cleared = clearing(&population, &[3.0, 2.0, 1.0, 0.5], 1.0, 2, &Distance::Euclidean);
+        assert_eq!(cleared, vec![3.0, 2.0, f64::NEG_INFINITY, 0.5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn clearing_rejects_zero_capacity(){
+        clearing(&Population::F64(vec![vec![0.0]]), &[1.0], 1.0, 0, &Distance::Euclidean);
+    }
+
+    #[test]
+    fn restricted_tournament_replaces_closest_worse_individual(){
+        let mut population = Population::F64(vec![vec![0.0], vec![10.0]]);
+        let mut scores = vec![1.0, 1.0];
+        let children = Population::F64(vec![vec![9.0], vec![0.5]]);
+        restricted_tournament(&mut population, &mut scores, &children, &[2.0, 0.5], 2, &Distance::Euclidean);
+        assert_eq!(scores, vec![1.0, 2.0]);
+        assert_eq!(GetPopulation::<f64>::get_individual(&population, 1).unwrap(), vec![9.0]);
+    }
+
+    #[test]
+    fn restricted_tournament_on_empty_population(){
+        let mut population = Population::F64(Vec::new());
+        let children = Population::F64(vec![vec![1.0]]);
+        restricted_tournament(&mut population, &mut [], &children, &[1.0], 3, &Distance::Euclidean);
+        assert!(population.is_empty());
+    }
+}
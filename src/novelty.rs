@@ -0,0 +1,136 @@
+use rand::Rng;
+use crate::population::Population;
+
+///rule deciding which individuals enter the archive of novel behaviors
+#[derive(Clone, Debug)]
+pub enum ArchiveRule{
+    ///individuals whose novelty exceeds the threshold
+    Threshold(f64),
+    ///each individual with the given probability
+    Random(f64),
+}
+
+///novelty search used by GA to rank individuals (see GA::with_novelty)<BR>
+/// the novelty of an individual is the average distance of its behavior to the k nearest behaviors of the population and the archive
+pub struct Novelty{
+    ///behavior of an individual (e.g. final position of a robot)
+    behavior:Box<dyn Fn(Population)->Vec<f64> + Send>,
+    ///number of nearest neighbours
+    pub k:usize,
+    pub rule:ArchiveRule,
+    ///maximum size of the archive, the oldest behaviors are dropped first (None for no limit)
+    pub max_archive:Option<usize>,
+    ///weight of the objective fitness in the ranking score, 0 for pure novelty search<BR>
+    /// novelty and fitness are normalized to [0,1] over the population before blending
+    pub fitness_weight:f64,
+    ///behaviors archived so far
+    pub archive:Vec<Vec<f64>>,
+}
+impl Novelty{
+    ///pure novelty search with 15 nearest neighbours, archiving individuals with probability 0.05
+    pub fn new<B>(behavior:B) -> Self
+    where B:Fn(Population)->Vec<f64> + Send + 'static{
+        Novelty{behavior:Box::new(behavior), k:15, rule:ArchiveRule::Random(0.05), max_archive:None, fitness_weight:0.0, archive:Vec::new()}
+    }
+    ///behavior of every individual of a population
+    pub fn behaviors(&self, population:&Population)->Vec<Vec<f64>>{
+        (0..population.len()).map(|i| (self.behavior)(population.select(&[i]))).collect()
+    }
+    ///novelty of each behavior with respect to the other behaviors and the archive
+    pub fn novelty(&self, behaviors:&[Vec<f64>])->Vec<f64>{
+        let distance = |a:&Vec<f64>, b:&Vec<f64>| a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt();
+        behaviors.iter().enumerate().map(|(i, behavior)| {
+            let mut distances:Vec<f64> = behaviors.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, other)| other)
+                .chain(&self.archive)
+                .map(|other| distance(behavior, other))
+                .collect();
+            distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let neighbours = self.k.min(distances.len());
+            if neighbours == 0 {0.0} else {distances[..neighbours].iter().sum::<f64>() / neighbours as f64}
+        }).collect()
+    }
+    ///score used to rank a population: novelty blended with fitness (evals)<BR>
+    /// novel behaviors are added to the archive according to the archive rule
+    pub fn score(&mut self, population:&Population, evals:Vec<f64>)->Vec<f64>{
+        let behaviors = self.behaviors(population);
+        let novelty = self.novelty(&behaviors);
+        let mut rng = rand::thread_rng();
+        for (behavior, &value) in behaviors.into_iter().zip(&novelty) {
+            let archived = match self.rule {
+                ArchiveRule::Threshold(threshold) => value > threshold,
+                ArchiveRule::Random(probability) => rng.gen::<f64>() < probability,
+            };
+            if archived {
+                self.archive.push(behavior);
+            }
+        }
+        if let Some(max) = self.max_archive {
+            let excess = self.archive.len().saturating_sub(max);
+            self.archive.drain(..excess);
+        }
+        if self.fitness_weight == 0.0 {
+            return novelty;
+        }
+        let novelty = normalize(novelty);
+        let evals = normalize(evals);
+        novelty.iter().zip(evals).map(|(n, f)| (1.0 - self.fitness_weight) * n + self.fitness_weight * f).collect()
+    }
+}
+///rescale values to [0,1] (all 1 if they are equal)
+fn normalize(values:Vec<f64>)->Vec<f64>{
+    let min = values.iter().cloned().reduce(f64::min).unwrap_or(0.0);
+    let max = values.iter().cloned().reduce(f64::max).unwrap_or(0.0);
+    values.into_iter().map(|value| if max > min {(value - min) / (max - min)} else {1.0}).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn novelty(k:usize, rule:ArchiveRule)->Novelty{
+        let behavior = |individual:Population| match individual {
+            Population::F64(vec) => vec[0].clone(),
+            _ => unreachable!(),
+        };
+        Novelty{k, rule, archive:vec![vec![10.0]], ..Novelty::new(behavior)}
+    }
+
+    fn population()->Population{
+        Population::F64(vec![vec![0.0], vec![1.0], vec![3.0]])
+    }
+
+    #[test]
+    fn novelty_is_average_distance_to_k_nearest(){
+        let behaviors = vec![vec![0.0], vec![1.0], vec![3.0]];
+        assert_eq!(novelty(2, ArchiveRule::Random(0.0)).novelty(&behaviors), vec![2.0, 1.5, 2.5]);
+        //fewer neighbours than k: average over all of them
+        assert_eq!(novelty(10, ArchiveRule::Random(0.0)).novelty(&behaviors)[0], 14.0 / 3.0);
+        let distances = novelty(1, ArchiveRule::Random(0.0)).novelty(&[vec![0.0, 0.0], vec![3.0, 4.0]]);
+        assert_eq!(distances, vec![5.0, 5.0]);
+    }
+
+    #[test]
+    fn threshold_rule_archives_only_novel_behaviors(){
+        let mut novelty = novelty(2, ArchiveRule::Threshold(2.0));
+        assert_eq!(novelty.score(&population(), vec![0.0; 3]), vec![2.0, 1.5, 2.5]);
+        assert_eq!(novelty.archive, vec![vec![10.0], vec![3.0]]);
+    }
+
+    #[test]
+    fn archive_drops_oldest_behaviors(){
+        let mut novelty = Novelty{max_archive:Some(2), ..novelty(2, ArchiveRule::Random(1.0))};
+        novelty.score(&population(), vec![0.0; 3]);
+        assert_eq!(novelty.archive, vec![vec![1.0], vec![3.0]]);
+    }
+
+    #[test]
+    fn score_blends_normalized_novelty_and_fitness(){
+        let evals = vec![0.0, 10.0, 5.0];
+        let mut blended = Novelty{fitness_weight:0.5, ..novelty(2, ArchiveRule::Random(0.0))};
+        //normalized novelty [0.5, 0, 1] and fitness [0, 1, 0.5]
+        assert_eq!(blended.score(&population(), evals.clone()), vec![0.25, 0.5, 0.75]);
+        let mut fitness_only = Novelty{fitness_weight:1.0, ..novelty(2, ArchiveRule::Random(0.0))};
+        assert_eq!(fitness_only.score(&population(), evals), vec![0.0, 1.0, 0.5]);
+        assert_eq!(normalize(vec![2.0, 2.0]), vec![1.0, 1.0]);
+    }
+}
@@ -10,8 +10,8 @@ pub fn draw_fitness(fitness_curve:Vec<f64>, filename:&str) {
         .into_drawing_area();
     root_area.fill(&WHITE).unwrap();
 
-    let chart_max = fitness_curve.clone().into_iter().reduce(f64::max).map(|max| if signum(max)>0. {max*1.1}else{max*0.9}).unwrap();
-    let chart_min = fitness_curve.clone().into_iter().reduce(f64::min).map(|min| if signum(min)>0. {min*0.9}else{min*1.1}).unwrap();
+    let chart_max = fitness_curve.iter().cloned().reduce(f64::max).map(|max| if signum(max)>0. {max*1.1}else{max*0.9}).unwrap();
+    let chart_min = fitness_curve.iter().cloned().reduce(f64::min).map(|min| if signum(min)>0. {min*0.9}else{min*1.1}).unwrap();
 
 
     // Define the chart
@@ -40,12 +40,12 @@ pub fn draw_fitness(fitness_curve:Vec<f64>, filename:&str) {
         ))
         .unwrap()
         .label("Fitness")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
 
     // Configure the legend
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()
         .unwrap();
 
@@ -53,3 +53,98 @@ pub fn draw_fitness(fitness_curve:Vec<f64>, filename:&str) {
     root_area.present().unwrap();
     println!("File saved as = {:?}",env::current_dir().unwrap().into_os_string().into_string().unwrap()+filename);
 }
+
+
+///function to draw a grid of values (e.g. fitness or diversity of a cellular GA) as a heatmap<BR>
+/// values are given row by row, NaN cells are drawn in grey
+pub fn draw_heatmap(grid:Vec<Vec<f64>>, filename:&str) {
+
+    // Create a drawing area
+    let root_area = BitMapBackend::new(filename, (800, 800))
+        .into_drawing_area();
+    root_area.fill(&WHITE).unwrap();
+
+    let values = grid.iter().flatten().cloned().filter(|v| !v.is_nan());
+    let min = values.clone().reduce(f64::min).unwrap_or(0.);
+    let max = values.reduce(f64::max).unwrap_or(1.);
+    let rows = grid.len();
+    let cols = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    // Define the chart
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Heatmap", ("sans-serif", 50).into_font())
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0..cols, 0..rows)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .draw()
+        .unwrap();
+
+    // Plot one rectangle per cell, from blue (min) to red (max)
+    chart
+        .draw_series(grid.iter().enumerate().flat_map(|(row, values)| values.iter().enumerate().map(move |(col, &value)| {
+            let color = if value.is_nan() {
+                RGBColor(200, 200, 200)
+            } else {
+                let t = if max > min {(value - min) / (max - min)} else {1.};
+                RGBColor((255. * t) as u8, 0, (255. * (1. - t)) as u8)
+            };
+            Rectangle::new([(col, row), (col + 1, row + 1)], color.filled())
+        })))
+        .unwrap();
+
+    // Save the plot as an image
+    root_area.present().unwrap();
+    println!("File saved as = {:?}",env::current_dir().unwrap().into_os_string().into_string().unwrap()+filename);
+}
+
+///function to draw a Gantt chart: one row of bars (start, end, job) per resource (e.g. machine)<BR>
+/// bars of the same job share the same color
+pub fn draw_gantt(rows:Vec<Vec<(f64,f64,usize)>>, filename:&str) {
+
+    // Create a drawing area
+    let root_area = BitMapBackend::new(filename, (1200, 100 + 40 * rows.len() as u32))
+        .into_drawing_area();
+    root_area.fill(&WHITE).unwrap();
+
+    let end = rows.iter().flatten().map(|&(_, end, _)| end).fold(1., f64::max);
+
+    // Define the chart
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Gantt Chart", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0.0..end * 1.02, 0.0..rows.len() as f64)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc("Time")
+        .y_desc("Machine")
+        .y_labels(rows.len() * 2 + 1)
+        .y_label_formatter(&|y| if y.fract() == 0.5 {format!("{}", y.floor())} else {String::new()})
+        .draw()
+        .unwrap();
+
+    // Plot one rectangle per bar, with a black border to separate consecutive bars of the same job
+    for (row, bars) in rows.iter().enumerate() {
+        let y = row as f64;
+        chart
+            .draw_series(bars.iter().map(|&(start, end, job)| Rectangle::new([(start, y + 0.1), (end, y + 0.9)], Palette99::pick(job).filled())))
+            .unwrap();
+        chart
+            .draw_series(bars.iter().map(|&(start, end, _)| Rectangle::new([(start, y + 0.1), (end, y + 0.9)], BLACK.stroke_width(1))))
+            .unwrap();
+    }
+
+    // Save the plot as an image
+    root_area.present().unwrap();
+    println!("File saved as = {:?}",env::current_dir().unwrap().into_os_string().into_string().unwrap()+filename);
+}
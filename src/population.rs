@@ -2,6 +2,16 @@ use std::ops::Range;
 use rand::seq::SliceRandom;
 use rand::{Rng, thread_rng};
 use std::time::Instant;
+use std::cell::RefCell;
+use std::mem;
+use std::collections::HashMap;
+use std::thread;
+use crate::memetic::{Learning, LocalSearchStrategy};
+use crate::niching::{self, Distance, Niching};
+use crate::novelty::Novelty;
+use crate::constraints::{Constraints, RepairStrategy};
+use crate::cache::{CacheStats, FitnessCache, Genome};
+use crate::gp::Tree;
 
 pub trait Initialization<T>{
     fn initialize(&self, config: Config) -> T;
@@ -14,6 +24,19 @@ pub struct Config{
     pub num_genes:usize,
     pub range:Range<f64>,
     pub mutation_rate:f64,
+    ///probability of applying local search to an individual (only used with GA::with_local_search)
+    pub local_search_rate:f64,
+    ///maximum number of fitness evaluations spent by one local search
+    pub local_search_budget:usize,
+    pub learning:Learning,
+    ///optional niching method to keep several optima (None by default)
+    pub niching:Option<Niching>,
+    ///genes pinned to a value that initialization, crossover and mutation never change (None by default, not supported for tree populations)
+    pub locked:Option<LockedGenes>,
+    ///print scores at each step and the elapsed time of evolve (true by default)
+    pub verbose:bool,
+    ///evaluate the individuals of a generation on several threads (false by default, not used with Baldwinian local search)
+    pub parallel:bool,
 }
 ///default configuration
 impl Default for Config{
@@ -21,8 +44,34 @@ impl Default for Config{
         Config{
             num_individuals:10,
             num_genes:10,
-            range: -10.0..10.0,//only used in Initialization<Vec<Vec<f64>>>
+            range: -10.0..10.0,//only used for Vec<f64> individuals
             mutation_rate:0.1,
+            local_search_rate:0.1,
+            local_search_budget:50,
+            learning:Learning::Lamarckian,
+            niching:None,
+            locked:None,
+            verbose:true,
+            parallel:false,
+        }
+    }
+}
+///value of each locked gene, None for free genes (see Config::locked)<BR>
+/// genes of tree individuals cannot be locked
+#[derive(Clone, Debug)]
+pub enum LockedGenes{
+    ///locked genes are overwritten with their value
+    Usize(Vec<Option<usize>>),
+    ///locked genes are set by swapping the value into place when it appears elsewhere in the individual, so permutations stay valid
+    Permutation(Vec<Option<usize>>),
+    F64(Vec<Option<f64>>),
+}
+impl LockedGenes{
+    ///true if the gene at index is locked
+    pub fn is_locked(&self, index:usize)->bool{
+        match self {
+            LockedGenes::Usize(values) | LockedGenes::Permutation(values) => matches!(values.get(index), Some(Some(_))),
+            LockedGenes::F64(values) => matches!(values.get(index), Some(Some(_))),
         }
     }
 }
@@ -54,7 +103,7 @@ impl Initialization<Vec<Vec<usize>>> for SudokuInitialization{
         let mut rng = thread_rng();
         let mut individuals = Vec::with_capacity(config.num_individuals);
         for _ in 0..config.num_individuals{
-            let mut individual:Vec<usize> = (0..81).map(|_|rng.gen_range(0..10)).collect();
+            let individual:Vec<usize> = (0..81).map(|_|rng.gen_range(0..10)).collect();
             //individual.shuffle(&mut rng);
             individuals.push(individual);
         }
@@ -62,227 +111,724 @@ impl Initialization<Vec<Vec<usize>>> for SudokuInitialization{
     }
 }
 pub enum InitializationStrategy{
-    Usize(Box<dyn Initialization<Vec<Vec<usize>>>>),
-    F64(Box<dyn Initialization<Vec<Vec<f64>>>>),
+    Usize(Box<dyn Initialization<Vec<Vec<usize>>> + Send>),
+    F64(Box<dyn Initialization<Vec<Vec<f64>>> + Send>),
+    Tree(Box<dyn Initialization<Vec<Tree>> + Send>),
+}
+impl InitializationStrategy{
+    ///build a population of Config::num_individuals individuals
+    ///locked genes (Config::locked) are set to their value
+    pub fn initialize(&self, config:Config)->Population{
+        let mut population = match self {
+            InitializationStrategy::Usize(init) => Population::Usize(init.initialize(config.clone())),
+            InitializationStrategy::F64(init) => Population::F64(init.initialize(config.clone())),
+            InitializationStrategy::Tree(init) => Population::Tree(init.initialize(config.clone())),
+        };
+        if let Some(locked) = &config.locked {
+            population.lock(locked);
+        }
+        population
+    }
 }
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Population{
     Usize(Vec<Vec<usize>>),
     F64(Vec<Vec<f64>>),
+    ///programs evolved by genetic programming (see gp module)
+    Tree(Vec<Tree>),
+}
+impl Population{
+    ///number of individuals
+    pub fn len(&self)->usize{
+        match self {
+            Population::Usize(vec)=>vec.len(),
+            Population::F64(vec)=>vec.len(),
+            Population::Tree(vec)=>vec.len(),
+        }
+    }
+    ///true if there are no individuals
+    pub fn is_empty(&self)->bool{
+        self.len()==0
+    }
+    ///return a new population made of copies of the individuals at the given indices
+    pub fn select(&self, indices:&[usize])->Population{
+        match self {
+            Population::Usize(vec)=>Population::Usize(indices.iter().map(|&i| vec[i].clone()).collect()),
+            Population::F64(vec)=>Population::F64(indices.iter().map(|&i| vec[i].clone()).collect()),
+            Population::Tree(vec)=>Population::Tree(indices.iter().map(|&i| vec[i].clone()).collect()),
+        }
+    }
+    ///overwrite individual at index with a copy of individual other_index of another population
+    pub fn replace(&mut self, index:usize, other:&Population, other_index:usize){
+        match (self, other) {
+            (Population::Usize(vec), Population::Usize(other))=>vec[index].clone_from(&other[other_index]),
+            (Population::F64(vec), Population::F64(other))=>vec[index].clone_from(&other[other_index]),
+            (Population::Tree(vec), Population::Tree(other))=>vec[index].clone_from(&other[other_index]),
+            _ => panic!("Populations must be of the same type"),
+        }
+    }
+    ///true if individual index has the same genome as individual other_index of another population
+    pub fn same_genome(&self, index:usize, other:&Population, other_index:usize)->bool{
+        match (self, other) {
+            (Population::Usize(vec), Population::Usize(other))=>vec[index] == other[other_index],
+            (Population::F64(vec), Population::F64(other))=>vec[index] == other[other_index],
+            (Population::Tree(vec), Population::Tree(other))=>vec[index].nodes == other[other_index].nodes,
+            _ => false,
+        }
+    }
+    ///append a copy of individual other_index of another population
+    pub fn push(&mut self, other:&Population, other_index:usize){
+        match (self, other) {
+            (Population::Usize(vec), Population::Usize(other))=>vec.push(other[other_index].clone()),
+            (Population::F64(vec), Population::F64(other))=>vec.push(other[other_index].clone()),
+            (Population::Tree(vec), Population::Tree(other))=>vec.push(other[other_index].clone()),
+            _ => panic!("Populations must be of the same type"),
+        }
+    }
+    ///overwrite the population with copies of the individuals of another population at the given indices<BR>
+    /// genomes are copied in place, so no allocation happens once the population has the right size
+    pub fn copy_from(&mut self, other:&Population, indices:&[usize]){
+        match (&mut *self, other) {
+            (Population::Usize(vec), Population::Usize(other))=>copy_genomes(vec, other, indices),
+            (Population::F64(vec), Population::F64(other))=>copy_genomes(vec, other, indices),
+            (Population::Tree(vec), Population::Tree(other))=>copy_genomes(vec, other, indices),
+            _ => *self = other.select(indices),
+        }
+    }
+    ///set locked genes of every individual to their value
+    pub fn lock(&mut self, locked:&LockedGenes){
+        for index in 0..self.len(){
+            self.lock_at(index, locked);
+        }
+    }
+    ///set locked genes of individual at index to their value
+    pub fn lock_at(&mut self, index:usize, locked:&LockedGenes){
+        match (self, locked) {
+            (Population::Usize(vec), LockedGenes::Usize(values))=>{
+                for (gene, value) in vec[index].iter_mut().zip(values){
+                    if let Some(value) = value {
+                        *gene = *value;
+                    }
+                }
+            }
+            (Population::Usize(vec), LockedGenes::Permutation(values))=>{
+                let individual = &mut vec[index];
+                for (i, value) in values.iter().enumerate(){
+                    if let Some(value) = *value {
+                        if individual[i] != value {
+                            match (0..individual.len()).find(|&j| individual[j] == value && !locked.is_locked(j)) {
+                                Some(j) => individual.swap(i, j),
+                                None => individual[i] = value,
+                            }
+                        }
+                    }
+                }
+            }
+            (Population::F64(vec), LockedGenes::F64(values))=>{
+                for (gene, value) in vec[index].iter_mut().zip(values){
+                    if let Some(value) = value {
+                        *gene = *value;
+                    }
+                }
+            }
+            (Population::Tree(_), _) => panic!("Genes of tree individuals cannot be locked"),
+            _ => panic!("Locked genes and population must be of the same type"),
+        }
+    }
+    ///perform crossover between individuals 2k and 2k+1, children keep the same positions<BR>
+    /// with an odd number of individuals the last one is copied unchanged
+    pub fn mate_pairs(&self)->Population{
+        let mut children = self.select(&[]);
+        self.mate_into(&(0..self.len()).collect::<Vec<usize>>(), &mut children, &mut Vec::new());
+        children
+    }
+    ///write in children the offspring of parents 2k and 2k+1 of the given indices (see mate_pairs)<BR>
+    /// children are overwritten in place and counts is scratch space for the Vec<usize> crossover,
+    /// so no allocation happens once the buffers have the right size (except for trees)
+    pub fn mate_into(&self, parents:&[usize], children:&mut Population, counts:&mut Vec<usize>){
+        children.copy_from(self, parents);
+        let mut rng = rand::thread_rng();
+        match (self, children) {
+            (Population::F64(_), Population::F64(out))=>{
+                for pair in out.chunks_exact_mut(2){
+                    let (child1, child2) = pair.split_at_mut(1);
+                    let crossover_point = rand::random::<usize>() % child1[0].len();
+                    child1[0][crossover_point..].swap_with_slice(&mut child2[0][crossover_point..]);
+                }
+            }
+            (Population::Usize(vec), Population::Usize(out))=>{
+                for (pair, indices) in out.chunks_exact_mut(2).zip(parents.chunks_exact(2)){
+                    let size = pair[0].len();
+                    let start = rng.gen_range(0..size);
+                    let end = rng.gen_range(start..size);
+                    let (child1, child2) = pair.split_at_mut(1);
+                    order_crossover(&vec[indices[0]], &vec[indices[1]], start, end, &mut child1[0], counts);
+                    order_crossover(&vec[indices[1]], &vec[indices[0]], start, end, &mut child2[0], counts);
+                }
+            }
+            (Population::Tree(vec), Population::Tree(out))=>{
+                for (pair, indices) in out.chunks_exact_mut(2).zip(parents.chunks_exact(2)){
+                    let (child1, child2) = vec[indices[0]].crossover(&vec[indices[1]]);
+                    pair[0] = child1;
+                    pair[1] = child2;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    ///mutate individual at index in place (see Mutation), returns true if its genome changed<BR>
+    /// locked genes (config.locked) are never mutated
+    pub fn mutate_at(&mut self, index:usize, config:&Config)->bool{
+        let mut rng = rand::thread_rng();
+        let locked = |i:usize| config.locked.as_ref().is_some_and(|locked| locked.is_locked(i));
+        match self {
+            Population::F64(vec)=>{
+                let mut changed = false;
+                for (i, gene) in vec[index].iter_mut().enumerate(){
+                    if rng.gen::<f64>() < config.mutation_rate && !locked(i){
+                        *gene=rng.gen_range(config.range.clone());
+                        changed = true;
+                    }
+                }
+                changed
+            }
+            Population::Usize(vec)=>{
+                let individual = &mut vec[index];
+                if rng.gen::<f64>() >= config.mutation_rate{
+                    return false;
+                }
+                let free = (0..individual.len()).filter(|&i| !locked(i)).count();
+                if free == 0{
+                    return false;
+                }
+                let index1 = (0..individual.len()).filter(|&i| !locked(i)).nth(rng.gen_range(0..free)).unwrap();
+                let index2 = (0..individual.len()).filter(|&i| !locked(i)).nth(rng.gen_range(0..free)).unwrap();
+                individual.swap(index1, index2);
+                individual[index1] != individual[index2]
+            }
+            Population::Tree(vec)=>{
+                if rng.gen::<f64>() < config.mutation_rate{
+                    vec[index] = vec[index].mutate();
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+    ///return a copy of the tree at index (None for other kinds of population)
+    pub fn get_tree(&self, index:usize)->Option<Tree>{
+        match self {
+            Population::Tree(vec)=>vec.get(index).cloned(),
+            _=>None,
+        }
+    }
 }
 
+///fitness of an individual and the violation of each of its constraints (empty without GA::with_constraints)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation{
+    pub fitness:f64,
+    pub violations:Vec<f64>,
+}
 pub struct GA<F>
 where F:Fn(Population)->f64{
-    initialization: InitializationStrategy,
-    pub population: Population,
+    ///individuals, only changed through update, replace_individual and the operators so that their scores stay consistent
+    population: Population,
+    ///evaluation of each individual, None until evaluated or after an operator changed its genome
+    scores: Vec<Option<Evaluation>>,
     fitness:F,
     config: Config,
+    local_search: Option<LocalSearchStrategy>,
+    novelty: Option<Novelty>,
+    constraints: Option<Constraints>,
+    repair: Option<RepairStrategy>,
+    cache: Option<RefCell<FitnessCache<Evaluation>>>,
+    variation: Option<VariationStrategy>,
+    ///spare population and scores, swapped with the current ones so that generations are built without allocating
+    offspring: Population,
+    offspring_scores: Vec<Option<Evaluation>>,
+    ///reused buffers: parent or sort indices, cumulative probabilities of rank selection, crossover scratch space
+    indices: Vec<usize>,
+    cumulative_prob: Vec<f64>,
+    counts: Vec<usize>,
 }
 impl<F> GA<F>
-where F:Fn(Population)->f64{
+where F:Fn(Population)->f64 + Sync{
     ///initialize population based on an initialization strategy and a fitness function
     pub fn new(initialization:InitializationStrategy, fitness:F, config: Config) -> Self{
-        let population = match &initialization {
-            InitializationStrategy::Usize(init) =>{
-                Population::Usize(init.initialize(config.clone()))
-            }
-            InitializationStrategy::F64(init) =>{
-                Population::F64(init.initialize(config.clone()))
+        let population = initialization.initialize(config.clone());
+        let scores = vec![None; population.len()];
+        let offspring = population.clone();
+        let offspring_scores = scores.clone();
+        GA{population, scores, fitness, config, local_search:None, novelty:None, constraints:None, repair:None, cache:None, variation:None,
+            offspring, offspring_scores, indices:Vec::new(), cumulative_prob:Vec::new(), counts:Vec::new()}
+    }
+    ///turn the GA into a memetic algorithm refining offspring with a local search<BR>
+    /// see Config::local_search_rate, Config::local_search_budget and Config::learning
+    pub fn with_local_search(mut self, local_search:LocalSearchStrategy) -> Self{
+        self.local_search = Some(local_search);
+        self
+    }
+    ///rank parents by novelty of their behavior (optionally blended with fitness) instead of fitness alone
+    pub fn with_novelty(mut self, novelty:Novelty) -> Self{
+        self.novelty = Some(novelty);
+        self
+    }
+    ///novelty search settings and archive, None if not enabled
+    pub fn novelty(&self)->Option<&Novelty>{
+        self.novelty.as_ref()
+    }
+    ///rank individuals with a constraint handling method applied to the fitness (see constraints::constrained_ga)
+    pub fn with_constraints(mut self, constraints:Constraints) -> Self{
+        self.constraints = Some(constraints);
+        self
+    }
+    ///constraint handling settings, None if not enabled
+    pub fn constraints(&self)->Option<&Constraints>{
+        self.constraints.as_ref()
+    }
+    ///repair offspring after mutation
+    pub fn with_repair(mut self, repair:RepairStrategy) -> Self{
+        self.repair = Some(repair);
+        self
+    }
+    ///replace the built-in crossover and mutation with problem specific operators (e.g. route-aware operators of problems::vrp)
+    pub fn with_variation(mut self, variation:VariationStrategy) -> Self{
+        self.variation = Some(variation);
+        self
+    }
+    ///cache the fitness of up to capacity genomes so that identical individuals (e.g. unchanged elites) are not evaluated again
+    pub fn with_cache(mut self, capacity:usize) -> Self{
+        self.cache = Some(RefCell::new(FitnessCache::new(capacity)));
+        self
+    }
+    ///hit statistics of the fitness cache, None if not enabled
+    pub fn cache_stats(&self)->Option<CacheStats>{
+        self.cache.as_ref().map(|cache| cache.borrow().stats())
+    }
+    ///fitness and constraint violations of individual index of a population, read from the cache if enabled<BR>
+    /// the fitness function (or the combined one of the constraints) is called once per individual
+    fn fitness_of(&self, population:&Population, index:usize)->Evaluation{
+        let evaluate = || evaluate_individual(&self.fitness, self.constraints.as_ref(), population, index);
+        match &self.cache {
+            Some(cache) => {
+                let genome = Genome::of(population, index);
+                let cached = cache.borrow_mut().get(&genome);
+                cached.unwrap_or_else(|| {
+                    let evaluation = evaluate();
+                    cache.borrow_mut().insert(genome, evaluation.clone());
+                    evaluation
+                })
             }
-        };
-        GA{initialization, population, fitness, config}
+            None => evaluate(),
+        }
+    }
+    ///violations of individual index (empty without constraints)
+    fn violations_of(&self, index:usize)->Vec<f64>{
+        self.constraints.as_ref().map_or_else(Vec::new, |constraints| constraints.violations_of(&self.population, index))
     }
     ///print population
     pub fn inspect(&self){
         println!("{:?}",self.population);
     }
-    ///evaluate fitness of the whole population
+    ///configuration the GA was built with
+    pub fn config(&self)->&Config{
+        &self.config
+    }
+    ///current individuals (see update and replace_individual to change them)
+    pub fn population(&self)->&Population{
+        &self.population
+    }
+    ///fitness and violations of each individual, None if not evaluated since its genome last changed
+    pub fn scores(&self)->&[Option<Evaluation>]{
+        &self.scores
+    }
+    ///copy of individual at index with its fitness (evaluated if not known)
+    pub fn get_individual<T>(&self, index:usize)->Option<(Vec<T>,f64)>
+    where Population:GetPopulation<T>{
+        let individual = self.population.get_individual(index)?;
+        let score = match self.scores.get(index) {
+            Some(Some(evaluation)) => evaluation.fitness,
+            _ => self.fitness_of(&self.population, index).fitness,
+        };
+        Some((individual, score))
+    }
+    ///fitness of the whole population, individuals with a known score are not evaluated again<BR>
+    /// with Baldwinian local search, new individuals may be scored with the fitness reached after learning
     pub fn evaluate(&self)->Vec<f64>{
-        let mut eval = vec![];
-        match &self.population {
-            Population::F64(individuals)=>{
-                for individual in individuals{
-                    eval.push((self.fitness)(Population::F64(vec![individual.clone()])));
+        let pending:Vec<usize> = (0..self.population.len()).filter(|&i| !matches!(self.scores.get(i), Some(Some(_)))).collect();
+        let mut evaluations = pending.iter().cloned().zip(self.evaluate_all(&pending)).peekable();
+        (0..self.population.len()).map(|i| match evaluations.next_if(|(index, _)| *index == i) {
+            Some((_, evaluation)) => evaluation.fitness,
+            None => self.scores[i].as_ref().unwrap().fitness,
+        }).collect()
+    }
+    ///evaluations of the individuals at the given indices, computed on several threads if Config::parallel is set<BR>
+    /// the cache is read and filled on the calling thread and identical genomes are evaluated once
+    fn evaluate_all(&self, indices:&[usize])->Vec<Evaluation>{
+        let baldwinian = matches!((&self.local_search, &self.config.learning), (Some(_), Learning::Baldwinian));
+        if !self.config.parallel || baldwinian || indices.len() < 2 {
+            return indices.iter().map(|&i| self.evaluation(i)).collect();
+        }
+        let mut evaluations:Vec<Option<Evaluation>> = vec![None; indices.len()];
+        //individuals to evaluate, and for each individual missing from the cache its position among them
+        let mut missing:Vec<usize> = Vec::new();
+        let mut positions:Vec<Option<usize>> = vec![None; indices.len()];
+        match &self.cache {
+            Some(cache) => {
+                let mut cache = cache.borrow_mut();
+                let mut seen:HashMap<Genome,usize> = HashMap::new();
+                for (k, &i) in indices.iter().enumerate() {
+                    let genome = Genome::of(&self.population, i);
+                    if let Some(&position) = seen.get(&genome) {
+                        positions[k] = Some(position);
+                    } else if let Some(evaluation) = cache.get(&genome) {
+                        evaluations[k] = Some(evaluation);
+                    } else {
+                        seen.insert(genome, missing.len());
+                        positions[k] = Some(missing.len());
+                        missing.push(i);
+                    }
                 }
             }
-            Population::Usize(individuals)=>{
-                for individual in individuals{
-                    eval.push((self.fitness)(Population::Usize(vec![individual.clone()])));
-                }
+            None => {
+                positions = (0..indices.len()).map(Some).collect();
+                missing = indices.to_vec();
             }
         }
-        eval
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = missing.len().div_ceil(threads).max(1);
+        let (fitness, constraints, population) = (&self.fitness, self.constraints.as_ref(), &self.population);
+        let computed:Vec<Evaluation> = thread::scope(|scope| {
+            let handles:Vec<_> = missing.chunks(chunk)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(|&i| evaluate_individual(fitness, constraints, population, i)).collect::<Vec<Evaluation>>()))
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+        if let Some(cache) = &self.cache {
+            let mut cache = cache.borrow_mut();
+            for (&i, evaluation) in missing.iter().zip(&computed) {
+                cache.insert(Genome::of(&self.population, i), evaluation.clone());
+            }
+        }
+        evaluations.into_iter().zip(positions)
+            .map(|(evaluation, position)| evaluation.unwrap_or_else(|| computed[position.unwrap()].clone()))
+            .collect()
+    }
+    ///evaluation of individual index (its fitness after learning with Baldwinian local search)
+    fn evaluation(&self, index:usize)->Evaluation{
+        let baldwinian = matches!((&self.local_search, &self.config.learning), (Some(_), Learning::Baldwinian));
+        let learned = if baldwinian {self.learn(index)} else {None};
+        match learned {
+            Some((_, fitness)) => Evaluation{fitness, violations:self.violations_of(index)},
+            None => self.fitness_of(&self.population, index),
+        }
+    }
+    ///evaluate individuals without a known score and store their evaluation, returns their fitness
+    fn score(&mut self)->Vec<f64>{
+        let pending:Vec<usize> = (0..self.population.len()).filter(|&i| self.scores[i].is_none()).collect();
+        for (i, evaluation) in pending.iter().cloned().zip(self.evaluate_all(&pending)) {
+            self.scores[i] = Some(evaluation);
+        }
+        self.scores.iter().map(|evaluation| evaluation.as_ref().unwrap().fitness).collect()
+    }
+    ///stored evaluations (all individuals must have been scored)
+    fn evaluations(&self)->Vec<&Evaluation>{
+        self.scores.iter().map(|evaluation| evaluation.as_ref().expect("Individual not evaluated")).collect()
+    }
+    ///reorder population (and scores) by selecting the individuals at the given indices<BR>
+    /// individuals are copied into the spare buffers, which are then swapped with the current ones
+    fn reorder(&mut self, order:&[usize]){
+        self.offspring.copy_from(&self.population, order);
+        self.offspring_scores.clear();
+        self.offspring_scores.extend(order.iter().map(|&i| self.scores.get(i).cloned().flatten()));
+        mem::swap(&mut self.population, &mut self.offspring);
+        mem::swap(&mut self.scores, &mut self.offspring_scores);
     }
     ///sort in place population based on an input vector of fitness
     pub fn sort(&mut self, evals:Vec<f64>){
-        match &self.population {
-            Population::F64(vec)=>{
-                let mut evaluated_individuals:Vec<(Vec<f64>,f64)> = vec.iter().cloned().zip(evals.into_iter()).collect();
-                evaluated_individuals.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap());
-                self.population = Population::F64(evaluated_individuals.into_iter().map(|(individual,_)|individual).collect(),)
-            }
-            Population::Usize(vec)=> {
-                let mut evaluated_individuals: Vec<(Vec<usize>, f64)> = vec.iter().cloned().zip(evals.into_iter()).collect();
-                evaluated_individuals.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-                self.population = Population::Usize(evaluated_individuals.into_iter().map(|(individual, _)| individual).collect(), )
-            }
-        }
+        let mut order = mem::take(&mut self.indices);
+        order.clear();
+        order.extend(0..self.population.len());
+        order.sort_unstable_by(|&a,&b| evals[b].partial_cmp(&evals[a]).unwrap());
+        self.reorder(&order);
+        self.indices = order;
     }
-    ///update population with a provided one
+    ///update population with a provided one<BR>
+    /// individuals whose genome is unchanged at the same position keep their score
     pub fn update(&mut self, new_population:Population){
+        self.scores = (0..new_population.len()).map(|i| match self.scores.get(i) {
+            Some(Some(evaluation)) if i < self.population.len() && self.population.same_genome(i, &new_population, i) => Some(evaluation.clone()),
+            _ => None,
+        }).collect();
         self.population = new_population
     }
+    ///overwrite individual at index with a copy of individual other_index of another population, along with its score if known
+    pub fn replace_individual(&mut self, index:usize, other:&Population, other_index:usize, score:Option<Evaluation>){
+        self.population.replace(index, other, other_index);
+        self.scores[index] = score;
+    }
     ///rank select parents based on cumulative distribution function
     pub fn rank_selection_cdf(&self)->Population{
-        match &self.population {
-            Population::F64(vec)=>{
-                let total_rank: f64 = (1..=vec.len()).map(|i| i as f64).sum();
-                let mut selected_parents = Vec::with_capacity(vec.len());
-                let mut sum = 0.0;
-                let mut cumulative_prob = Vec::with_capacity(vec.len());
-                for (rank, individual) in vec.iter().enumerate() {
-                    sum += (vec.len() - rank) as f64 / total_rank;
-                    cumulative_prob.push((sum, individual.clone()));  // Clone the individual
-                }
-                for _ in 0..vec.len() {
-                    let rand_num: f64 = rand::random::<f64>();
-                    for &(prob, ref individual) in &cumulative_prob {
-                        if rand_num < prob {
-                            // Adding cloned individual to the selected parents
-                            selected_parents.push(individual.clone());
-                            break;
-                        }
-                    }
-                }
-                Population::F64(selected_parents)
+        let mut selected_parents = Vec::new();
+        rank_selection(self.population.len(), &mut Vec::new(), &mut selected_parents);
+        self.population.select(&selected_parents)
+    }
+    ///shuffle population and perform crossover (locked genes are restored in children)
+    pub fn mate_population(&self)->Population{
+        let mut order:Vec<usize> = (0..self.population.len()).collect();
+        order.shuffle(&mut rand::thread_rng());
+        self.locked(self.population.select(&order).mate_pairs())
+    }
+    ///restore locked genes (Config::locked) of a population
+    fn locked(&self, mut population:Population)->Population{
+        if let Some(locked) = &self.config.locked {
+            population.lock(locked);
+        }
+        population
+    }
+    ///mutate population
+    pub fn mutate(&mut self) ->Population{
+        self.population.mutate(&self.config)
+    }
+    ///run local search on individual at index with probability Config::local_search_rate<BR>
+    /// returns the improved individual and its fitness, None if no local search was run
+    fn learn(&self, index:usize)->Option<(Population,f64)>{
+        if rand::random::<f64>() >= self.config.local_search_rate {
+            return None;
+        }
+        match (&self.local_search, &self.population) {
+            (Some(LocalSearchStrategy::F64(search)), Population::F64(vec))=>{
+                let (individual, score) = search.improve(vec[index].clone(), &self.fitness, &self.config);
+                Some((Population::F64(vec![individual]), score))
             }
-            Population::Usize(vec)=>{
-                let total_rank: f64 = (1..=vec.len()).map(|i| i as f64).sum();
-                let mut selected_parents = Vec::with_capacity(vec.len());
-                let mut sum = 0.0;
-                let mut cumulative_prob = Vec::with_capacity(vec.len());
-                for (rank, individual) in vec.iter().enumerate() {
-                    sum += (vec.len() - rank) as f64 / total_rank;
-                    cumulative_prob.push((sum, individual.clone()));  // Clone the individual
-                }
-                for _ in 0..vec.len() {
-                    let rand_num: f64 = rand::random::<f64>();
-                    for &(prob, ref individual) in &cumulative_prob {
-                        if rand_num < prob {
-                            // Adding cloned individual to the selected parents
-                            selected_parents.push(individual.clone());
-                            break;
-                        }
-                    }
-                }
-                Population::Usize(selected_parents)
+            (Some(LocalSearchStrategy::Usize(search)), Population::Usize(vec))=>{
+                let (individual, score) = search.improve(vec[index].clone(), &self.fitness, &self.config);
+                Some((Population::Usize(vec![individual]), score))
+            }
+            (Some(LocalSearchStrategy::Tree(search)), Population::Tree(vec))=>{
+                let (individual, score) = search.improve(vec[index].clone(), &self.fitness, &self.config);
+                Some((Population::Tree(vec![individual]), score))
             }
+            (None, _) => None,
+            _ => panic!("Local search and population must be of the same type"),
         }
     }
-    ///shuffle population and perform crossover
-    pub fn mate_population(&self)->Population{
-        match &self.population {
-            Population::F64(vec)=>{
-                let mut selected_parents= vec.clone();
-                selected_parents.shuffle(& mut rand::thread_rng());
-                let mut new_population = Vec::new();
-                let selected_parents = Population::F64(selected_parents);
-                for i in (0..vec.len()).step_by(2){
-                    if i+1 < vec.len(){
-                        let (child1, child2) = selected_parents.crossover(i, i+1);
-                        new_population.push(child1);
-                        new_population.push(child2);
-                    }
-                }
-                Population::F64(new_population)
+    ///apply Lamarckian local search: improved individuals replace the original ones
+    /// (with constraints, their violations are evaluated again)
+    pub fn refine(&mut self){
+        for i in 0..self.population.len(){
+            if let Some((individual, fitness)) = self.learn(i){
+                self.replace_individual(i, &individual, 0, None);
+                self.scores[i] = Some(Evaluation{fitness, violations:self.violations_of(i)});
             }
-            Population::Usize(vec)=> {
-                let mut selected_parents = vec.clone();
-                selected_parents.shuffle(&mut rand::thread_rng());
-                let mut new_population = Vec::new();
-                let selected_parents = Population::Usize(selected_parents);
-                for i in (0..vec.len()).step_by(2) {
-                    if i + 1 < vec.len() {
-                        let (child1, child2) = selected_parents.crossover(i, i + 1);
-                        new_population.push(child1);
-                        new_population.push(child2);
-                    }
-                }
-                Population::Usize(new_population)
+        }
+    }
+    ///fitness used for ranking, adjusted by fitness sharing or clearing if configured
+    fn niched(&self, evals:Vec<f64>)->Vec<f64>{
+        match &self.config.niching {
+            Some(niching) => niching.adjust(&self.population, evals),
+            None => evals,
+        }
+    }
+    ///replacement based niching (deterministic crowding or restricted tournament)<BR>
+    /// random pairs of parents mate, children are mutated, repaired and refined as in breed,
+    /// then compete with similar individuals
+    fn niched_replacement(&mut self){
+        let scores = self.score();
+        let mut order:Vec<usize> = (0..self.population.len()).collect();
+        order.shuffle(&mut rand::thread_rng());
+        let parent_evaluations:Vec<Option<Evaluation>> = order.iter().map(|&i| self.scores[i].clone()).collect();
+        self.vary(&order);
+        let parents = self.offspring.select(&order);
+        let mut parent_scores:Vec<f64> = order.iter().map(|&i| scores[i]).collect();
+        self.improve_offspring();
+        let child_scores = self.score();
+        let survivors = match &self.config.niching {
+            Some(Niching::DeterministicCrowding{distance}) => {
+                let (survivors, _) = niching::deterministic_crowding(&parents, &parent_scores, &self.population, &child_scores, distance);
+                survivors
             }
+            Some(Niching::RestrictedTournament{window, distance}) => {
+                let mut survivors = parents.clone();
+                niching::restricted_tournament(&mut survivors, &mut parent_scores, &self.population, &child_scores, *window, distance);
+                survivors
+            }
+            _ => unreachable!(),
+        };
+        //survivors are parents or children, their evaluations (with constraint violations) are looked up instead of recomputed
+        let scores = (0..survivors.len()).map(|i| match survivors.same_genome(i, &parents, i) {
+            true => parent_evaluations[i].clone(),
+            false => (0..self.population.len()).find(|&c| survivors.same_genome(i, &self.population, c)).and_then(|c| self.scores[c].clone()),
+        }).collect();
+        self.population = survivors;
+        self.scores = scores;
+    }
+    ///return one representative per niche found in the population (best individual of each niche)
+    pub fn niches(&self, radius:f64, distance:&Distance)->Population{
+        niching::representatives(&self.population, &self.evaluate(), radius, distance)
+    }
+    ///fitness adjusted by the constraint handling if configured, using the stored violations
+    fn constrained(&self, evals:Vec<f64>)->Vec<f64>{
+        match &self.constraints {
+            Some(constraints) => constraints.score(&self.evaluations()),
+            None => evals,
         }
     }
-    ///mutate population
-    pub fn mutate(&mut self) ->Population{
-        match &self.population {
-            Population::F64(vec)=>{
-                let mut old_pop = vec.clone();
-                let mut rng = rand::thread_rng();
-                for i in 0..old_pop.len(){
-                    for j in 0..old_pop.first().unwrap().len(){
-                        if rng.gen::<f64>() < self.config.mutation_rate{
-                            old_pop[i][j]=rng.gen_range(Config::default().range.clone());
-                            //println!("Mutating {} gene for {} individual",j,i);
-                        }
+    ///score used to rank parents: novelty if enabled, niched fitness otherwise (both after constraint handling)
+    fn ranking(&mut self, evals:Vec<f64>)->Vec<f64>{
+        if let Some(mut constraints) = self.constraints.take() {
+            constraints.next_generation(&self.evaluations());
+            self.constraints = Some(constraints);
+        }
+        let evals = self.constrained(evals);
+        match self.novelty.as_mut() {
+            Some(novelty) => novelty.score(&self.population, evals),
+            None => self.niched(evals),
+        }
+    }
+    ///select parents, mate and mutate them in place
+    fn breed(&mut self){
+        //parents are drawn independently, so consecutive pairs are already random
+        let mut parents = mem::take(&mut self.indices);
+        rank_selection(self.population.len(), &mut self.cumulative_prob, &mut parents);
+        self.vary(&parents);
+        self.indices = parents;
+    }
+    ///mate the individuals 2k and 2k+1 of the given indices and mutate the children
+    /// (with the operators of GA::with_variation if set)<BR>
+    /// children are written into the spare buffers which are then swapped with the current ones,
+    /// so that the previous population is left in the spare buffers<BR>
+    /// children identical to their parent keep its score
+    fn vary(&mut self, parents:&[usize]){
+        match &self.variation {
+            Some(variation) => variation.mate_into(&self.population, parents, &mut self.offspring),
+            None => self.population.mate_into(parents, &mut self.offspring, &mut self.counts),
+        }
+        if let Some(locked) = &self.config.locked {
+            self.offspring.lock(locked);
+        }
+        self.offspring_scores.clear();
+        for (i, &parent) in parents.iter().enumerate() {
+            let same = self.offspring.same_genome(i, &self.population, parent);
+            self.offspring_scores.push(if same {self.scores[parent].clone()} else {None});
+        }
+        mem::swap(&mut self.population, &mut self.offspring);
+        mem::swap(&mut self.scores, &mut self.offspring_scores);
+        for i in 0..self.population.len() {
+            let changed = match &self.variation {
+                Some(variation) => {
+                    let changed = variation.mutate_at(&mut self.population, i, &self.config);
+                    if let (true, Some(locked)) = (changed, &self.config.locked) {
+                        self.population.lock_at(i, locked);
                     }
+                    changed
                 }
-                Population::F64(old_pop)
+                None => self.population.mutate_at(i, &self.config),
+            };
+            if changed {
+                self.scores[i] = None;
             }
-            Population::Usize(vec)=>{
-                let mut old_pop = vec.clone();
-                let mut rng = rand::thread_rng();
-                for i in 0..old_pop.len(){
-                    if rng.gen::<f64>() < self.config.mutation_rate{
-                        let index1 = rng.gen_range(0..old_pop[i].len());
-                        let index2 = rng.gen_range(0..old_pop[i].len());
-                        old_pop[i].swap(index1, index2);
-                        //println!("Swapping {} and {} genes for individual {}",index1,index2,i);
-                    }
+        }
+    }
+    ///repair offspring and refine them with local search (Lamarckian learning) if configured,
+    /// using the spare buffers as scratch space
+    fn improve_offspring(&mut self){
+        if let Some(repair) = &self.repair {
+            for i in 0..self.population.len() {
+                self.offspring.replace(i, &self.population, i);
+                repair.repair_at(&mut self.population, i);
+                if !self.population.same_genome(i, &self.offspring, i) {
+                    self.scores[i] = None;
                 }
-                Population::Usize(old_pop)
             }
         }
+        if let (Some(_), Learning::Lamarckian) = (&self.local_search, &self.config.learning) {
+            self.refine();
+        }
     }
     ///evolve population forward by one step<BR>
     /// in particular:<BR>
-    /// evaluate population<BR>
+    /// evaluate population (adjusted by fitness sharing or clearing if configured, replaced by novelty if enabled)<BR>
     /// select population to mate<BR>
     /// mate<BR>
-    /// mutate<BR>
-    /// evaluate again population<BR>
-    /// returns new score of updated population<BR>
+    /// mutate (with the operators of GA::with_variation if set)<BR>
+    /// repair offspring if configured<BR>
+    /// refine offspring with local search (Lamarckian learning)<BR>
+    /// evaluate offspring whose genome changed<BR>
+    /// with deterministic crowding or restricted tournament, selection and replacement are done by niched_replacement<BR>
+    /// returns new best score of updated population<BR>
     pub fn step(&mut self)->f64{
-        let mut evals = self.evaluate();
-        self.sort(evals.clone());
-        print!("Initial score = {} .... evolving ...",evals[0].clone());
-        let selected = self.rank_selection_cdf();
-        self.update(selected);
-        let mated_pop = self.mate_population();
-        self.update(mated_pop);
-        let mutated_pop = self.mutate();
-        self.update(mutated_pop);
-        evals = self.evaluate();
-        self.sort(evals.clone());
-        evals = self.evaluate();
-        print!("... final score = {:?}",evals[0]);
-        evals[0].clone()
+        let evals = self.score();
+        let ranking = self.ranking(evals.clone());
+        self.sort(ranking);
+        if self.config.verbose {
+            print!("Initial score = {} .... evolving ...",evals[0]);
+        }
+        match &self.config.niching {
+            Some(Niching::DeterministicCrowding{..}) | Some(Niching::RestrictedTournament{..}) => self.niched_replacement(),
+            _ => {
+                self.breed();
+                self.improve_offspring();
+            }
+        }
+        let evals = self.score();
+        let ranking = self.niched(self.constrained(evals.clone()));
+        self.sort(ranking);
+        //objective of the best individual, the top-ranked one under constraint handling so that it respects the constraints
+        let best = match &self.constraints {
+            Some(_) => self.scores[0].as_ref().unwrap().fitness,
+            None => evals.into_iter().reduce(f64::max).unwrap(),
+        };
+        if self.config.verbose {
+            print!("... final score = {:?}",best);
+        }
+        best
     }
     ///execute num_steps forward of evolution, return a vector of scores (curve of fitness)
     pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
         let start_time = Instant::now();
         let mut hist=Vec::new();
         for i in 0..num_steps{
-            println!();
-            println!("Step {}",i);
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
             hist.push(self.step());
         }
         let end_time = Instant::now();
         let duration = end_time.duration_since(start_time);
-        println!();
-        println!("Elapsed_time = {:?}",duration);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
         hist
     }
 }
+///objective and violations of individual index of a population, without cache
+fn evaluate_individual<F>(fitness:&F, constraints:Option<&Constraints>, population:&Population, index:usize)->Evaluation
+where F:Fn(Population)->f64{
+    match constraints {
+        Some(constraints) => constraints.evaluate(fitness, population, index),
+        None => Evaluation{fitness:fitness(population.select(&[index])), violations:Vec::new()},
+    }
+}
+///fill selected with len parents drawn by rank selection (index 0 being the best), cumulative_prob is scratch space
+fn rank_selection(len:usize, cumulative_prob:&mut Vec<f64>, selected:&mut Vec<usize>){
+    let total_rank: f64 = (1..=len).map(|i| i as f64).sum();
+    let mut sum = 0.0;
+    cumulative_prob.clear();
+    for rank in 0..len {
+        sum += (len - rank) as f64 / total_rank;
+        cumulative_prob.push(sum);
+    }
+    selected.clear();
+    for _ in 0..len {
+        let rand_num: f64 = rand::random::<f64>();
+        selected.push(cumulative_prob.partition_point(|&prob| prob <= rand_num).min(len - 1));
+    }
+}
 pub trait Crossover<T>{
     fn crossover(&self, index1:usize, index2:usize)->(T,T);
 }
@@ -290,18 +836,85 @@ impl Crossover<Vec<f64>> for Population{
      fn crossover(&self, parent1_index: usize, parent2_index: usize) -> (Vec<f64>,Vec<f64>) {
         match &self{
             Population::F64(vec)=>{
-                let gene_length = vec[parent1_index].len();
-                let crossover_point = rand::random::<usize>() % gene_length;
-                let mut child1_genes = vec[parent1_index][..crossover_point].to_vec();
-                child1_genes.extend_from_slice(&vec[parent2_index][crossover_point..]);
-                let mut child2_genes = vec[parent2_index][..crossover_point].to_vec();
-                child2_genes.extend_from_slice(&vec[parent1_index][crossover_point..]);
+                let mut child1_genes = vec[parent1_index].clone();
+                let mut child2_genes = vec[parent2_index].clone();
+                let crossover_point = rand::random::<usize>() % child1_genes.len();
+                child1_genes[crossover_point..].swap_with_slice(&mut child2_genes[crossover_point..]);
                 (child1_genes,child2_genes)
             }
         _ => unimplemented!()
         }
     }
 }
+///problem specific crossover and mutation used by GA instead of the built-in ones (see GA::with_variation)
+pub trait Variation<T>{
+    ///two children of two parents
+    fn crossover(&self, parent1:&T, parent2:&T)->(T,T);
+    ///mutate an individual in place with probability mutation_rate, returns true if it changed
+    fn mutate(&self, individual:&mut T, mutation_rate:f64)->bool;
+}
+pub enum VariationStrategy{
+    Usize(Box<dyn Variation<Vec<usize>> + Send>),
+    F64(Box<dyn Variation<Vec<f64>> + Send>),
+    Tree(Box<dyn Variation<Tree> + Send>),
+}
+impl VariationStrategy{
+    ///write in children the offspring of parents 2k and 2k+1 of the given indices (see Population::mate_into)
+    pub fn mate_into(&self, population:&Population, parents:&[usize], children:&mut Population){
+        children.copy_from(population, parents);
+        match (self, population, children) {
+            (VariationStrategy::Usize(variation), Population::Usize(vec), Population::Usize(out)) => mate_with(variation.as_ref(), vec, parents, out),
+            (VariationStrategy::F64(variation), Population::F64(vec), Population::F64(out)) => mate_with(variation.as_ref(), vec, parents, out),
+            (VariationStrategy::Tree(variation), Population::Tree(vec), Population::Tree(out)) => mate_with(variation.as_ref(), vec, parents, out),
+            _ => panic!("Variation and population must be of the same type"),
+        }
+    }
+    ///mutate individual at index in place, returns true if it changed
+    pub fn mutate_at(&self, population:&mut Population, index:usize, config:&Config)->bool{
+        match (self, population) {
+            (VariationStrategy::Usize(variation), Population::Usize(vec)) => variation.mutate(&mut vec[index], config.mutation_rate),
+            (VariationStrategy::F64(variation), Population::F64(vec)) => variation.mutate(&mut vec[index], config.mutation_rate),
+            (VariationStrategy::Tree(variation), Population::Tree(vec)) => variation.mutate(&mut vec[index], config.mutation_rate),
+            _ => panic!("Variation and population must be of the same type"),
+        }
+    }
+}
+fn mate_with<T>(variation:&dyn Variation<T>, vec:&[T], parents:&[usize], out:&mut [T]){
+    for (pair, indices) in out.chunks_exact_mut(2).zip(parents.chunks_exact(2)){
+        let (child1, child2) = variation.crossover(&vec[indices[0]], &vec[indices[1]]);
+        pair[0] = child1;
+        pair[1] = child2;
+    }
+}
+pub trait Mutation{
+    fn mutate(&self, config:&Config)->Population;
+}
+impl Mutation for Population{
+    ///return a mutated copy of the population<BR>
+    /// F64: each gene is redrawn within config.range with probability config.mutation_rate<BR>
+    /// Usize: each individual has two genes swapped with probability config.mutation_rate<BR>
+    /// Tree: each tree is mutated with probability config.mutation_rate (see Tree::mutate)<BR>
+    /// locked genes (config.locked) are never mutated and are restored if a previous operator moved them
+    fn mutate(&self, config:&Config)->Population{
+        let mut mutated = self.clone();
+        for index in 0..mutated.len(){
+            mutated.mutate_at(index, config);
+        }
+        if let Some(locked) = &config.locked {
+            mutated.lock(locked);
+        }
+        mutated
+    }
+}
+impl Crossover<Tree> for Population{
+    ///subtree crossover (see Tree::crossover)
+    fn crossover(&self, parent1_index: usize, parent2_index: usize) -> (Tree,Tree) {
+        match self {
+            Population::Tree(vec)=>vec[parent1_index].crossover(&vec[parent2_index]),
+            _ => unimplemented!(),
+        }
+    }
+}
 /*
 impl Crossover<Vec<usize>> for Population {
     fn crossover_old(&self, parent1_index: usize, parent2_index: usize) -> (Vec<usize>, Vec<usize>) {
@@ -343,62 +956,71 @@ impl Crossover<Vec<usize>> for Population {
     }
 }
 */
-use std::collections::HashMap;
-
 impl Crossover<Vec<usize>> for Population {
+    ///order crossover (see order_crossover)
     fn crossover(&self, parent1_index: usize, parent2_index: usize) -> (Vec<usize>, Vec<usize>) {
         match self {
             Population::Usize(vec) => {
                 let size = vec[parent1_index].len();
-                let mut child1 = vec![None; size];
-                let mut child2 = vec![None; size];
+                let mut child1 = vec![0; size];
+                let mut child2 = vec![0; size];
                 let mut rng = rand::thread_rng();
                 let start = rng.gen_range(0..size);
                 let end = rng.gen_range(start..size);
-
-                // Crossover Section
-                for i in start..=end {
-                    child1[i] = Some(vec[parent1_index][i]);
-                    child2[i] = Some(vec[parent2_index][i]);
-                }
-
-                // Create frequency maps for tracking duplicates
-                let mut freq_map_child1 = HashMap::new();
-                let mut freq_map_child2 = HashMap::new();
-
-                let mut current_index1 = (end + 1) % size;
-                let mut current_index2 = (end + 1) % size;
-
-                for &gene in &vec[parent2_index] {
-                    let count_child1 = freq_map_child1.entry(gene).or_insert(0);
-                    if !child1[current_index1].is_some() && *count_child1 < count_occurrences(gene, &vec[parent2_index]) {
-                        child1[current_index1] = Some(gene);
-                        *count_child1 += 1;
-                        current_index1 = (current_index1 + 1) % size;
-                    }
-                }
-
-                for &gene in &vec[parent1_index] {
-                    let count_child2 = freq_map_child2.entry(gene).or_insert(0);
-                    if !child2[current_index2].is_some() && *count_child2 < count_occurrences(gene, &vec[parent1_index]) {
-                        child2[current_index2] = Some(gene);
-                        *count_child2 += 1;
-                        current_index2 = (current_index2 + 1) % size;
-                    }
-                }
-
-                (child1.into_iter().map(|x| x.unwrap()).collect(), child2.into_iter().map(|x| x.unwrap()).collect())
+                let mut counts = Vec::new();
+                order_crossover(&vec[parent1_index], &vec[parent2_index], start, end, &mut child1, &mut counts);
+                order_crossover(&vec[parent2_index], &vec[parent1_index], start, end, &mut child2, &mut counts);
+                (child1, child2)
             }
             _ => unimplemented!(),
         }
     }
 }
 
-// Helper function to count occurrences of a value
-fn count_occurrences(value: usize, vec: &Vec<usize>) -> usize {
-    vec.iter().filter(|&&x| x == value).count()
+///order crossover (OX) generalized to repeated genes<BR>
+/// the child keeps parent1[start..=end], the other positions (from end+1, wrapping around) receive the genes of parent2
+/// in order (from end+1, wrapping around), skipping one occurrence of each gene already in the kept segment<BR>
+/// permutations stay permutations, and children of parents with the same genes (e.g. permutations with repetition) keep them<BR>
+/// counts is scratch space, left zeroed
+fn order_crossover(parent1:&[usize], parent2:&[usize], start:usize, end:usize, child:&mut [usize], counts:&mut Vec<usize>){
+    let size = parent1.len();
+    child.copy_from_slice(parent1);
+    for &gene in &parent1[start..=end] {
+        if gene >= counts.len() {
+            counts.resize(gene + 1, 0);
+        }
+        counts[gene] += 1;
+    }
+    let mut position = (end + 1) % size;
+    let mut remaining = size - (end - start + 1);
+    for k in 0..size {
+        if remaining == 0 {
+            break;
+        }
+        let gene = parent2[(end + 1 + k) % size];
+        if gene < counts.len() && counts[gene] > 0 {
+            counts[gene] -= 1;
+            continue;
+        }
+        child[position] = gene;
+        position = (position + 1) % size;
+        remaining -= 1;
+    }
+    for &gene in &parent1[start..=end] {
+        counts[gene] = 0;
+    }
+}
+///copy other[indices[i]] into vec[i], reusing the allocations of vec
+fn copy_genomes<T:Clone>(vec:&mut Vec<T>, other:&[T], indices:&[usize]){
+    vec.truncate(indices.len());
+    for (i, &j) in indices.iter().enumerate() {
+        if i < vec.len() {
+            vec[i].clone_from(&other[j]);
+        } else {
+            vec.push(other[j].clone());
+        }
+    }
 }
-
 
 pub trait GetPopulation<T>{
     fn get_individual(&self, index:usize)->Option<Vec<T>>;
@@ -418,4 +1040,121 @@ impl GetPopulation<usize> for Population{
             _=>None,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn sorted(genes:&[usize])->Vec<usize>{
+        let mut genes = genes.to_vec();
+        genes.sort_unstable();
+        genes
+    }
+
+    #[test]
+    fn order_crossover_children_are_permutations(){
+        let mut rng = thread_rng();
+        let mut counts = Vec::new();
+        for _ in 0..1000 {
+            let size = rng.gen_range(1..30);
+            let mut parent1:Vec<usize> = (0..size).collect();
+            let mut parent2 = parent1.clone();
+            parent1.shuffle(&mut rng);
+            parent2.shuffle(&mut rng);
+            let start = rng.gen_range(0..size);
+            let end = rng.gen_range(start..size);
+            let mut child = vec![0; size];
+            order_crossover(&parent1, &parent2, start, end, &mut child, &mut counts);
+            assert_eq!(sorted(&child), (0..size).collect::<Vec<usize>>());
+            assert_eq!(child[start..=end], parent1[start..=end]);
+            assert!(counts.iter().all(|&count| count == 0));
+        }
+    }
+
+    #[test]
+    fn order_crossover_keeps_repeated_genes(){
+        let mut rng = thread_rng();
+        let genes = vec![0, 0, 1, 1, 1, 2, 3, 3];
+        for _ in 0..1000 {
+            let mut population = vec![genes.clone(), genes.clone()];
+            population.iter_mut().for_each(|individual| individual.shuffle(&mut rng));
+            let (child1, child2):(Vec<usize>,Vec<usize>) = Population::Usize(population).crossover(0, 1);
+            assert_eq!(sorted(&child1), genes);
+            assert_eq!(sorted(&child2), genes);
+        }
+    }
+
+    #[test]
+    fn locked_integer_genes_are_overwritten(){
+        let mut population = Population::Usize(vec![vec![1, 2, 3, 2]]);
+        population.lock(&LockedGenes::Usize(vec![Some(2), None, None, None]));
+        assert_eq!(GetPopulation::<usize>::get_individual(&population, 0).unwrap(), vec![2, 2, 3, 2]);
+    }
+
+    #[test]
+    fn locked_permutation_genes_are_swapped_into_place(){
+        let mut population = Population::Usize(vec![vec![0, 1, 2, 3]]);
+        population.lock(&LockedGenes::Permutation(vec![None, None, None, Some(1)]));
+        assert_eq!(GetPopulation::<usize>::get_individual(&population, 0).unwrap(), vec![0, 3, 2, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Genes of tree individuals cannot be locked")]
+    fn locking_tree_genes_panics(){
+        Population::Tree(Vec::new()).lock_at(0, &LockedGenes::Usize(vec![Some(0)]));
+    }
+
+    #[test]
+    fn update_resets_scores_of_changed_genomes(){
+        let sum = |population:Population| match population {
+            Population::F64(vec) => vec[0].iter().sum(),
+            _ => panic!("Expected Population::F64"),
+        };
+        let config = Config{num_individuals:4, num_genes:3, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), sum, config);
+        ga.score();
+        let mut individuals:Vec<Vec<f64>> = (0..4).map(|i| GetPopulation::<f64>::get_individual(ga.population(), i).unwrap()).collect();
+        individuals[1] = vec![1.0, 2.0, 3.0];
+        ga.update(Population::F64(individuals));
+        assert!(ga.scores()[0].is_some());
+        assert!(ga.scores()[1].is_none());
+        assert_eq!(ga.evaluate()[1], 6.0);
+    }
+
+    #[test]
+    fn parallel_evaluation_matches_sequential(){
+        let sum = |population:Population| match population {
+            Population::F64(vec) => vec[0].iter().sum(),
+            _ => panic!("Expected Population::F64"),
+        };
+        let config = Config{num_individuals:50, num_genes:3, verbose:false, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), sum, config.clone());
+        let sequential = ga.evaluate();
+        ga.config = Config{parallel:true, ..config};
+        assert_eq!(ga.evaluate(), sequential);
+        assert_eq!(ga.score(), sequential);
+        assert!(ga.scores().iter().all(|evaluation| evaluation.is_some()));
+    }
+
+    #[test]
+    fn parallel_evaluation_reads_and_fills_the_cache(){
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static CALLS:AtomicUsize = AtomicUsize::new(0);
+        let sum = |population:Population| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            match population {
+                Population::F64(vec) => vec[0].iter().sum(),
+                _ => panic!("Expected Population::F64"),
+            }
+        };
+        let config = Config{num_individuals:4, num_genes:2, parallel:true, verbose:false, ..Config::default()};
+        let mut ga = GA::new(InitializationStrategy::F64(Box::new(RandomInitialization)), sum, config).with_cache(10);
+        ga.update(Population::F64(vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![1.0, 2.0], vec![1.0, 2.0]]));
+        //identical genomes are evaluated once
+        assert_eq!(ga.score(), vec![3.0, 7.0, 3.0, 3.0]);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+        ga.update(Population::F64(vec![vec![3.0, 4.0], vec![5.0, 6.0], vec![1.0, 2.0], vec![1.0, 2.0]]));
+        assert_eq!(ga.evaluate(), vec![7.0, 11.0, 3.0, 3.0]);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 3);
+    }
+}
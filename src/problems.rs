@@ -0,0 +1,8 @@
+//! standard benchmark problems ready to be used as fitness functions
+pub mod continuous;
+pub mod multi_objective;
+pub mod combinatorial;
+pub mod tsp;
+pub mod vrp;
+pub mod scheduling;
+pub mod graph;
@@ -0,0 +1,420 @@
+use std::fs;
+use std::io;
+use std::mem;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::constraints::Repair;
+use crate::population::Population;
+
+///decode the bits of the first individual of a population<BR>
+/// Usize genes are bits (non zero is 1), as used by EDA<BR>
+/// F64 genes are thresholded (> 0.5 is 1), e.g. GA with Config::range = 0.0..1.0:
+/// GA mutation of Usize genes swaps them (see Mutation) while F64 genes are redrawn, which acts as a bit flip
+pub fn bits(individual:&Population)->Vec<bool>{
+    match individual {
+        Population::Usize(vec) => vec[0].iter().map(|&gene| gene != 0).collect(),
+        Population::F64(vec) => vec[0].iter().map(|&gene| gene > 0.5).collect(),
+        _ => panic!("Expected Population::Usize or Population::F64"),
+    }
+}
+
+///binary maximization problem
+pub trait BinaryProblem{
+    ///value of a bit string (to be maximized)
+    fn value(&self, bits:&[bool])->f64;
+    ///number of bits (to be used as Config::num_genes)
+    fn num_bits(&self)->usize;
+    ///value of the global maximum (None if unknown)
+    fn optimum(&self)->Option<f64>;
+    ///fitness function for GA::new, EDA::new and the other engines (see bits for the accepted encodings)
+    fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>
+    where Self:Sized + Send + Sync + 'static{
+        Box::new(move |individual| self.value(&bits(&individual)))
+    }
+}
+
+///number of ones
+#[derive(Clone, Debug)]
+pub struct OneMax{
+    pub num_bits:usize,
+}
+impl BinaryProblem for OneMax{
+    fn value(&self, bits:&[bool])->f64{
+        bits.iter().filter(|&&bit| bit).count() as f64
+    }
+    fn num_bits(&self)->usize{
+        self.num_bits
+    }
+    fn optimum(&self)->Option<f64>{
+        Some(self.num_bits as f64)
+    }
+}
+
+///number of consecutive ones at the start of the string
+#[derive(Clone, Debug)]
+pub struct LeadingOnes{
+    pub num_bits:usize,
+}
+impl BinaryProblem for LeadingOnes{
+    fn value(&self, bits:&[bool])->f64{
+        bits.iter().take_while(|&&bit| bit).count() as f64
+    }
+    fn num_bits(&self)->usize{
+        self.num_bits
+    }
+    fn optimum(&self)->Option<f64>{
+        Some(self.num_bits as f64)
+    }
+}
+
+///concatenated deceptive traps: each block of block_size bits with u ones scores block_size if u = block_size,
+/// block_size - 1 - u otherwise, so hill climbing leads every block to all zeros
+#[derive(Clone, Debug)]
+pub struct Trap{
+    pub num_blocks:usize,
+    pub block_size:usize,
+}
+impl Trap{
+    pub fn new(num_blocks:usize, block_size:usize) -> Self{
+        assert!(block_size > 0, "Trap blocks need at least one bit");
+        Trap{num_blocks, block_size}
+    }
+}
+impl BinaryProblem for Trap{
+    fn value(&self, bits:&[bool])->f64{
+        bits.chunks(self.block_size).map(|block| {
+            let ones = block.iter().filter(|&&bit| bit).count();
+            if ones == block.len() {ones} else {block.len() - 1 - ones}
+        }).sum::<usize>() as f64
+    }
+    fn num_bits(&self)->usize{
+        self.num_blocks * self.block_size
+    }
+    fn optimum(&self)->Option<f64>{
+        Some(self.num_bits() as f64)
+    }
+}
+
+///0/1 (one constraint) or multidimensional knapsack: pick items maximizing the total value
+/// without exceeding any capacity<BR>
+/// infeasible selections score minus their total excess weight, below every feasible one
+#[derive(Clone, Debug)]
+pub struct Knapsack{
+    pub values:Vec<f64>,
+    ///weight of each item, one row per constraint
+    pub weights:Vec<Vec<f64>>,
+    ///capacity of each constraint
+    pub capacities:Vec<f64>,
+    ///best known value (None if unknown)
+    pub optimum:Option<f64>,
+}
+impl Knapsack{
+    ///0/1 knapsack
+    pub fn new(values:Vec<f64>, weights:Vec<f64>, capacity:f64) -> Self{
+        Knapsack::multidimensional(values, vec![weights], vec![capacity])
+    }
+    pub fn multidimensional(values:Vec<f64>, weights:Vec<Vec<f64>>, capacities:Vec<f64>) -> Self{
+        assert_eq!(weights.len(), capacities.len(), "One capacity is needed per row of weights");
+        assert!(weights.iter().all(|row| row.len() == values.len()), "One weight per item is needed in every row");
+        Knapsack{values, weights, capacities, optimum:None}
+    }
+    ///random instance as generated by Chu and Beasley: weights in [0,1000), capacities half of the total weight
+    /// (tightness 0.5) and values correlated with the weights
+    pub fn random(num_items:usize, num_constraints:usize) -> Self{
+        let mut rng = rand::thread_rng();
+        let weights:Vec<Vec<f64>> = (0..num_constraints).map(|_| (0..num_items).map(|_| rng.gen_range(0..1000) as f64).collect()).collect();
+        let capacities = weights.iter().map(|row| 0.5 * row.iter().sum::<f64>()).collect();
+        let values = (0..num_items)
+            .map(|i| (weights.iter().map(|row| row[i]).sum::<f64>() / num_constraints as f64 + 500.0 * rng.gen::<f64>()).round())
+            .collect();
+        Knapsack::multidimensional(values, weights, capacities)
+    }
+    ///weight of the selected items exceeding each capacity (0 when satisfied), to be used as violations with Constraints
+    pub fn excess(&self, bits:&[bool])->Vec<f64>{
+        self.weights.iter().zip(&self.capacities).map(|(row, capacity)| {
+            let weight:f64 = row.iter().zip(bits).filter(|(_, &bit)| bit).map(|(weight, _)| weight).sum();
+            (weight - capacity).max(0.0)
+        }).collect()
+    }
+    ///greedy repair: drop the selected items of lowest value per unit of weight until the selection is feasible,
+    /// then add the unselected items of highest value per unit of weight that fit
+    pub fn repair_bits(&self, bits:&mut [bool]){
+        let efficiency = |i:usize| self.values[i] / self.weights.iter().zip(&self.capacities).map(|(row, capacity)| row[i] / capacity).sum::<f64>().max(f64::EPSILON);
+        let mut order:Vec<usize> = (0..self.values.len()).collect();
+        order.sort_by(|&a, &b| efficiency(a).partial_cmp(&efficiency(b)).unwrap());
+        let mut load:Vec<f64> = self.weights.iter().map(|row| row.iter().zip(&*bits).filter(|(_, &bit)| bit).map(|(weight, _)| weight).sum()).collect();
+        for &i in &order {
+            if load.iter().zip(&self.capacities).all(|(load, capacity)| load <= capacity) {
+                break;
+            }
+            if bits[i] {
+                bits[i] = false;
+                load.iter_mut().zip(&self.weights).for_each(|(load, row)| *load -= row[i]);
+            }
+        }
+        for &i in order.iter().rev() {
+            if !bits[i] && load.iter().zip(&self.weights).zip(&self.capacities).all(|((load, row), capacity)| load + row[i] <= *capacity) {
+                bits[i] = true;
+                load.iter_mut().zip(&self.weights).for_each(|(load, row)| *load += row[i]);
+            }
+        }
+    }
+}
+impl BinaryProblem for Knapsack{
+    fn value(&self, bits:&[bool])->f64{
+        let excess:f64 = self.excess(bits).iter().sum();
+        if excess > 0.0 {
+            return -excess;
+        }
+        self.values.iter().zip(bits).filter(|(_, &bit)| bit).map(|(value, _)| value).sum()
+    }
+    fn num_bits(&self)->usize{
+        self.values.len()
+    }
+    fn optimum(&self)->Option<f64>{
+        self.optimum
+    }
+}
+///greedy repair of Usize bits (see Knapsack::repair_bits)
+impl Repair<Vec<usize>> for Knapsack{
+    fn repair(&self, individual:Vec<usize>)->Vec<usize>{
+        let mut bits:Vec<bool> = individual.iter().map(|&gene| gene != 0).collect();
+        self.repair_bits(&mut bits);
+        bits.into_iter().map(usize::from).collect()
+    }
+}
+///greedy repair of F64 bits (see Knapsack::repair_bits), genes keep their value when their bit is unchanged
+impl Repair<Vec<f64>> for Knapsack{
+    fn repair(&self, mut individual:Vec<f64>)->Vec<f64>{
+        let mut bits:Vec<bool> = individual.iter().map(|&gene| gene > 0.5).collect();
+        self.repair_bits(&mut bits);
+        for (gene, bit) in individual.iter_mut().zip(bits) {
+            if bit != (*gene > 0.5) {
+                *gene = if bit {1.0} else {0.0};
+            }
+        }
+        individual
+    }
+}
+
+///Kauffman NK landscape: the mean over genes of a random contribution depending on the gene and K other genes<BR>
+/// K = 0 gives a smooth landscape, larger K a more rugged one
+#[derive(Clone, Debug)]
+pub struct NKLandscape{
+    pub k:usize,
+    ///the K genes interacting with each gene
+    pub neighbours:Vec<Vec<usize>>,
+    ///contribution of each gene for each of the 2^(K+1) values of the gene and its neighbours
+    pub contributions:Vec<Vec<f64>>,
+}
+impl NKLandscape{
+    ///random landscape: neighbours are the next K genes (wrapping around) if adjacent, otherwise K random genes
+    pub fn new(n:usize, k:usize, adjacent:bool) -> Self{
+        assert!(k < n, "K must be lower than N");
+        let mut rng = rand::thread_rng();
+        let neighbours = (0..n).map(|i| {
+            if adjacent {
+                (1..=k).map(|j| (i + j) % n).collect()
+            } else {
+                let others:Vec<usize> = (0..n).filter(|&j| j != i).collect();
+                others.choose_multiple(&mut rng, k).cloned().collect()
+            }
+        }).collect();
+        let contributions = (0..n).map(|_| (0..1usize << (k + 1)).map(|_| rng.gen::<f64>()).collect()).collect();
+        NKLandscape{k, neighbours, contributions}
+    }
+}
+impl BinaryProblem for NKLandscape{
+    fn value(&self, bits:&[bool])->f64{
+        self.neighbours.iter().zip(&self.contributions).enumerate().map(|(i, (neighbours, contributions))| {
+            let index = neighbours.iter().fold(usize::from(bits[i]), |index, &j| index << 1 | usize::from(bits[j]));
+            contributions[index]
+        }).sum::<f64>() / bits.len() as f64
+    }
+    fn num_bits(&self)->usize{
+        self.neighbours.len()
+    }
+    ///exhaustive search, only for N <= 20
+    fn optimum(&self)->Option<f64>{
+        let n = self.num_bits();
+        if n > 20 {
+            return None;
+        }
+        (0..1usize << n)
+            .map(|string| self.value(&(0..n).map(|i| string >> i & 1 == 1).collect::<Vec<bool>>()))
+            .reduce(f64::max)
+    }
+}
+
+///MAX-SAT: number of satisfied clauses of a CNF formula
+#[derive(Clone, Debug)]
+pub struct MaxSat{
+    pub num_variables:usize,
+    ///literals of each clause: variable i (from 1) is i, its negation -i
+    pub clauses:Vec<Vec<i64>>,
+    ///best known number of satisfied clauses (None if unknown)
+    pub optimum:Option<f64>,
+}
+impl MaxSat{
+    ///read a DIMACS CNF file
+    pub fn from_dimacs(path:&str) -> io::Result<Self>{
+        MaxSat::parse_dimacs(&fs::read_to_string(path)?)
+    }
+    ///parse DIMACS CNF: comment lines start with c, the problem line is "p cnf variables clauses",
+    /// then each clause is a list of literals terminated by 0 (clauses may span several lines)
+    pub fn parse_dimacs(content:&str) -> io::Result<Self>{
+        let invalid = |message:String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut header = None;
+        let mut clauses = Vec::new();
+        let mut clause = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            //some benchmark files end with "%" followed by "0"
+            if line.starts_with('%') {
+                break;
+            }
+            if line.starts_with('p') {
+                let fields:Vec<&str> = line.split_whitespace().collect();
+                match fields[..] {
+                    ["p", "cnf", variables, count] => {
+                        let parse = |field:&str| field.parse::<usize>().map_err(|e| invalid(format!("line {}: {}", number + 1, e)));
+                        header = Some((parse(variables)?, parse(count)?));
+                    }
+                    _ => return Err(invalid(format!("line {}: expected p cnf <variables> <clauses>", number + 1))),
+                }
+                continue;
+            }
+            let (num_variables, _) = header.ok_or_else(|| invalid(format!("line {}: clause before the problem line", number + 1)))?;
+            for field in line.split_whitespace() {
+                let literal:i64 = field.parse().map_err(|e| invalid(format!("line {}: {}", number + 1, e)))?;
+                if literal == 0 {
+                    clauses.push(mem::take(&mut clause));
+                } else if literal.unsigned_abs() as usize > num_variables {
+                    return Err(invalid(format!("line {}: variable {} out of range", number + 1, literal.abs())));
+                } else {
+                    clause.push(literal);
+                }
+            }
+        }
+        if !clause.is_empty() {
+            clauses.push(clause);
+        }
+        let (num_variables, num_clauses) = header.ok_or_else(|| invalid("missing problem line".to_string()))?;
+        if clauses.len() != num_clauses {
+            return Err(invalid(format!("{} clauses declared, {} found", num_clauses, clauses.len())));
+        }
+        Ok(MaxSat{num_variables, clauses, optimum:None})
+    }
+    ///random satisfiable instance: clauses of k distinct variables, each satisfied by a hidden random assignment
+    pub fn planted(num_variables:usize, num_clauses:usize, k:usize) -> Self{
+        let mut rng = rand::thread_rng();
+        let assignment:Vec<bool> = (0..num_variables).map(|_| rng.gen()).collect();
+        let variables:Vec<usize> = (1..=num_variables).collect();
+        let mut clauses = Vec::with_capacity(num_clauses);
+        while clauses.len() < num_clauses {
+            let clause:Vec<i64> = variables.choose_multiple(&mut rng, k).map(|&v| if rng.gen() {v as i64} else {-(v as i64)}).collect();
+            if clause.iter().any(|&literal| (literal > 0) == assignment[literal.unsigned_abs() as usize - 1]) {
+                clauses.push(clause);
+            }
+        }
+        MaxSat{num_variables, clauses, optimum:Some(num_clauses as f64)}
+    }
+}
+impl BinaryProblem for MaxSat{
+    fn value(&self, bits:&[bool])->f64{
+        self.clauses.iter()
+            .filter(|clause| clause.iter().any(|&literal| (literal > 0) == bits[literal.unsigned_abs() as usize - 1]))
+            .count() as f64
+    }
+    fn num_bits(&self)->usize{
+        self.num_variables
+    }
+    fn optimum(&self)->Option<f64>{
+        self.optimum
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn dimacs_cnf_is_parsed(){
+        let content = "c example formula\nc\np cnf 3 3\n1 -2 0\n2 3\n-1 0 -3 0\n%\n0\n";
+        let problem = MaxSat::parse_dimacs(content).unwrap();
+        assert_eq!(problem.num_variables, 3);
+        assert_eq!(problem.clauses, vec![vec![1, -2], vec![2, 3, -1], vec![-3]]);
+        assert_eq!(problem.value(&[true, true, false]), 3.0);
+        assert_eq!(problem.value(&[false, false, true]), 2.0);
+    }
+
+    #[test]
+    fn malformed_dimacs_cnf_is_rejected(){
+        for content in ["1 2 0\np cnf 2 1\n", "p cnf 2 1\n1 3 0\n", "p sat 2 1\n1 2 0\n", "p cnf 2 1\n1 x 0\n", "c no problem line\n", "p cnf 2 2\n1 2 0\n", "p cnf 2 1\n1 0 2 0\n"] {
+            let error = MaxSat::parse_dimacs(content).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", content);
+        }
+    }
+
+    #[test]
+    fn planted_instances_are_satisfiable(){
+        let problem = MaxSat::planted(12, 50, 3);
+        assert_eq!(problem.clauses.len(), 50);
+        assert!(problem.clauses.iter().all(|clause| clause.len() == 3));
+        let best = (0..1usize << 12).map(|string| problem.value(&(0..12).map(|i| string >> i & 1 == 1).collect::<Vec<bool>>())).fold(0.0, f64::max);
+        assert_eq!(Some(best), problem.optimum());
+    }
+
+    #[test]
+    fn trap_block_values(){
+        let trap = Trap::new(2, 4);
+        assert_eq!(trap.num_bits(), 8);
+        //all ones scores block_size, otherwise block_size - 1 - ones
+        assert_eq!(trap.value(&[true; 8]), 8.0);
+        assert_eq!(trap.value(&[false; 8]), 6.0);
+        assert_eq!(trap.value(&[true, true, true, false, true, false, false, false]), 2.0);
+        //a partial last block is scored as a trap of its own length
+        assert_eq!(trap.value(&[true, true, true, true, false, false]), 5.0);
+        assert_eq!(trap.value(&[false, false, false, false, true, true]), 5.0);
+        assert_eq!(trap.value(&[false, false, false, false, true, false]), 3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Trap blocks need at least one bit")]
+    fn trap_rejects_empty_blocks(){
+        Trap::new(3, 0);
+    }
+
+    #[test]
+    fn knapsack_repair_keeps_efficient_items(){
+        //values per unit of weight: 2, 0.2, 3, 1
+        let knapsack = Knapsack::new(vec![10.0, 1.0, 6.0, 4.0], vec![5.0, 5.0, 2.0, 4.0], 7.0);
+        for start in [[true; 4], [false; 4], [true, true, false, false]] {
+            let mut bits = start;
+            knapsack.repair_bits(&mut bits);
+            assert_eq!(bits, [true, false, true, false], "{:?}", start);
+            assert_eq!(knapsack.value(&bits), 16.0);
+        }
+        let repaired = Repair::<Vec<f64>>::repair(&knapsack, vec![0.9, 0.7, 0.8, 0.2]);
+        assert_eq!(repaired, vec![0.9, 0.0, 0.8, 0.2]);
+    }
+
+    #[test]
+    fn knapsack_repair_is_feasible(){
+        let knapsack = Knapsack::random(30, 3);
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let mut bits:Vec<bool> = (0..30).map(|_| rng.gen()).collect();
+            knapsack.repair_bits(&mut bits);
+            assert!(knapsack.excess(&bits).iter().all(|&excess| excess == 0.0));
+            //no unselected item fits any more
+            let load:Vec<f64> = knapsack.weights.iter().map(|row| row.iter().zip(&bits).filter(|(_, &bit)| bit).map(|(w, _)| w).sum()).collect();
+            for i in (0..30).filter(|&i| !bits[i]) {
+                assert!(knapsack.weights.iter().zip(&load).zip(&knapsack.capacities).any(|((row, load), capacity)| load + row[i] > *capacity));
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::f64::consts::{E, PI};
+use std::ops::Range;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use crate::population::Population;
+
+///continuous minimization problem on Vec<f64> individuals
+pub trait Problem{
+    ///objective value of a point (to be minimized)
+    fn value(&self, x:&[f64])->f64;
+    ///recommended search range of every gene (to be used as Config::range)
+    fn bounds(&self)->Range<f64>;
+    ///location of the global minimum in dim dimensions (None if unknown)
+    fn optimum(&self, dim:usize)->Option<Vec<f64>>;
+    ///value of the global minimum in dim dimensions (None if unknown)
+    fn optimum_value(&self, dim:usize)->Option<f64>;
+    ///fitness function for GA::new and the other engines (negated value, as fitness is maximized)
+    fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>
+    where Self:Sized + Send + Sync + 'static{
+        Box::new(move |individual| match individual {
+            Population::F64(vec) => -self.value(&vec[0]),
+            _ => panic!("Expected Population::F64"),
+        })
+    }
+}
+
+///classic test functions, defined for any number of dimensions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Benchmark{
+    ///sum of x², unimodal
+    Sphere,
+    ///10d + sum of x² - 10cos(2πx), regular grid of local minima
+    Rastrigin,
+    ///sum of 100(x[i+1] - x[i]²)² + (1 - x[i])², minimum at the end of a narrow curved valley
+    Rosenbrock,
+    ///nearly flat outer region with a deep hole at the origin
+    Ackley,
+    ///sum of x²/4000 - product of cos(x[i]/√(i+1)) + 1
+    Griewank,
+    ///418.9829d - sum of x sin(√|x|), the best local minima are far from the global one<BR>
+    /// genes outside [-500,500] are folded back with a quadratic penalty (as in CEC 2014) so that the optimum stays global
+    Schwefel,
+    ///sin²(πw[0]) + sum of (w[i] - 1)²(1 + 10sin²(πw[i] + 1)) + (w[d-1] - 1)²(1 + sin²(2πw[d-1])) with w = 1 + (x - 1)/4
+    Levy,
+    ///-sum of sin(x[i]) sin((i+1)x[i]²/π)^20, steep ridges and valleys
+    Michalewicz,
+    ///sum of (x⁴ - 16x² + 5x) / 2
+    StyblinskiTang,
+}
+impl Benchmark{
+    pub fn all()->[Benchmark;9]{
+        [Benchmark::Sphere, Benchmark::Rastrigin, Benchmark::Rosenbrock, Benchmark::Ackley, Benchmark::Griewank,
+            Benchmark::Schwefel, Benchmark::Levy, Benchmark::Michalewicz, Benchmark::StyblinskiTang]
+    }
+}
+impl Problem for Benchmark{
+    fn value(&self, x:&[f64])->f64{
+        assert!(!x.is_empty(), "Benchmarks are defined for at least one dimension");
+        let d = x.len() as f64;
+        match self {
+            Benchmark::Sphere => x.iter().map(|x| x * x).sum(),
+            Benchmark::Rastrigin => 10.0 * d + x.iter().map(|x| x * x - 10.0 * (2.0 * PI * x).cos()).sum::<f64>(),
+            Benchmark::Rosenbrock => x.windows(2).map(|x| 100.0 * (x[1] - x[0] * x[0]).powi(2) + (1.0 - x[0]).powi(2)).sum(),
+            Benchmark::Ackley => {
+                let squares = x.iter().map(|x| x * x).sum::<f64>() / d;
+                let cosines = x.iter().map(|x| (2.0 * PI * x).cos()).sum::<f64>() / d;
+                -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + E
+            }
+            Benchmark::Griewank => {
+                let squares = x.iter().map(|x| x * x).sum::<f64>() / 4000.0;
+                let cosines:f64 = x.iter().enumerate().map(|(i, x)| (x / ((i + 1) as f64).sqrt()).cos()).product();
+                squares - cosines + 1.0
+            }
+            Benchmark::Schwefel => 418.9828872724338 * d - x.iter().map(|&x| {
+                if x.abs() <= 500.0 {
+                    x * x.abs().sqrt().sin()
+                } else {
+                    let folded = (500.0 - x.abs() % 500.0) * x.signum();
+                    folded * folded.abs().sqrt().sin() - (x.abs() - 500.0).powi(2) / (10000.0 * d)
+                }
+            }).sum::<f64>(),
+            Benchmark::Levy => {
+                let w:Vec<f64> = x.iter().map(|x| 1.0 + (x - 1.0) / 4.0).collect();
+                let last = w[w.len() - 1];
+                (PI * w[0]).sin().powi(2)
+                    + w[..w.len() - 1].iter().map(|w| (w - 1.0).powi(2) * (1.0 + 10.0 * (PI * w + 1.0).sin().powi(2))).sum::<f64>()
+                    + (last - 1.0).powi(2) * (1.0 + (2.0 * PI * last).sin().powi(2))
+            }
+            Benchmark::Michalewicz => -x.iter().enumerate().map(|(i, x)| x.sin() * ((i + 1) as f64 * x * x / PI).sin().powi(20)).sum::<f64>(),
+            Benchmark::StyblinskiTang => x.iter().map(|x| x.powi(4) - 16.0 * x * x + 5.0 * x).sum::<f64>() / 2.0,
+        }
+    }
+    fn bounds(&self)->Range<f64>{
+        match self {
+            Benchmark::Sphere | Benchmark::Rastrigin => -5.12..5.12,
+            Benchmark::Rosenbrock => -5.0..10.0,
+            Benchmark::Ackley => -32.768..32.768,
+            Benchmark::Griewank => -600.0..600.0,
+            Benchmark::Schwefel => -500.0..500.0,
+            Benchmark::Levy => -10.0..10.0,
+            Benchmark::Michalewicz => 0.0..PI,
+            Benchmark::StyblinskiTang => -5.0..5.0,
+        }
+    }
+    fn optimum(&self, dim:usize)->Option<Vec<f64>>{
+        match self {
+            Benchmark::Sphere | Benchmark::Rastrigin | Benchmark::Ackley | Benchmark::Griewank => Some(vec![0.0; dim]),
+            Benchmark::Rosenbrock | Benchmark::Levy => Some(vec![1.0; dim]),
+            Benchmark::Schwefel => Some(vec![420.9687462275036; dim]),
+            Benchmark::Michalewicz => None,
+            Benchmark::StyblinskiTang => Some(vec![-2.903534027771178; dim]),
+        }
+    }
+    fn optimum_value(&self, dim:usize)->Option<f64>{
+        match self {
+            //only known numerically for a few dimensions
+            Benchmark::Michalewicz => match dim {
+                2 => Some(-1.8013034),
+                5 => Some(-4.6876582),
+                10 => Some(-9.6601517),
+                _ => None,
+            },
+            _ => self.optimum(dim).map(|optimum| self.value(&optimum)),
+        }
+    }
+}
+
+///CEC-style variant of a benchmark: F(x) = f(M(x - o) + x*) + bias<BR>
+/// where o is the shift, M an orthogonal rotation and x* the optimum of f, so that the optimum of F is at o<BR>
+/// (x* is taken as the origin when the optimum of f is unknown)
+#[derive(Clone, Debug)]
+pub struct Transformed{
+    pub benchmark:Benchmark,
+    ///location of the optimum, one value per dimension
+    pub shift:Vec<f64>,
+    ///orthogonal matrix applied after shifting (None for shifted only variants)
+    pub rotation:Option<Vec<Vec<f64>>>,
+    ///value added to the objective
+    pub bias:f64,
+}
+impl Transformed{
+    ///shift drawn uniformly within the central 80% of the benchmark bounds
+    pub fn shifted(benchmark:Benchmark, dim:usize) -> Self{
+        assert!(dim >= 1, "Benchmarks are defined for at least one dimension");
+        let bounds = benchmark.bounds();
+        let center = (bounds.start + bounds.end) / 2.0;
+        let radius = 0.4 * (bounds.end - bounds.start);
+        let mut rng = rand::thread_rng();
+        let shift = (0..dim).map(|_| center + rng.gen_range(-radius..radius)).collect();
+        Transformed{benchmark, shift, rotation:None, bias:0.0}
+    }
+    ///random shift (see shifted) and random rotation (see random_rotation)
+    pub fn shifted_rotated(benchmark:Benchmark, dim:usize) -> Self{
+        Transformed{rotation:Some(random_rotation(dim)), ..Transformed::shifted(benchmark, dim)}
+    }
+    pub fn with_bias(mut self, bias:f64) -> Self{
+        self.bias = bias;
+        self
+    }
+}
+impl Problem for Transformed{
+    fn value(&self, x:&[f64])->f64{
+        assert_eq!(x.len(), self.shift.len(), "Individuals must have one gene per shift dimension");
+        let shifted:Vec<f64> = x.iter().zip(&self.shift).map(|(x, o)| x - o).collect();
+        let rotated = match &self.rotation {
+            Some(rotation) => rotation.iter().map(|row| row.iter().zip(&shifted).map(|(m, z)| m * z).sum()).collect(),
+            None => shifted,
+        };
+        let center = self.benchmark.optimum(x.len()).unwrap_or_else(|| vec![0.0; x.len()]);
+        let z:Vec<f64> = rotated.iter().zip(&center).map(|(z, c)| z + c).collect();
+        self.benchmark.value(&z) + self.bias
+    }
+    fn bounds(&self)->Range<f64>{
+        self.benchmark.bounds()
+    }
+    fn optimum(&self, dim:usize)->Option<Vec<f64>>{
+        self.benchmark.optimum(dim).map(|_| self.shift.clone())
+    }
+    fn optimum_value(&self, dim:usize)->Option<f64>{
+        self.benchmark.optimum(dim).and(self.benchmark.optimum_value(dim)).map(|value| value + self.bias)
+    }
+}
+
+///random orthogonal matrix: Gram-Schmidt orthonormalization of a gaussian matrix
+pub fn random_rotation(dim:usize)->Vec<Vec<f64>>{
+    let mut rng = rand::thread_rng();
+    let mut rows:Vec<Vec<f64>> = Vec::with_capacity(dim);
+    while rows.len() < dim {
+        let mut row:Vec<f64> = (0..dim).map(|_| rng.sample(StandardNormal)).collect();
+        for other in &rows {
+            let dot:f64 = row.iter().zip(other).map(|(a, b)| a * b).sum();
+            row.iter_mut().zip(other).for_each(|(a, b)| *a -= dot * b);
+        }
+        let norm = row.iter().map(|a| a * a).sum::<f64>().sqrt();
+        //draw again the (unlikely) rows that are almost dependent on the previous ones
+        if norm > 1e-6 {
+            rows.push(row.into_iter().map(|a| a / norm).collect());
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn optima_have_the_published_values(){
+        for dim in [1, 2, 5, 10] {
+            let d = dim as f64;
+            for (benchmark, expected) in [(Benchmark::Sphere, 0.0), (Benchmark::Rastrigin, 0.0), (Benchmark::Rosenbrock, 0.0), (Benchmark::Ackley, 0.0),
+                (Benchmark::Griewank, 0.0), (Benchmark::Schwefel, 0.0), (Benchmark::Levy, 0.0), (Benchmark::StyblinskiTang, -39.16616570377142 * d)] {
+                let value = benchmark.optimum_value(dim).unwrap();
+                assert!((value - expected).abs() < 1e-6 * d, "{:?} in {} dimensions: {} instead of {}", benchmark, dim, value, expected);
+            }
+        }
+        let michalewicz = Benchmark::Michalewicz.value(&[2.202905520, PI / 2.0]);
+        assert!((michalewicz - Benchmark::Michalewicz.optimum_value(2).unwrap()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn optima_are_local_minima(){
+        let mut rng = rand::thread_rng();
+        for benchmark in Benchmark::all() {
+            let Some(optimum) = benchmark.optimum(4) else {continue};
+            let best = benchmark.value(&optimum);
+            for _ in 0..100 {
+                let neighbour:Vec<f64> = optimum.iter().map(|x| x + rng.gen_range(-1e-3..1e-3)).collect();
+                assert!(benchmark.value(&neighbour) >= best, "{:?} decreases around its optimum", benchmark);
+            }
+        }
+    }
+
+    #[test]
+    fn transformed_optimum_is_the_shift(){
+        for benchmark in Benchmark::all() {
+            let problem = Transformed::shifted_rotated(benchmark, 3).with_bias(100.0);
+            if let (Some(optimum), Some(value)) = (problem.optimum(3), problem.optimum_value(3)) {
+                assert!((problem.value(&optimum) - value).abs() < 1e-6, "{:?}", benchmark);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one dimension")]
+    fn zero_dimensions_are_rejected(){
+        Transformed::shifted(Benchmark::Levy, 0);
+    }
+}
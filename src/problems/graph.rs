@@ -0,0 +1,452 @@
+use std::fs;
+use std::io;
+use rand::Rng;
+use crate::constraints::Repair;
+use crate::population::{Config, Initialization, InitializationStrategy, Population, Variation, VariationStrategy};
+use crate::problems::combinatorial::BinaryProblem;
+use crate::problems::scheduling::PermutationWithRepetition;
+use crate::problems::tsp::invalid;
+
+///undirected weighted graph, vertices numbered from 0
+#[derive(Clone, Debug)]
+pub struct Graph{
+    pub num_vertices:usize,
+    ///edges (u, v, weight) with u < v
+    pub edges:Vec<(usize,usize,f64)>,
+    ///neighbours (vertex, weight) of each vertex
+    pub adjacency:Vec<Vec<(usize,f64)>>,
+}
+impl Graph{
+    ///self loops are dropped and only the first of duplicated edges (in either direction) is kept,
+    /// as DIMACS files often list both directions
+    pub fn new(num_vertices:usize, edges:Vec<(usize,usize,f64)>) -> Self{
+        let mut adjacency:Vec<Vec<(usize,f64)>> = vec![Vec::new(); num_vertices];
+        let mut kept = Vec::with_capacity(edges.len());
+        for (u, v, weight) in edges {
+            assert!(u < num_vertices && v < num_vertices, "Edge ({}, {}) out of range", u, v);
+            let (u, v) = (u.min(v), u.max(v));
+            if u == v || adjacency[u].iter().any(|&(w, _)| w == v) {
+                continue;
+            }
+            adjacency[u].push((v, weight));
+            adjacency[v].push((u, weight));
+            kept.push((u, v, weight));
+        }
+        Graph{num_vertices, edges:kept, adjacency}
+    }
+    ///Erdos-Renyi random graph: each edge exists with the given probability, with weight 1
+    pub fn random(num_vertices:usize, probability:f64) -> Self{
+        let mut rng = rand::thread_rng();
+        let edges = (0..num_vertices)
+            .flat_map(|u| (u + 1..num_vertices).map(move |v| (u, v, 1.0)))
+            .filter(|_| rng.gen_bool(probability))
+            .collect();
+        Graph::new(num_vertices, edges)
+    }
+    pub fn num_edges(&self)->usize{
+        self.edges.len()
+    }
+    pub fn degree(&self, vertex:usize)->usize{
+        self.adjacency[vertex].len()
+    }
+    ///DSATUR greedy coloring: repeatedly color the vertex with the most distinct neighbour colors
+    /// (ties broken by degree) with the lowest available color<BR>
+    /// the number of colors used is an upper bound of the chromatic number
+    pub fn dsatur(&self)->Vec<usize>{
+        let mut colors:Vec<Option<usize>> = vec![None; self.num_vertices];
+        let mut neighbour_colors:Vec<Vec<bool>> = vec![Vec::new(); self.num_vertices];
+        for _ in 0..self.num_vertices {
+            let saturation = |v:usize| neighbour_colors[v].iter().filter(|&&used| used).count();
+            let vertex = (0..self.num_vertices)
+                .filter(|&v| colors[v].is_none())
+                .max_by_key(|&v| (saturation(v), self.degree(v)))
+                .unwrap();
+            let color = neighbour_colors[vertex].iter().position(|&used| !used).unwrap_or(neighbour_colors[vertex].len());
+            colors[vertex] = Some(color);
+            for &(neighbour, _) in &self.adjacency[vertex] {
+                let used = &mut neighbour_colors[neighbour];
+                if used.len() <= color {
+                    used.resize(color + 1, false);
+                }
+                used[color] = true;
+            }
+        }
+        colors.into_iter().map(Option::unwrap).collect()
+    }
+    ///read a DIMACS graph (.col) file
+    pub fn from_dimacs(path:&str) -> io::Result<Self>{
+        Graph::parse_dimacs(&fs::read_to_string(path)?)
+    }
+    ///parse a DIMACS graph: comment lines start with c, the problem line is "p edge vertices edges" (or "p col"),
+    /// edges are "e u v" with vertices numbered from 1 and weight 1
+    pub fn parse_dimacs(content:&str) -> io::Result<Self>{
+        let mut num_vertices = None;
+        let mut edges = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let fields:Vec<&str> = line.split_whitespace().collect();
+            let parse = |field:&str| field.parse::<usize>().map_err(|e| invalid(format!("line {}: {}", number + 1, e)));
+            match fields[..] {
+                [] | ["c", ..] => {}
+                ["p", "edge" | "col", vertices, _] => num_vertices = Some(parse(vertices)?),
+                ["e", u, v] => {
+                    let n = num_vertices.ok_or_else(|| invalid(format!("line {}: edge before the problem line", number + 1)))?;
+                    let (u, v) = (parse(u)?, parse(v)?);
+                    if u == 0 || v == 0 || u > n || v > n {
+                        return Err(invalid(format!("line {}: edge ({}, {}) out of range", number + 1, u, v)));
+                    }
+                    edges.push((u - 1, v - 1, 1.0));
+                }
+                _ => return Err(invalid(format!("line {}: expected p edge <vertices> <edges> or e <u> <v>", number + 1))),
+            }
+        }
+        let num_vertices = num_vertices.ok_or_else(|| invalid("missing problem line".to_string()))?;
+        Ok(Graph::new(num_vertices, edges))
+    }
+    ///read an edge list file
+    pub fn from_edge_list(path:&str) -> io::Result<Self>{
+        Graph::parse_edge_list(&fs::read_to_string(path)?)
+    }
+    ///parse an edge list: one edge "u v" or "u v weight" per line with vertices numbered from 0,
+    /// lines starting with # or % are comments<BR>
+    /// the number of vertices is the largest vertex plus one
+    pub fn parse_edge_list(content:&str) -> io::Result<Self>{
+        let mut edges = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+                continue;
+            }
+            let error = |message:String| invalid(format!("line {}: {}", number + 1, message));
+            let fields:Vec<&str> = line.split_whitespace().collect();
+            let (u, v, weight) = match fields[..] {
+                [u, v] => (u, v, None),
+                [u, v, weight] => (u, v, Some(weight)),
+                _ => return Err(error("expected <u> <v> [weight]".to_string())),
+            };
+            let u = u.parse::<usize>().map_err(|e| error(e.to_string()))?;
+            let v = v.parse::<usize>().map_err(|e| error(e.to_string()))?;
+            let weight = weight.map_or(Ok(1.0), |weight| weight.parse::<f64>()).map_err(|e| error(e.to_string()))?;
+            edges.push((u, v, weight));
+        }
+        let num_vertices = edges.iter().map(|&(u, v, _)| u.max(v) + 1).max().unwrap_or(0);
+        Ok(Graph::new(num_vertices, edges))
+    }
+}
+
+///integer genes drawn uniformly in 0..num_values (e.g. colors or part labels)
+pub struct IntegerInitialization{
+    pub num_values:usize,
+}
+impl Initialization<Vec<Vec<usize>>> for IntegerInitialization{
+    fn initialize(&self, config: Config) -> Vec<Vec<usize>> {
+        let mut rng = rand::thread_rng();
+        (0..config.num_individuals).map(|_| (0..config.num_genes).map(|_| rng.gen_range(0..self.num_values)).collect()).collect()
+    }
+}
+///operators for integer genes in 0..num_values, to be used with GA::with_variation
+/// (the built-in Usize operators only move genes around, they never change their values)<BR>
+/// uniform crossover, mutation redraws each gene with probability mutation_rate
+pub struct IntegerVariation{
+    pub num_values:usize,
+}
+impl Variation<Vec<usize>> for IntegerVariation{
+    fn crossover(&self, parent1:&Vec<usize>, parent2:&Vec<usize>)->(Vec<usize>,Vec<usize>){
+        let mut rng = rand::thread_rng();
+        let (mut child1, mut child2) = (parent1.clone(), parent2.clone());
+        for i in 0..child1.len() {
+            if rng.gen_bool(0.5) {
+                std::mem::swap(&mut child1[i], &mut child2[i]);
+            }
+        }
+        (child1, child2)
+    }
+    fn mutate(&self, individual:&mut Vec<usize>, mutation_rate:f64)->bool{
+        let mut rng = rand::thread_rng();
+        let mut changed = false;
+        for gene in individual.iter_mut() {
+            if rng.gen_bool(mutation_rate) {
+                let value = rng.gen_range(0..self.num_values);
+                changed |= value != *gene;
+                *gene = value;
+            }
+        }
+        changed
+    }
+}
+
+///graph coloring with a fixed number of colors: gene i is the color of vertex i,
+/// the fitness is minus the number of conflicts (edges whose ends share a color)
+#[derive(Clone, Debug)]
+pub struct GraphColoring{
+    pub graph:Graph,
+    pub num_colors:usize,
+}
+impl GraphColoring{
+    pub fn new(graph:Graph, num_colors:usize) -> Self{
+        GraphColoring{graph, num_colors}
+    }
+    ///number of edges whose ends share a color
+    pub fn conflicts(&self, colors:&[usize])->usize{
+        self.graph.edges.iter().filter(|&&(u, v, _)| colors[u] == colors[v]).count()
+    }
+    ///initialization strategy for GA::new (with Config::num_genes = number of vertices)
+    pub fn initialization(&self)->InitializationStrategy{
+        InitializationStrategy::Usize(Box::new(IntegerInitialization{num_values:self.num_colors}))
+    }
+    ///operators for GA::with_variation
+    pub fn variation(&self)->VariationStrategy{
+        VariationStrategy::Usize(Box::new(IntegerVariation{num_values:self.num_colors}))
+    }
+    ///fitness function for GA::new with initialization(): minus the number of conflicts, 0 for a proper coloring
+    pub fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -(self.conflicts(&vec[0]) as f64),
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+}
+///greedy repair: colors out of range are wrapped, then every vertex in conflict takes the color
+/// shared with the fewest neighbours (the lowest one on ties)
+impl Repair<Vec<usize>> for GraphColoring{
+    fn repair(&self, mut individual:Vec<usize>)->Vec<usize>{
+        individual.iter_mut().for_each(|color| *color %= self.num_colors);
+        let mut counts = vec![0; self.num_colors];
+        for vertex in 0..self.graph.num_vertices {
+            let neighbours = &self.graph.adjacency[vertex];
+            if neighbours.iter().all(|&(neighbour, _)| individual[neighbour] != individual[vertex]) {
+                continue;
+            }
+            counts.iter_mut().for_each(|count| *count = 0);
+            neighbours.iter().for_each(|&(neighbour, _)| counts[individual[neighbour]] += 1);
+            individual[vertex] = (0..self.num_colors).min_by_key(|&color| counts[color]).unwrap();
+        }
+        individual
+    }
+}
+
+///maximum cut: split the vertices in two sides (bit i is the side of vertex i) maximizing the weight of the edges across
+#[derive(Clone, Debug)]
+pub struct MaxCut{
+    pub graph:Graph,
+}
+impl MaxCut{
+    pub fn new(graph:Graph) -> Self{
+        MaxCut{graph}
+    }
+    ///one flip local search: move vertices to the other side while it increases the cut
+    pub fn improve_bits(&self, bits:&mut [bool]){
+        let gain = |vertex:usize, bits:&[bool]| self.graph.adjacency[vertex].iter()
+            .map(|&(neighbour, weight)| if bits[neighbour] == bits[vertex] {weight} else {-weight})
+            .sum::<f64>();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for vertex in 0..bits.len() {
+                if gain(vertex, bits) > 0.0 {
+                    bits[vertex] = !bits[vertex];
+                    improved = true;
+                }
+            }
+        }
+    }
+}
+impl BinaryProblem for MaxCut{
+    ///weight of the cut
+    fn value(&self, bits:&[bool])->f64{
+        self.graph.edges.iter().filter(|&&(u, v, _)| bits[u] != bits[v]).map(|&(_, _, weight)| weight).sum()
+    }
+    fn num_bits(&self)->usize{
+        self.graph.num_vertices
+    }
+    ///exhaustive search, only for up to 20 vertices
+    fn optimum(&self)->Option<f64>{
+        let n = self.num_bits();
+        if n > 20 {
+            return None;
+        }
+        //the first vertex stays on one side, as both sides are symmetric
+        (0..1usize << n.saturating_sub(1))
+            .map(|string| self.value(&(0..n).map(|i| (string << 1) >> i & 1 == 1).collect::<Vec<bool>>()))
+            .reduce(f64::max)
+    }
+}
+///local search repair (see improve_bits), for Usize bits
+impl Repair<Vec<usize>> for MaxCut{
+    fn repair(&self, individual:Vec<usize>)->Vec<usize>{
+        let mut bits:Vec<bool> = individual.iter().map(|&gene| gene != 0).collect();
+        self.improve_bits(&mut bits);
+        bits.into_iter().map(usize::from).collect()
+    }
+}
+///local search repair (see improve_bits), for F64 genes thresholded at 0.5: flipped genes are mirrored around 0.5
+impl Repair<Vec<f64>> for MaxCut{
+    fn repair(&self, mut individual:Vec<f64>)->Vec<f64>{
+        let mut bits:Vec<bool> = individual.iter().map(|&gene| gene > 0.5).collect();
+        self.improve_bits(&mut bits);
+        for (gene, bit) in individual.iter_mut().zip(bits) {
+            if bit != (*gene > 0.5) {
+                *gene = 1.0 - *gene;
+            }
+        }
+        individual
+    }
+}
+
+///balanced k-way partitioning: gene i is the part of vertex i, minimize the weight of the edges between parts
+/// with at most max_part_size vertices per part<BR>
+/// overloaded partitions score below every balanced one
+#[derive(Clone, Debug)]
+pub struct Partitioning{
+    pub graph:Graph,
+    pub num_parts:usize,
+    pub max_part_size:usize,
+}
+impl Partitioning{
+    ///parts may exceed the average size by the imbalance ratio (0.0 for perfectly balanced parts)
+    pub fn new(graph:Graph, num_parts:usize, imbalance:f64) -> Self{
+        let max_part_size = ((1.0 + imbalance) * graph.num_vertices as f64 / num_parts as f64).ceil() as usize;
+        Partitioning{graph, num_parts, max_part_size}
+    }
+    pub fn part_sizes(&self, parts:&[usize])->Vec<usize>{
+        let mut sizes = vec![0; self.num_parts];
+        parts.iter().for_each(|&part| sizes[part] += 1);
+        sizes
+    }
+    ///weight of the edges between different parts
+    pub fn cut(&self, parts:&[usize])->f64{
+        self.graph.edges.iter().filter(|&&(u, v, _)| parts[u] != parts[v]).map(|&(_, _, weight)| weight).sum()
+    }
+    ///number of vertices above max_part_size over all parts
+    pub fn overload(&self, parts:&[usize])->usize{
+        self.part_sizes(parts).iter().map(|&size| size.saturating_sub(self.max_part_size)).sum()
+    }
+    ///balanced random partitions, kept balanced by the built-in Usize crossover and mutation
+    /// (with Config::num_genes = number of vertices)
+    pub fn initialization(&self)->InitializationStrategy{
+        let n = self.graph.num_vertices;
+        let repetitions = (0..self.num_parts).map(|part| n / self.num_parts + usize::from(part < n % self.num_parts)).collect();
+        InitializationStrategy::Usize(Box::new(PermutationWithRepetition{repetitions}))
+    }
+    ///operators for GA::with_variation, which move vertices between parts, to be combined with the repair
+    pub fn variation(&self)->VariationStrategy{
+        VariationStrategy::Usize(Box::new(IntegerVariation{num_values:self.num_parts}))
+    }
+    ///fitness function for GA::new: minus the cut, minus a penalty per overloaded vertex larger than any cut
+    pub fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        let penalty = 1.0 + self.graph.edges.iter().map(|&(_, _, weight)| weight.abs()).sum::<f64>();
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -self.cut(&vec[0]) - penalty * self.overload(&vec[0]) as f64,
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+}
+///greedy repair: parts out of range are wrapped, then vertices of overloaded parts move to parts with room,
+/// choosing each time the move that increases the cut the least
+impl Repair<Vec<usize>> for Partitioning{
+    fn repair(&self, mut individual:Vec<usize>)->Vec<usize>{
+        individual.iter_mut().for_each(|part| *part %= self.num_parts);
+        let mut sizes = self.part_sizes(&individual);
+        let mut weights = vec![0.0; self.num_parts];
+        while let Some(from) = (0..self.num_parts).find(|&part| sizes[part] > self.max_part_size) {
+            let mut best:Option<(f64,usize,usize)> = None;
+            for vertex in (0..individual.len()).filter(|&vertex| individual[vertex] == from) {
+                weights.iter_mut().for_each(|weight| *weight = 0.0);
+                self.graph.adjacency[vertex].iter().for_each(|&(neighbour, weight)| weights[individual[neighbour]] += weight);
+                for to in (0..self.num_parts).filter(|&to| sizes[to] < self.max_part_size) {
+                    let increase = weights[from] - weights[to];
+                    if best.map_or(true, |(best, _, _)| increase < best) {
+                        best = Some((increase, vertex, to));
+                    }
+                }
+            }
+            let (_, vertex, to) = best.expect("max_part_size too small to hold every vertex");
+            individual[vertex] = to;
+            sizes[from] -= 1;
+            sizes[to] += 1;
+        }
+        individual
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    ///cycle of n vertices 0-1-...-(n-1)-0
+    fn cycle(n:usize)->Graph{
+        Graph::new(n, (0..n).map(|u| (u, (u + 1) % n, 1.0)).collect())
+    }
+
+    #[test]
+    fn dimacs_graph_is_parsed(){
+        let content = "c myciel3 excerpt\np edge 4 5\ne 1 2\ne 2 1\ne 2 3\n\ne 3 4\ne 4 4\nc end\ne 1 4\n";
+        let graph = Graph::parse_dimacs(content).unwrap();
+        assert_eq!(graph.num_vertices, 4);
+        //the reversed duplicate and the self loop are dropped
+        assert_eq!(graph.edges, vec![(0, 1, 1.0), (1, 2, 1.0), (2, 3, 1.0), (0, 3, 1.0)]);
+        assert_eq!((graph.degree(0), graph.degree(1)), (2, 2));
+        for content in ["e 1 2\np edge 2 1\n", "p edge 2 1\ne 1 3\n", "p edge 2 1\ne 0 1\n", "p edge 2 1\nx 1 2\n", "p edge 2 1\ne 1 b\n", "c empty\n"] {
+            assert_eq!(Graph::parse_dimacs(content).unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", content);
+        }
+    }
+
+    #[test]
+    fn edge_list_is_parsed(){
+        let graph = Graph::parse_edge_list("# weighted\n0 1 2.5\n% comment\n1 3\n").unwrap();
+        assert_eq!(graph.num_vertices, 4);
+        assert_eq!(graph.edges, vec![(0, 1, 2.5), (1, 3, 1.0)]);
+        assert!(Graph::parse_edge_list("0 1 2 3\n").is_err());
+        assert!(Graph::parse_edge_list("0 -1\n").is_err());
+    }
+
+    #[test]
+    fn dsatur_colors_properly(){
+        let coloring = |graph:&Graph| {
+            let colors = graph.dsatur();
+            assert_eq!(GraphColoring::new(graph.clone(), graph.num_vertices).conflicts(&colors), 0);
+            colors.into_iter().max().map_or(0, |color| color + 1)
+        };
+        assert_eq!(coloring(&cycle(6)), 2);
+        assert_eq!(coloring(&cycle(5)), 3);
+        let complete = Graph::new(5, (0..5).flat_map(|u| (u + 1..5).map(move |v| (u, v, 1.0))).collect());
+        assert_eq!(coloring(&complete), 5);
+        for _ in 0..10 {
+            coloring(&Graph::random(30, 0.3));
+        }
+    }
+
+    #[test]
+    fn coloring_repair_never_adds_conflicts(){
+        let mut rng = rand::thread_rng();
+        let star = GraphColoring::new(Graph::new(4, vec![(0, 1, 1.0), (0, 2, 1.0), (0, 3, 1.0)]), 2);
+        assert_eq!(star.repair(vec![1, 1, 3, 1]), vec![0, 1, 1, 1]);
+        for _ in 0..20 {
+            let problem = GraphColoring::new(Graph::random(30, 0.2), 4);
+            let colors:Vec<usize> = (0..30).map(|_| rng.gen_range(0..4)).collect();
+            let repaired = problem.repair(colors.clone());
+            assert!(problem.conflicts(&repaired) <= problem.conflicts(&colors));
+        }
+    }
+
+    #[test]
+    fn max_cut_repair_reaches_a_local_optimum(){
+        let problem = MaxCut::new(cycle(6));
+        assert_eq!(problem.optimum(), Some(6.0));
+        let complete = MaxCut::new(Graph::new(4, (0..4).flat_map(|u| (u + 1..4).map(move |v| (u, v, 1.0))).collect()));
+        assert_eq!(complete.optimum(), Some(4.0));
+        let repaired:Vec<usize> = complete.repair(vec![0, 0, 0, 0]);
+        assert_eq!(complete.value(&repaired.iter().map(|&gene| gene != 0).collect::<Vec<bool>>()), 4.0);
+        let repaired:Vec<f64> = complete.repair(vec![0.1, 0.2, 0.3, 0.4]);
+        assert_eq!(repaired.iter().filter(|&&gene| gene > 0.5).count(), 2);
+    }
+
+    #[test]
+    fn partitioning_repair_balances_parts(){
+        let problem = Partitioning::new(cycle(12), 3, 0.0);
+        assert_eq!(problem.max_part_size, 4);
+        let repaired = problem.repair(vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 5, 2, 2]);
+        assert_eq!(problem.overload(&repaired), 0);
+        assert_eq!(problem.part_sizes(&repaired), vec![4, 4, 4]);
+        assert_eq!(problem.cut(&[0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2]), 3.0);
+    }
+}
@@ -0,0 +1,417 @@
+use std::f64::consts::PI;
+use std::ops::Range;
+use crate::population::Population;
+
+///multi-objective test problems, all objectives are minimized<BR>
+/// ZDT problems have 2 objectives, DTLZ and WFG problems any number M >= 2
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MOBenchmark{
+    ///convex front f2 = 1 - √f1
+    ZDT1,
+    ///concave front f2 = 1 - f1²
+    ZDT2,
+    ///disconnected front
+    ZDT3,
+    ///ZDT1 front with 21^9 local fronts (variables other than the first lie in [-5,5])
+    ZDT4,
+    ///deceptive binary problem: genes are bits (a gene > 0.5 is a 1), 30 bits for the first variable and 5 for each other one
+    ZDT5,
+    ///non-uniform concave front f2 = 1 - f1², solutions are biased towards f1 = 1
+    ZDT6,
+    ///linear front (sum of objectives = 0.5) with 11^k - 1 local fronts
+    DTLZ1,
+    ///spherical front
+    DTLZ2,
+    ///spherical front with 3^k - 1 local fronts
+    DTLZ3,
+    ///spherical front, solutions are biased towards the f_M axis
+    DTLZ4,
+    ///degenerate front: a curve on the unit sphere
+    DTLZ5,
+    ///DTLZ5 front, harder to converge to
+    DTLZ6,
+    ///2^(M-1) disconnected regions
+    DTLZ7,
+    ///concave front, multimodal
+    WFG4,
+    ///concave front, deceptive
+    WFG5,
+    ///concave front, non-separable
+    WFG6,
+    ///concave front, position parameters biased by the distance parameters
+    WFG7,
+}
+
+///instance of a multi-objective benchmark
+#[derive(Clone, Debug)]
+pub struct MultiObjective{
+    pub benchmark:MOBenchmark,
+    pub num_objectives:usize,
+    ///number of decision variables (genes)
+    pub num_variables:usize,
+    ///number of position parameters of WFG problems (multiple of num_objectives - 1), the others are distance parameters
+    pub position_parameters:usize,
+}
+impl MultiObjective{
+    ///instance with the sizes recommended in the literature:<BR>
+    /// ZDT1-3: 30 variables, ZDT4 and ZDT6: 10, ZDT5: 80 bits<BR>
+    /// DTLZ: M + k - 1 variables with k = 5 (DTLZ1), 20 (DTLZ7) or 10<BR>
+    /// WFG: 2(M-1) position and 20 distance parameters
+    pub fn new(benchmark:MOBenchmark, num_objectives:usize) -> Self{
+        use MOBenchmark::*;
+        let num_objectives = match benchmark {
+            ZDT1 | ZDT2 | ZDT3 | ZDT4 | ZDT5 | ZDT6 => 2,
+            _ => num_objectives,
+        };
+        assert!(num_objectives >= 2, "Multi-objective problems need at least 2 objectives");
+        let position_parameters = 2 * (num_objectives - 1);
+        let num_variables = match benchmark {
+            ZDT1 | ZDT2 | ZDT3 => 30,
+            ZDT4 | ZDT6 => 10,
+            ZDT5 => 80,
+            DTLZ1 => num_objectives + 4,
+            DTLZ7 => num_objectives + 19,
+            DTLZ2 | DTLZ3 | DTLZ4 | DTLZ5 | DTLZ6 => num_objectives + 9,
+            WFG4 | WFG5 | WFG6 | WFG7 => position_parameters + 20,
+        };
+        MultiObjective{benchmark, num_objectives, num_variables, position_parameters}
+    }
+    ///range of each decision variable
+    pub fn bounds(&self)->Vec<Range<f64>>{
+        (0..self.num_variables).map(|i| match self.benchmark {
+            MOBenchmark::ZDT4 if i > 0 => -5.0..5.0,
+            MOBenchmark::WFG4 | MOBenchmark::WFG5 | MOBenchmark::WFG6 | MOBenchmark::WFG7 => 0.0..2.0 * (i + 1) as f64,
+            _ => 0.0..1.0,
+        }).collect()
+    }
+    ///map genes from [0,1] to the bounds of each variable
+    pub fn scale(&self, genes:&[f64])->Vec<f64>{
+        genes.iter().zip(self.bounds()).map(|(gene, range)| range.start + gene * (range.end - range.start)).collect()
+    }
+    ///objectives of a vector of decision variables
+    pub fn evaluate(&self, x:&[f64])->Vec<f64>{
+        assert_eq!(x.len(), self.num_variables, "Wrong number of decision variables");
+        let m = self.num_objectives;
+        match self.benchmark {
+            MOBenchmark::ZDT1 | MOBenchmark::ZDT2 | MOBenchmark::ZDT3 | MOBenchmark::ZDT4 => {
+                let rest = &x[1..];
+                let g = match self.benchmark {
+                    MOBenchmark::ZDT4 => 1.0 + 10.0 * rest.len() as f64 + rest.iter().map(|x| x * x - 10.0 * (4.0 * PI * x).cos()).sum::<f64>(),
+                    _ => 1.0 + 9.0 * rest.iter().sum::<f64>() / rest.len() as f64,
+                };
+                let ratio = x[0] / g;
+                let h = match self.benchmark {
+                    MOBenchmark::ZDT2 => 1.0 - ratio * ratio,
+                    MOBenchmark::ZDT3 => 1.0 - ratio.sqrt() - ratio * (10.0 * PI * x[0]).sin(),
+                    _ => 1.0 - ratio.sqrt(),
+                };
+                vec![x[0], g * h]
+            }
+            MOBenchmark::ZDT5 => {
+                let ones = |bits:&[f64]| bits.iter().filter(|&&bit| bit > 0.5).count();
+                let f1 = 1.0 + ones(&x[..30]) as f64;
+                let g:f64 = x[30..].chunks(5).map(|bits| match ones(bits) {
+                    5 => 1.0,
+                    u => 2.0 + u as f64,
+                }).sum();
+                vec![f1, g / f1]
+            }
+            MOBenchmark::ZDT6 => {
+                let f1 = 1.0 - (-4.0 * x[0]).exp() * (6.0 * PI * x[0]).sin().powi(6);
+                let g = 1.0 + 9.0 * (x[1..].iter().sum::<f64>() / (x.len() - 1) as f64).powf(0.25);
+                vec![f1, g * (1.0 - (f1 / g).powi(2))]
+            }
+            MOBenchmark::DTLZ1 | MOBenchmark::DTLZ3 => {
+                let (position, distance) = x.split_at(m - 1);
+                let g = 100.0 * (distance.len() as f64
+                    + distance.iter().map(|x| (x - 0.5).powi(2) - (20.0 * PI * (x - 0.5)).cos()).sum::<f64>());
+                match self.benchmark {
+                    MOBenchmark::DTLZ1 => linear(position).into_iter().map(|f| 0.5 * f * (1.0 + g)).collect(),
+                    _ => concave(&angles(position, |x| x)).into_iter().map(|f| f * (1.0 + g)).collect(),
+                }
+            }
+            MOBenchmark::DTLZ2 | MOBenchmark::DTLZ4 => {
+                let (position, distance) = x.split_at(m - 1);
+                let g:f64 = distance.iter().map(|x| (x - 0.5).powi(2)).sum();
+                let alpha = if self.benchmark == MOBenchmark::DTLZ4 {100.0} else {1.0};
+                concave(&angles(position, |x| x.powf(alpha))).into_iter().map(|f| f * (1.0 + g)).collect()
+            }
+            MOBenchmark::DTLZ5 | MOBenchmark::DTLZ6 => {
+                let (position, distance) = x.split_at(m - 1);
+                let g:f64 = match self.benchmark {
+                    MOBenchmark::DTLZ5 => distance.iter().map(|x| (x - 0.5).powi(2)).sum(),
+                    _ => distance.iter().map(|x| x.powf(0.1)).sum(),
+                };
+                let theta:Vec<f64> = position.iter().enumerate().map(|(i, x)| {
+                    if i == 0 {x * PI / 2.0} else {PI / (4.0 * (1.0 + g)) * (1.0 + 2.0 * g * x)}
+                }).collect();
+                concave(&theta).into_iter().map(|f| f * (1.0 + g)).collect()
+            }
+            MOBenchmark::DTLZ7 => {
+                let (position, distance) = x.split_at(m - 1);
+                let g = 1.0 + 9.0 * distance.iter().sum::<f64>() / distance.len() as f64;
+                let h = m as f64 - position.iter().map(|f| f / (1.0 + g) * (1.0 + (3.0 * PI * f).sin())).sum::<f64>();
+                position.iter().cloned().chain([(1.0 + g) * h]).collect()
+            }
+            MOBenchmark::WFG4 | MOBenchmark::WFG5 | MOBenchmark::WFG6 | MOBenchmark::WFG7 => self.wfg(x),
+        }
+    }
+    ///WFG toolkit: transitions of the normalized variables followed by the concave shape scaled by 2m
+    fn wfg(&self, z:&[f64])->Vec<f64>{
+        let (m, k) = (self.num_objectives, self.position_parameters);
+        assert!(k % (m - 1) == 0 && k < z.len(), "WFG position parameters must be a multiple of num_objectives - 1");
+        let mut y:Vec<f64> = z.iter().enumerate().map(|(i, z)| (z / (2.0 * (i + 1) as f64)).clamp(0.0, 1.0)).collect();
+        match self.benchmark {
+            MOBenchmark::WFG4 => y.iter_mut().for_each(|y| *y = s_multi(*y, 30.0, 10.0, 0.35)),
+            MOBenchmark::WFG5 => y.iter_mut().for_each(|y| *y = s_decept(*y, 0.35, 0.001, 0.05)),
+            MOBenchmark::WFG6 => y[k..].iter_mut().for_each(|y| *y = s_linear(*y, 0.35)),
+            _ => {
+                let biased:Vec<f64> = (0..k).map(|i| {
+                    let u = y[i + 1..].iter().sum::<f64>() / (y.len() - i - 1) as f64;
+                    b_param(y[i], u, 0.98 / 49.98, 0.02, 50.0)
+                }).collect();
+                y[..k].copy_from_slice(&biased);
+                y[k..].iter_mut().for_each(|y| *y = s_linear(*y, 0.35));
+            }
+        }
+        let group = k / (m - 1);
+        let mut t:Vec<f64> = y[..k].chunks(group).map(|chunk| match self.benchmark {
+            MOBenchmark::WFG6 => r_nonsep(chunk, group),
+            _ => chunk.iter().sum::<f64>() / chunk.len() as f64,
+        }).collect();
+        t.push(match self.benchmark {
+            MOBenchmark::WFG6 => r_nonsep(&y[k..], y.len() - k),
+            _ => y[k..].iter().sum::<f64>() / (y.len() - k) as f64,
+        });
+        let distance = t[m - 1];
+        let theta:Vec<f64> = t[..m - 1].iter().map(|x| x * PI / 2.0).collect();
+        concave(&theta).into_iter().enumerate().map(|(i, h)| distance + 2.0 * (i + 1) as f64 * h).collect()
+    }
+    ///objectives function for genes in [0,1] (scaled to the bounds, see scale), e.g. with Config::range = 0.0..1.0
+    pub fn objectives(self)->Box<dyn Fn(Population)->Vec<f64> + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::F64(vec) => self.evaluate(&self.scale(&vec[0])),
+            _ => panic!("Expected Population::F64"),
+        })
+    }
+    ///about num_points points of the true Pareto front<BR>
+    /// fronts of 3 or more objectives are sampled on a Das-Dennis lattice, disconnected fronts are filtered to their non-dominated points
+    pub fn pareto_front(&self, num_points:usize)->Vec<Vec<f64>>{
+        let m = self.num_objectives;
+        let line = |n:usize| (0..n).map(move |i| if n > 1 {i as f64 / (n - 1) as f64} else {0.0});
+        match self.benchmark {
+            MOBenchmark::ZDT1 | MOBenchmark::ZDT4 => line(num_points).map(|f1| vec![f1, 1.0 - f1.sqrt()]).collect(),
+            MOBenchmark::ZDT2 => line(num_points).map(|f1| vec![f1, 1.0 - f1 * f1]).collect(),
+            MOBenchmark::ZDT3 => {
+                let points:Vec<Vec<f64>> = line(num_points.max(2) * 10).map(|f1| vec![f1, 1.0 - f1.sqrt() - f1 * (10.0 * PI * f1).sin()]).collect();
+                thin(non_dominated(&points), num_points)
+            }
+            MOBenchmark::ZDT5 => (0..=30).map(|u| {
+                let f1 = 1.0 + u as f64;
+                vec![f1, (self.num_variables - 30) as f64 / 5.0 / f1]
+            }).collect(),
+            MOBenchmark::ZDT6 => {
+                let min = 0.2807753191;
+                line(num_points).map(|t| min + t * (1.0 - min)).map(|f1| vec![f1, 1.0 - f1 * f1]).collect()
+            }
+            MOBenchmark::DTLZ1 => simplex_lattice(m, num_points).into_iter().map(|point| point.into_iter().map(|f| 0.5 * f).collect()).collect(),
+            MOBenchmark::DTLZ2 | MOBenchmark::DTLZ3 | MOBenchmark::DTLZ4 => simplex_lattice(m, num_points).into_iter().map(normalize).collect(),
+            MOBenchmark::DTLZ5 | MOBenchmark::DTLZ6 => line(num_points).map(|x| {
+                let theta:Vec<f64> = (0..m - 1).map(|i| if i == 0 {x * PI / 2.0} else {PI / 4.0}).collect();
+                concave(&theta)
+            }).collect(),
+            MOBenchmark::DTLZ7 => {
+                //grid over the first M-1 objectives, the last one is the one of a solution with g = 1
+                let resolution = ((num_points * 10) as f64).powf(1.0 / (m - 1) as f64).ceil().max(2.0) as usize;
+                let points:Vec<Vec<f64>> = (0..resolution.pow((m - 1) as u32)).map(|index| {
+                    let position:Vec<f64> = (0..m - 1).map(|i| (index / resolution.pow(i as u32) % resolution) as f64 / (resolution - 1) as f64).collect();
+                    let h = m as f64 - position.iter().map(|f| f / 2.0 * (1.0 + (3.0 * PI * f).sin())).sum::<f64>();
+                    position.iter().cloned().chain([2.0 * h]).collect()
+                }).collect();
+                thin(non_dominated(&points), num_points)
+            }
+            MOBenchmark::WFG4 | MOBenchmark::WFG5 | MOBenchmark::WFG6 | MOBenchmark::WFG7 => simplex_lattice(m, num_points).into_iter()
+                .map(|point| normalize(point).into_iter().enumerate().map(|(i, f)| 2.0 * (i + 1) as f64 * f).collect())
+                .collect(),
+        }
+    }
+}
+
+///linear shape of DTLZ1 (before scaling by 0.5(1 + g)), the objectives sum to 1
+fn linear(x:&[f64])->Vec<f64>{
+    let m = x.len() + 1;
+    (0..m).map(|i| {
+        let product:f64 = x[..m - 1 - i].iter().product();
+        if i == 0 {product} else {product * (1.0 - x[m - 1 - i])}
+    }).collect()
+}
+///angles of the DTLZ2 shape for position variables in [0,1]
+fn angles(x:&[f64], map:impl Fn(f64)->f64)->Vec<f64>{
+    x.iter().map(|&x| map(x) * PI / 2.0).collect()
+}
+///concave (spherical) shape: f1 = sin θ1 ... sin θ(M-1), f_M = cos θ1 (sum of squares is 1)
+fn concave(theta:&[f64])->Vec<f64>{
+    let m = theta.len() + 1;
+    (0..m).map(|i| {
+        let product:f64 = theta[..m - 1 - i].iter().map(|t| t.sin()).product();
+        if i == 0 {product} else {product * theta[m - 1 - i].cos()}
+    }).collect()
+}
+///keep at most num_points evenly spaced points
+fn thin(points:Vec<Vec<f64>>, num_points:usize)->Vec<Vec<f64>>{
+    let step = points.len().div_ceil(num_points.max(1)).max(1);
+    points.into_iter().step_by(step).collect()
+}
+fn normalize(point:Vec<f64>)->Vec<f64>{
+    let norm = point.iter().map(|f| f * f).sum::<f64>().sqrt();
+    point.into_iter().map(|f| f / norm).collect()
+}
+
+fn s_linear(y:f64, a:f64)->f64{
+    ((y - a).abs() / ((a - y).floor() + a).abs()).clamp(0.0, 1.0)
+}
+fn s_multi(y:f64, a:f64, b:f64, c:f64)->f64{
+    let r = (y - c).abs() / (2.0 * ((c - y).floor() + c));
+    ((1.0 + ((4.0 * a + 2.0) * PI * (0.5 - r)).cos() + 4.0 * b * r * r) / (b + 2.0)).clamp(0.0, 1.0)
+}
+fn s_decept(y:f64, a:f64, b:f64, c:f64)->f64{
+    let first = (y - a + b).floor() * (1.0 - c + (a - b) / b) / (a - b);
+    let second = (a + b - y).floor() * (1.0 - c + (1.0 - a - b) / b) / (1.0 - a - b);
+    (1.0 + ((y - a).abs() - b) * (first + second + 1.0 / b)).clamp(0.0, 1.0)
+}
+fn b_param(y:f64, u:f64, a:f64, b:f64, c:f64)->f64{
+    let v = a - (1.0 - 2.0 * u) * ((0.5 - u).floor() + a).abs();
+    y.powf(b + (c - b) * v).clamp(0.0, 1.0)
+}
+fn r_nonsep(y:&[f64], a:usize)->f64{
+    let n = y.len();
+    let sum:f64 = (0..n).map(|j| y[j] + (0..a - 1).map(|k| (y[j] - y[(1 + j + k) % n]).abs()).sum::<f64>()).sum();
+    let half = a.div_ceil(2) as f64;
+    (sum / (n as f64 / a as f64 * half * (1.0 + 2.0 * a as f64 - 2.0 * half))).clamp(0.0, 1.0)
+}
+
+///Das-Dennis points on the unit simplex: all vectors of m multiples of 1/divisions summing to 1
+pub fn das_dennis(m:usize, divisions:usize)->Vec<Vec<f64>>{
+    fn fill(point:&mut Vec<usize>, left:usize, m:usize, points:&mut Vec<Vec<usize>>){
+        if point.len() == m - 1 {
+            point.push(left);
+            points.push(point.clone());
+            point.pop();
+            return;
+        }
+        for i in 0..=left {
+            point.push(i);
+            fill(point, left - i, m, points);
+            point.pop();
+        }
+    }
+    let mut points = Vec::new();
+    fill(&mut Vec::with_capacity(m), divisions, m, &mut points);
+    points.into_iter().map(|point| point.into_iter().map(|i| i as f64 / divisions.max(1) as f64).collect()).collect()
+}
+///Das-Dennis lattice with the largest number of divisions giving at most num_points points (at least 1 division)
+fn simplex_lattice(m:usize, num_points:usize)->Vec<Vec<f64>>{
+    let count = |divisions:usize| (1..m).fold(1.0, |count, i| count * (divisions + i) as f64 / i as f64);
+    let mut divisions = 1;
+    while count(divisions + 1) <= num_points as f64 {
+        divisions += 1;
+    }
+    das_dennis(m, divisions)
+}
+
+///true if a is no worse than b in every objective and better in at least one (minimization)
+pub fn dominates(a:&[f64], b:&[f64])->bool{
+    a.iter().zip(b).all(|(a, b)| a <= b) && a.iter().zip(b).any(|(a, b)| a < b)
+}
+///points not dominated by any other point
+pub fn non_dominated(points:&[Vec<f64>])->Vec<Vec<f64>>{
+    points.iter().filter(|point| !points.iter().any(|other| dominates(other, point))).cloned().collect()
+}
+///inverted generational distance: average distance from each point of the reference front to the closest point of the approximation<BR>
+/// lower is better, 0 when the approximation covers the reference front
+pub fn igd(approximation:&[Vec<f64>], reference:&[Vec<f64>])->f64{
+    let distance = |a:&Vec<f64>, b:&Vec<f64>| a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt();
+    reference.iter()
+        .map(|point| approximation.iter().map(|other| distance(point, other)).fold(f64::INFINITY, f64::min))
+        .sum::<f64>() / reference.len() as f64
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use rand::Rng;
+
+    ///decision variables of a random Pareto-optimal solution: free position variables and distance variables at their optimum
+    fn pareto_optimal(problem:&MultiObjective, position:usize, distance:f64)->Vec<f64>{
+        let mut rng = rand::thread_rng();
+        let genes:Vec<f64> = (0..problem.num_variables).map(|i| if i < position {rng.gen_range(0.0..1.0)} else {distance}).collect();
+        problem.scale(&genes)
+    }
+
+    #[test]
+    fn zdt_optima_lie_on_the_front(){
+        for (benchmark, front) in [(MOBenchmark::ZDT1, (|f1:f64| 1.0 - f1.sqrt()) as fn(f64)->f64), (MOBenchmark::ZDT2, |f1| 1.0 - f1 * f1),
+            (MOBenchmark::ZDT3, |f1| 1.0 - f1.sqrt() - f1 * (10.0 * PI * f1).sin()), (MOBenchmark::ZDT6, |f1| 1.0 - f1 * f1)] {
+            let problem = MultiObjective::new(benchmark, 2);
+            for _ in 0..100 {
+                let f = problem.evaluate(&pareto_optimal(&problem, 1, 0.0));
+                assert!((f[1] - front(f[0])).abs() < 1e-9, "{:?}: {:?}", benchmark, f);
+            }
+        }
+        //variables other than the first are optimal at 0, the middle of [-5,5]
+        let problem = MultiObjective::new(MOBenchmark::ZDT4, 2);
+        let f = problem.evaluate(&pareto_optimal(&problem, 1, 0.5));
+        assert!((f[1] - (1.0 - f[0].sqrt())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dtlz_and_wfg_optima_lie_on_the_front(){
+        for m in [2, 3, 5] {
+            for (benchmark, distance) in [(MOBenchmark::DTLZ1, 0.5), (MOBenchmark::DTLZ2, 0.5), (MOBenchmark::DTLZ3, 0.5), (MOBenchmark::DTLZ4, 0.5),
+                (MOBenchmark::DTLZ5, 0.5), (MOBenchmark::DTLZ6, 0.0), (MOBenchmark::WFG4, 0.35), (MOBenchmark::WFG5, 0.35),
+                (MOBenchmark::WFG6, 0.35), (MOBenchmark::WFG7, 0.35)] {
+                let problem = MultiObjective::new(benchmark, m);
+                let position = match benchmark {
+                    MOBenchmark::WFG4 | MOBenchmark::WFG5 | MOBenchmark::WFG6 | MOBenchmark::WFG7 => problem.position_parameters,
+                    _ => m - 1,
+                };
+                for _ in 0..20 {
+                    let f = problem.evaluate(&pareto_optimal(&problem, position, distance));
+                    let deviation = match benchmark {
+                        MOBenchmark::DTLZ1 => f.iter().sum::<f64>() - 0.5,
+                        MOBenchmark::WFG4 | MOBenchmark::WFG5 | MOBenchmark::WFG6 | MOBenchmark::WFG7 =>
+                            f.iter().enumerate().map(|(i, f)| (f / (2.0 * (i + 1) as f64)).powi(2)).sum::<f64>() - 1.0,
+                        _ => f.iter().map(|f| f * f).sum::<f64>() - 1.0,
+                    };
+                    assert!(deviation.abs() < 1e-9, "{:?} with {} objectives: {:?}", benchmark, m, f);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sampled_fronts_are_non_dominated(){
+        for benchmark in [MOBenchmark::ZDT1, MOBenchmark::ZDT2, MOBenchmark::ZDT3, MOBenchmark::ZDT6, MOBenchmark::DTLZ1, MOBenchmark::DTLZ2, MOBenchmark::DTLZ7, MOBenchmark::WFG4] {
+            let front = MultiObjective::new(benchmark, 3).pareto_front(100);
+            assert!(!front.is_empty() && front.len() <= 100, "{:?}: {} points", benchmark, front.len());
+            assert_eq!(non_dominated(&front).len(), front.len(), "{:?}", benchmark);
+        }
+    }
+
+    #[test]
+    fn das_dennis_points_cover_the_simplex(){
+        let points = das_dennis(3, 4);
+        assert_eq!(points.len(), 15);
+        assert!(points.iter().all(|point| (point.iter().sum::<f64>() - 1.0).abs() < 1e-12));
+        assert_eq!(simplex_lattice(3, 20), das_dennis(3, 4));
+    }
+
+    #[test]
+    fn igd_is_zero_on_the_reference_front(){
+        let front = MultiObjective::new(MOBenchmark::ZDT1, 2).pareto_front(50);
+        assert_eq!(igd(&front, &front), 0.0);
+        let shifted:Vec<Vec<f64>> = front.iter().map(|point| point.iter().map(|f| f + 0.1).collect()).collect();
+        assert!((igd(&shifted, &front) - 0.1 * 2f64.sqrt()).abs() < 0.02);
+        assert!(dominates(&[0.0, 1.0], &[0.0, 2.0]) && !dominates(&[0.0, 1.0], &[0.0, 1.0]));
+    }
+}
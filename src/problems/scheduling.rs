@@ -0,0 +1,476 @@
+use std::fs;
+use std::io;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use crate::plot::draw_gantt;
+use crate::population::{Config, Initialization, InitializationStrategy, Population};
+use crate::problems::tsp::invalid;
+
+///objective minimized by a schedule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective{
+    ///completion time of the last operation
+    Makespan,
+    ///sum over jobs of the time by which they complete after their due date
+    TotalTardiness,
+    ///sum of the completion times of the jobs
+    TotalFlowTime,
+}
+
+///how a job-shop genome is turned into a schedule
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduleBuilder{
+    ///operations are appended in genome order at the earliest time allowed by their job and machine
+    SemiActive,
+    ///Giffler-Thompson: among the operations that could start before the earliest completion time of the
+    /// next operations, the first one in the genome is scheduled, so that no operation can start earlier without delaying another one
+    Active,
+}
+
+///operation placed in a schedule
+#[derive(Clone, Copy, Debug)]
+pub struct ScheduledOperation{
+    pub job:usize,
+    ///position of the operation in its job
+    pub index:usize,
+    pub machine:usize,
+    pub start:f64,
+    pub end:f64,
+}
+///start and end time of every operation
+#[derive(Clone, Debug)]
+pub struct Schedule{
+    pub operations:Vec<ScheduledOperation>,
+    pub num_jobs:usize,
+    pub num_machines:usize,
+}
+impl Schedule{
+    pub fn makespan(&self)->f64{
+        self.operations.iter().map(|operation| operation.end).fold(0.0, f64::max)
+    }
+    ///completion time of each job
+    pub fn completion_times(&self)->Vec<f64>{
+        let mut completion = vec![0.0; self.num_jobs];
+        for operation in &self.operations {
+            completion[operation.job] = f64::max(completion[operation.job], operation.end);
+        }
+        completion
+    }
+    ///value of an objective (due_dates are only needed for the total tardiness)
+    pub fn objective(&self, objective:Objective, due_dates:Option<&[f64]>)->f64{
+        match objective {
+            Objective::Makespan => self.makespan(),
+            Objective::TotalFlowTime => self.completion_times().iter().sum(),
+            Objective::TotalTardiness => {
+                let due_dates = due_dates.expect("Due dates are needed to compute tardiness");
+                self.completion_times().iter().zip(due_dates).map(|(completion, due)| (completion - due).max(0.0)).sum()
+            }
+        }
+    }
+    ///bars (start, end, job) of each machine, for draw_gantt
+    pub fn gantt(&self)->Vec<Vec<(f64,f64,usize)>>{
+        let mut rows = vec![Vec::new(); self.num_machines];
+        for operation in &self.operations {
+            rows[operation.machine].push((operation.start, operation.end, operation.job));
+        }
+        rows
+    }
+    ///draw the schedule as a Gantt chart, one row per machine and one color per job
+    pub fn draw(&self, filename:&str){
+        draw_gantt(self.gantt(), filename);
+    }
+}
+
+///permutations with repetition: each gene value i appears repetitions[i] times (e.g. jobs repeated once per operation)<BR>
+/// the built-in Vec<usize> crossover and mutation keep the number of occurrences of every value
+pub struct PermutationWithRepetition{
+    pub repetitions:Vec<usize>,
+}
+impl Initialization<Vec<Vec<usize>>> for PermutationWithRepetition{
+    fn initialize(&self, config: Config) -> Vec<Vec<usize>> {
+        let mut rng = thread_rng();
+        let genes:Vec<usize> = self.repetitions.iter().enumerate().flat_map(|(value, &count)| std::iter::repeat(value).take(count)).collect();
+        (0..config.num_individuals).map(|_| {
+            let mut individual = genes.clone();
+            individual.shuffle(&mut rng);
+            individual
+        }).collect()
+    }
+}
+
+///job-shop scheduling: each job is a sequence of operations (machine, processing time) processed in order,
+/// each machine processes one operation at a time<BR>
+/// genomes are permutations with repetition of the jobs: the k-th occurrence of job j stands for its k-th operation
+#[derive(Clone, Debug)]
+pub struct JobShop{
+    pub name:String,
+    ///operations (machine, processing time) of each job
+    pub jobs:Vec<Vec<(usize,f64)>>,
+    pub num_machines:usize,
+    ///due date of each job (None if tardiness is not used)
+    pub due_dates:Option<Vec<f64>>,
+    ///best known makespan (None if unknown)
+    pub optimum:Option<f64>,
+}
+impl JobShop{
+    pub fn new(jobs:Vec<Vec<(usize,f64)>>) -> Self{
+        let num_machines = jobs.iter().flatten().map(|&(machine, _)| machine + 1).max().unwrap_or(0);
+        JobShop{name:String::new(), jobs, num_machines, due_dates:None, optimum:None}
+    }
+    ///set the due date of each job to factor times its total processing time
+    pub fn with_due_dates(mut self, factor:f64) -> Self{
+        self.due_dates = Some(self.jobs.iter().map(|job| factor * job.iter().map(|&(_, time)| time).sum::<f64>()).collect());
+        self
+    }
+    ///number of genes of the encoding (total number of operations)
+    pub fn num_operations(&self)->usize{
+        self.jobs.iter().map(|job| job.len()).sum()
+    }
+    ///initialization strategy for GA::new (with Config::num_genes = num_operations)
+    pub fn initialization(&self)->InitializationStrategy{
+        InitializationStrategy::Usize(Box::new(PermutationWithRepetition{repetitions:self.jobs.iter().map(|job| job.len()).collect()}))
+    }
+    ///build the schedule encoded by a permutation with repetition of the jobs
+    pub fn schedule(&self, genome:&[usize], builder:ScheduleBuilder)->Schedule{
+        let mut job_ready = vec![0.0; self.jobs.len()];
+        let mut machine_ready = vec![0.0; self.num_machines];
+        let mut next = vec![0; self.jobs.len()];
+        let mut operations = Vec::with_capacity(genome.len());
+        let place = |job:usize, index:usize, operations:&mut Vec<ScheduledOperation>, job_ready:&mut Vec<f64>, machine_ready:&mut Vec<f64>| {
+            let (machine, time) = self.jobs[job][index];
+            let start = f64::max(job_ready[job], machine_ready[machine]);
+            job_ready[job] = start + time;
+            machine_ready[machine] = start + time;
+            operations.push(ScheduledOperation{job, index, machine, start, end:start + time});
+        };
+        match builder {
+            ScheduleBuilder::SemiActive => {
+                for &job in genome {
+                    assert!(next[job] < self.jobs[job].len(), "Job {} appears more often than its number of operations", job);
+                    place(job, next[job], &mut operations, &mut job_ready, &mut machine_ready);
+                    next[job] += 1;
+                }
+            }
+            ScheduleBuilder::Active => {
+                //position in the genome of each operation, used as priority
+                let mut priority:Vec<Vec<usize>> = self.jobs.iter().map(|job| Vec::with_capacity(job.len())).collect();
+                for (position, &job) in genome.iter().enumerate() {
+                    priority[job].push(position);
+                }
+                assert!(priority.iter().zip(&self.jobs).all(|(positions, job)| positions.len() == job.len()), "Each job must appear once per operation");
+                for _ in 0..genome.len() {
+                    let earliest = |job:usize| {
+                        let (machine, time) = self.jobs[job][next[job]];
+                        let start = f64::max(job_ready[job], machine_ready[machine]);
+                        (machine, start, start + time)
+                    };
+                    let pending = (0..self.jobs.len()).filter(|&job| next[job] < self.jobs[job].len());
+                    let (machine, _, completion) = pending.clone().map(earliest)
+                        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+                        .unwrap();
+                    let job = pending
+                        .filter(|&job| {
+                            let (other, start, _) = earliest(job);
+                            other == machine && start < completion
+                        })
+                        .min_by_key(|&job| priority[job][next[job]])
+                        .unwrap();
+                    place(job, next[job], &mut operations, &mut job_ready, &mut machine_ready);
+                    next[job] += 1;
+                }
+            }
+        }
+        Schedule{operations, num_jobs:self.jobs.len(), num_machines:self.num_machines}
+    }
+    ///fitness function for GA::new with initialization(): the objective of the built schedule negated, as fitness is maximized
+    pub fn fitness(self, builder:ScheduleBuilder, objective:Objective)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -self.schedule(&vec[0], builder).objective(objective, self.due_dates.as_deref()),
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+    ///read a file of Taillard job-shop instances
+    pub fn from_taillard(path:&str) -> io::Result<Vec<Self>>{
+        JobShop::parse_taillard(&fs::read_to_string(path)?)
+    }
+    ///parse Taillard job-shop instances: a line "jobs machines time_seed machine_seed upper_bound lower_bound",
+    /// a "Times" matrix (one row per job) and a "Machines" matrix (one row per job, machines numbered from 1)<BR>
+    /// the upper bound is stored as optimum
+    pub fn parse_taillard(content:&str) -> io::Result<Vec<Self>>{
+        let rows = numeric_rows(content);
+        let mut instances = Vec::new();
+        let mut i = 0;
+        while i < rows.len() {
+            let header = &rows[i];
+            if header.len() != 6 {
+                return Err(invalid(format!("expected an instance header, found {:?}", header)));
+            }
+            let (num_jobs, num_machines) = (header[0] as usize, header[1] as usize);
+            let times = rows.get(i + 1..i + 1 + num_jobs).ok_or_else(|| invalid("missing processing times".to_string()))?;
+            let machines = rows.get(i + 1 + num_jobs..i + 1 + 2 * num_jobs).ok_or_else(|| invalid("missing machines".to_string()))?;
+            let jobs = times.iter().zip(machines).map(|(times, machines)| {
+                if times.len() != num_machines || machines.len() != num_machines || machines.iter().any(|&m| m < 1.0 || m as usize > num_machines) {
+                    return Err(invalid(format!("invalid job: times {:?}, machines {:?}", times, machines)));
+                }
+                Ok(machines.iter().zip(times).map(|(&machine, &time)| (machine as usize - 1, time)).collect())
+            }).collect::<io::Result<Vec<Vec<(usize,f64)>>>>()?;
+            let mut instance = JobShop::new(jobs);
+            instance.name = format!("ta{}x{}-{}", num_jobs, num_machines, instances.len() + 1);
+            instance.optimum = Some(header[4]);
+            instances.push(instance);
+            i += 1 + 2 * num_jobs;
+        }
+        Ok(instances)
+    }
+    ///read a file of OR-Library job-shop instances
+    pub fn from_orlib(path:&str) -> io::Result<Vec<Self>>{
+        JobShop::parse_orlib(&fs::read_to_string(path)?)
+    }
+    ///parse OR-Library job-shop instances (e.g. jobshop1.txt): an optional "instance name" line, description lines,
+    /// a line "jobs machines" then one row per job of (machine, processing time) pairs, machines numbered from 0
+    pub fn parse_orlib(content:&str) -> io::Result<Vec<Self>>{
+        let mut instances = Vec::new();
+        let mut name = String::new();
+        let mut lines = content.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            if let Some(instance) = line.strip_prefix("instance ") {
+                name = instance.trim().to_string();
+                continue;
+            }
+            let header = match parse_numbers(line) {
+                Some(header) if header.len() == 2 => header,
+                _ => continue,
+            };
+            let (num_jobs, num_machines) = (header[0] as usize, header[1] as usize);
+            let mut jobs = Vec::with_capacity(num_jobs);
+            while jobs.len() < num_jobs {
+                let line = lines.next().ok_or_else(|| invalid(format!("{}: missing jobs", name)))?;
+                if line.is_empty() {
+                    continue;
+                }
+                let row = parse_numbers(line).filter(|row| row.len() == 2 * num_machines)
+                    .ok_or_else(|| invalid(format!("{}: expected {} (machine, time) pairs, found {}", name, num_machines, line)))?;
+                jobs.push(row.chunks(2).map(|pair| (pair[0] as usize, pair[1])).collect());
+            }
+            let mut instance = JobShop::new(jobs);
+            instance.name = std::mem::take(&mut name);
+            instances.push(instance);
+        }
+        Ok(instances)
+    }
+}
+
+///permutation flow-shop scheduling: every job visits the machines in the same order and every machine processes the jobs in the same order<BR>
+/// genomes are permutations of the jobs (TSPInitialization with Config::num_genes = number of jobs)
+#[derive(Clone, Debug)]
+pub struct FlowShop{
+    pub name:String,
+    ///processing time of each job on each machine
+    pub processing_times:Vec<Vec<f64>>,
+    ///due date of each job (None if tardiness is not used)
+    pub due_dates:Option<Vec<f64>>,
+    ///best known makespan (None if unknown)
+    pub optimum:Option<f64>,
+}
+impl FlowShop{
+    pub fn new(processing_times:Vec<Vec<f64>>) -> Self{
+        FlowShop{name:String::new(), processing_times, due_dates:None, optimum:None}
+    }
+    ///set the due date of each job to factor times its total processing time
+    pub fn with_due_dates(mut self, factor:f64) -> Self{
+        self.due_dates = Some(self.processing_times.iter().map(|times| factor * times.iter().sum::<f64>()).collect());
+        self
+    }
+    pub fn num_jobs(&self)->usize{
+        self.processing_times.len()
+    }
+    ///schedule of the jobs in the given order, each operation starting as early as possible
+    pub fn schedule(&self, permutation:&[usize])->Schedule{
+        let num_machines = self.processing_times.first().map_or(0, |times| times.len());
+        let mut machine_ready = vec![0.0; num_machines];
+        let mut operations = Vec::with_capacity(permutation.len() * num_machines);
+        for &job in permutation {
+            let mut job_ready:f64 = 0.0;
+            for (machine, &time) in self.processing_times[job].iter().enumerate() {
+                let start = job_ready.max(machine_ready[machine]);
+                job_ready = start + time;
+                machine_ready[machine] = start + time;
+                operations.push(ScheduledOperation{job, index:machine, machine, start, end:start + time});
+            }
+        }
+        Schedule{operations, num_jobs:self.num_jobs(), num_machines}
+    }
+    ///job-shop instance with the same operations (every job visits machines 0, 1, ...)
+    pub fn to_job_shop(&self)->JobShop{
+        let mut job_shop = JobShop::new(self.processing_times.iter().map(|times| times.iter().cloned().enumerate().collect()).collect());
+        job_shop.name = self.name.clone();
+        job_shop.due_dates = self.due_dates.clone();
+        job_shop
+    }
+    ///fitness function for GA::new with TSPInitialization: the objective of the schedule negated, as fitness is maximized
+    pub fn fitness(self, objective:Objective)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -self.schedule(&vec[0]).objective(objective, self.due_dates.as_deref()),
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+    ///read a file of Taillard flow-shop instances
+    pub fn from_taillard(path:&str) -> io::Result<Vec<Self>>{
+        FlowShop::parse_taillard(&fs::read_to_string(path)?)
+    }
+    ///parse Taillard flow-shop instances: a line "jobs machines seed upper_bound lower_bound"
+    /// then the processing times, one row per machine<BR>
+    /// the upper bound is stored as optimum
+    pub fn parse_taillard(content:&str) -> io::Result<Vec<Self>>{
+        let rows = numeric_rows(content);
+        let mut instances = Vec::new();
+        let mut i = 0;
+        while i < rows.len() {
+            let header = &rows[i];
+            if header.len() != 5 {
+                return Err(invalid(format!("expected an instance header, found {:?}", header)));
+            }
+            let (num_jobs, num_machines) = (header[0] as usize, header[1] as usize);
+            let machines = rows.get(i + 1..i + 1 + num_machines).ok_or_else(|| invalid("missing processing times".to_string()))?;
+            if machines.iter().any(|row| row.len() != num_jobs) {
+                return Err(invalid(format!("expected {} processing times per machine", num_jobs)));
+            }
+            let mut instance = FlowShop::new((0..num_jobs).map(|job| machines.iter().map(|row| row[job]).collect()).collect());
+            instance.name = format!("ta{}x{}-{}", num_jobs, num_machines, instances.len() + 1);
+            instance.optimum = Some(header[3]);
+            instances.push(instance);
+            i += 1 + num_machines;
+        }
+        Ok(instances)
+    }
+    ///read a file of OR-Library flow-shop instances
+    pub fn from_orlib(path:&str) -> io::Result<Vec<Self>>{
+        FlowShop::parse_orlib(&fs::read_to_string(path)?)
+    }
+    ///parse OR-Library flow-shop instances (e.g. flowshop1.txt), same layout as JobShop::parse_orlib
+    pub fn parse_orlib(content:&str) -> io::Result<Vec<Self>>{
+        JobShop::parse_orlib(content)?.into_iter().map(|job_shop| {
+            let processing_times = job_shop.jobs.iter().map(|job| {
+                let mut times = vec![0.0; job_shop.num_machines];
+                job.iter().for_each(|&(machine, time)| times[machine] = time);
+                times
+            }).collect();
+            Ok(FlowShop{name:job_shop.name, processing_times, due_dates:None, optimum:None})
+        }).collect()
+    }
+}
+
+fn parse_numbers(line:&str)->Option<Vec<f64>>{
+    let numbers = line.split_whitespace().map(|field| field.parse::<f64>()).collect::<Result<Vec<f64>,_>>().ok()?;
+    if numbers.is_empty() {None} else {Some(numbers)}
+}
+///rows of numbers of a file, skipping text lines (titles such as "Times" or "processing times :")
+fn numeric_rows(content:&str)->Vec<Vec<f64>>{
+    content.lines().filter_map(parse_numbers).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const FT06:&str = " instance ft06\n +++++++++++++++++++++++++++++\n Fisher and Thompson 6x6 instance, alternate name (mt06)\n 6 6\n\
+        2  1  0  3  1  6  3  7  5  3  4  6\n 1  8  2  5  4 10  5 10  0 10  3  4\n 2  5  3  4  5  8  0  9  1  1  4  7\n\
+        1  5  0  5  2  5  3  3  4  8  5  9\n 2  9  1  3  4  5  5  4  0  3  3  1\n 1  3  3  3  5  9  0 10  4  4  2  1\n +++++++++++++++++++++++++++++\n";
+
+    fn random_genome(job_shop:&JobShop)->Vec<usize>{
+        let mut genome:Vec<usize> = job_shop.jobs.iter().enumerate().flat_map(|(job, operations)| vec![job; operations.len()]).collect();
+        genome.shuffle(&mut thread_rng());
+        genome
+    }
+
+    ///operations of each job follow each other, operations of a machine never overlap
+    fn assert_feasible(job_shop:&JobShop, schedule:&Schedule){
+        assert_eq!(schedule.operations.len(), job_shop.num_operations());
+        for operation in &schedule.operations {
+            let (machine, time) = job_shop.jobs[operation.job][operation.index];
+            assert_eq!((operation.machine, operation.end - operation.start), (machine, time));
+            if operation.index > 0 {
+                let previous = schedule.operations.iter().find(|other| other.job == operation.job && other.index == operation.index - 1).unwrap();
+                assert!(previous.end <= operation.start);
+            }
+            for other in &schedule.operations {
+                let same = other.job == operation.job && other.index == operation.index;
+                assert!(same || other.machine != operation.machine || other.end <= operation.start || operation.end <= other.start);
+            }
+        }
+    }
+
+    #[test]
+    fn ft06_is_parsed_from_orlib(){
+        let instances = JobShop::parse_orlib(FT06).unwrap();
+        assert_eq!(instances.len(), 1);
+        let ft06 = &instances[0];
+        assert_eq!(ft06.name, "ft06");
+        assert_eq!((ft06.jobs.len(), ft06.num_machines, ft06.num_operations()), (6, 6, 36));
+        assert_eq!(ft06.jobs[0][..2], [(2, 1.0), (0, 3.0)]);
+        assert_eq!(ft06.jobs[5][5], (2, 1.0));
+        assert!(JobShop::parse_orlib(" instance bad\n 2 2\n 0 1 1 2\n 0 1\n").is_err());
+    }
+
+    #[test]
+    fn schedules_are_feasible_and_not_below_the_optimum(){
+        let ft06 = JobShop::parse_orlib(FT06).unwrap().remove(0);
+        for _ in 0..100 {
+            let genome = random_genome(&ft06);
+            for builder in [ScheduleBuilder::SemiActive, ScheduleBuilder::Active] {
+                let schedule = ft06.schedule(&genome, builder);
+                assert_feasible(&ft06, &schedule);
+                assert!(schedule.makespan() >= 55.0);
+            }
+        }
+    }
+
+    #[test]
+    fn giffler_thompson_schedules_are_active(){
+        let ft06 = JobShop::parse_orlib(FT06).unwrap().remove(0);
+        for _ in 0..100 {
+            let schedule = ft06.schedule(&random_genome(&ft06), ScheduleBuilder::Active);
+            //no operation fits in an idle gap of its machine that starts after the end of its job predecessor
+            for operation in &schedule.operations {
+                let release = schedule.operations.iter()
+                    .filter(|other| other.job == operation.job && other.index + 1 == operation.index)
+                    .map(|other| other.end).fold(0.0, f64::max);
+                let mut busy:Vec<(f64,f64)> = schedule.operations.iter()
+                    .filter(|other| other.machine == operation.machine && other.start < operation.start)
+                    .map(|other| (other.start, other.end)).collect();
+                busy.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                let mut idle_from = 0.0;
+                for (start, end) in busy {
+                    assert!(start - f64::max(idle_from, release) < operation.end - operation.start, "operation {:?} fits before {}", operation, start);
+                    idle_from = f64::max(idle_from, end);
+                }
+                assert_eq!(operation.start, f64::max(idle_from, release));
+            }
+        }
+    }
+
+    #[test]
+    fn taillard_job_shop_is_parsed(){
+        let content = "Nb of jobs, Nb of Machines, Time seed, Machine seed, Upper bound, Lower bound\n 2 2 840612802 398197754 7 7\n\
+            Times\n 3 4\n 2 1\nMachines\n 1 2\n 2 1\n";
+        let instance = JobShop::parse_taillard(content).unwrap().remove(0);
+        assert_eq!(instance.jobs, vec![vec![(0, 3.0), (1, 4.0)], vec![(1, 2.0), (0, 1.0)]]);
+        assert_eq!(instance.optimum, Some(7.0));
+        assert_eq!(instance.schedule(&[0, 1, 0, 1], ScheduleBuilder::Active).makespan(), 7.0);
+        assert!(JobShop::parse_taillard(&content.replace("Machines\n 1 2", "Machines\n 1 3")).is_err());
+        assert!(JobShop::parse_taillard("2 2 0 0 7 7\nTimes\n3 4\n").is_err());
+    }
+
+    #[test]
+    fn flow_shop_instances_are_parsed(){
+        let content = "number of jobs, number of machines, initial seed, upper bound and lower bound :\n 3 2 873654221 7 7\nprocessing times :\n 1 2 3\n 3 2 1\n";
+        let taillard = FlowShop::parse_taillard(content).unwrap().remove(0);
+        assert_eq!(taillard.processing_times, vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0]]);
+        assert_eq!(taillard.optimum, Some(7.0));
+        assert_eq!(taillard.schedule(&[0, 1, 2]).makespan(), 7.0);
+        assert_eq!(taillard.schedule(&[2, 1, 0]).makespan(), 10.0);
+        let orlib = FlowShop::parse_orlib(" instance car0\n 3 2\n 0 1 1 3\n 0 2 1 2\n 0 3 1 1\n").unwrap().remove(0);
+        assert_eq!(orlib.processing_times, taillard.processing_times);
+        assert!(FlowShop::parse_taillard(&content.replace(" 3 2 1\n", " 3 2\n")).is_err());
+    }
+}
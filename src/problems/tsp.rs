@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use crate::population::Population;
+
+///how distances are computed from node coordinates (TSPLIB EDGE_WEIGHT_TYPE)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeWeightType{
+    ///Euclidean distance rounded to the nearest integer
+    Euc2D,
+    ///Euclidean distance rounded up
+    Ceil2D,
+    ///geographical distance in km, coordinates are latitude and longitude in DDD.MM format
+    Geo,
+    ///pseudo-Euclidean distance of the att instances
+    Att,
+    ///distances given in the file
+    Explicit,
+    ///exact Euclidean distance (not part of TSPLIB)
+    Euclidean,
+}
+impl EdgeWeightType{
+    ///distance between two nodes given their coordinates (Explicit has none, use TSP::distance)
+    pub fn distance(&self, a:(f64,f64), b:(f64,f64))->f64{
+        let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+        match self {
+            EdgeWeightType::Euc2D => nint((dx * dx + dy * dy).sqrt()),
+            EdgeWeightType::Ceil2D => (dx * dx + dy * dy).sqrt().ceil(),
+            EdgeWeightType::Euclidean => (dx * dx + dy * dy).sqrt(),
+            EdgeWeightType::Att => {
+                let r = ((dx * dx + dy * dy) / 10.0).sqrt();
+                let t = nint(r);
+                if t < r {t + 1.0} else {t}
+            }
+            EdgeWeightType::Geo => {
+                //TSPLIB truncates degrees and distances
+                let radians = |x:f64| {
+                    let degrees = x.trunc();
+                    TSPLIB_PI * (degrees + 5.0 * (x - degrees) / 3.0) / 180.0
+                };
+                let (latitude_a, longitude_a, latitude_b, longitude_b) = (radians(a.0), radians(a.1), radians(b.0), radians(b.1));
+                let q1 = (longitude_a - longitude_b).cos();
+                let q2 = (latitude_a - latitude_b).cos();
+                let q3 = (latitude_a + latitude_b).cos();
+                (6378.388 * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+            }
+            EdgeWeightType::Explicit => panic!("Explicit distances have no coordinates"),
+        }
+    }
+}
+///approximation of π used by TSPLIB, needed to reproduce its GEO distances
+#[allow(clippy::approx_constant)]
+const TSPLIB_PI:f64 = 3.141592;
+///nearest integer as defined by TSPLIB
+fn nint(x:f64)->f64{
+    (x + 0.5).floor()
+}
+
+///symmetric travelling salesman problem with a precomputed distance matrix, nodes are numbered from 0
+#[derive(Clone, Debug)]
+pub struct TSP{
+    pub name:String,
+    pub edge_weight_type:EdgeWeightType,
+    ///node coordinates (None for explicit distances)
+    pub coordinates:Option<Vec<(f64,f64)>>,
+    pub distances:Vec<Vec<f64>>,
+}
+impl TSP{
+    pub fn from_coordinates(coordinates:Vec<(f64,f64)>, edge_weight_type:EdgeWeightType) -> Self{
+        let distances = coordinates.iter().map(|&a| coordinates.iter().map(|&b| edge_weight_type.distance(a, b)).collect()).collect();
+        TSP{name:String::new(), edge_weight_type, coordinates:Some(coordinates), distances}
+    }
+    pub fn from_matrix(distances:Vec<Vec<f64>>) -> Self{
+        assert!(distances.iter().all(|row| row.len() == distances.len()), "The distance matrix must be square");
+        TSP{name:String::new(), edge_weight_type:EdgeWeightType::Explicit, coordinates:None, distances}
+    }
+    ///read a TSPLIB .tsp file
+    pub fn from_tsplib(path:&str) -> io::Result<Self>{
+        TSP::parse_tsplib(&fs::read_to_string(path)?)
+    }
+    ///parse a TSPLIB symmetric instance (TYPE TSP)<BR>
+    /// EDGE_WEIGHT_TYPE: EUC_2D, CEIL_2D, GEO, ATT with a NODE_COORD_SECTION,
+    /// or EXPLICIT with an EDGE_WEIGHT_SECTION in any EDGE_WEIGHT_FORMAT of symmetric matrices
+    pub fn parse_tsplib(content:&str) -> io::Result<Self>{
+        let tsplib = TSPLib::parse(content)?;
+        let dimension:usize = tsplib.value("DIMENSION")?.parse().map_err(|_| invalid("DIMENSION must be an integer".to_string()))?;
+        let edge_weight_type = match tsplib.value("EDGE_WEIGHT_TYPE")? {
+            "EUC_2D" => EdgeWeightType::Euc2D,
+            "CEIL_2D" => EdgeWeightType::Ceil2D,
+            "GEO" => EdgeWeightType::Geo,
+            "ATT" => EdgeWeightType::Att,
+            "EXPLICIT" => EdgeWeightType::Explicit,
+            other => return Err(invalid(format!("unsupported EDGE_WEIGHT_TYPE {}", other))),
+        };
+        let mut tsp = if edge_weight_type == EdgeWeightType::Explicit {
+            let format = tsplib.value("EDGE_WEIGHT_FORMAT")?;
+            let weights = tsplib.numbers("EDGE_WEIGHT_SECTION")?;
+            TSP::from_matrix(explicit_matrix(&weights, dimension, format)?)
+        } else {
+            let values = tsplib.numbers("NODE_COORD_SECTION")?;
+            if values.len() != 3 * dimension {
+                return Err(invalid(format!("expected {} nodes with 2 coordinates", dimension)));
+            }
+            let mut coordinates = vec![(0.0, 0.0); dimension];
+            for node in values.chunks(3) {
+                let index = node[0] as usize;
+                if index == 0 || index > dimension {
+                    return Err(invalid(format!("node {} out of range", node[0])));
+                }
+                coordinates[index - 1] = (node[1], node[2]);
+            }
+            TSP::from_coordinates(coordinates, edge_weight_type)
+        };
+        tsp.name = tsplib.value("NAME").unwrap_or_default().to_string();
+        Ok(tsp)
+    }
+    pub fn num_cities(&self)->usize{
+        self.distances.len()
+    }
+    pub fn distance(&self, a:usize, b:usize)->f64{
+        self.distances[a][b]
+    }
+    ///length of the closed tour, including the leg from the last city back to the first
+    pub fn tour_length(&self, tour:&[usize])->f64{
+        let legs:f64 = tour.windows(2).map(|leg| self.distances[leg[0]][leg[1]]).sum();
+        match (tour.first(), tour.last()) {
+            (Some(&first), Some(&last)) => legs + self.distances[last][first],
+            _ => 0.0,
+        }
+    }
+    ///relative excess of a tour over a reference (e.g. optimal) tour, 0 when both have the same length
+    pub fn gap(&self, tour:&[usize], reference:&[usize])->f64{
+        let reference = self.tour_length(reference);
+        (self.tour_length(tour) - reference) / reference
+    }
+    ///fitness function for GA::new with TSPInitialization: the tour length negated, as fitness is maximized
+    pub fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -self.tour_length(&vec[0]),
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+}
+
+///read a TSPLIB .tour file (e.g. .opt.tour), cities are returned numbered from 0
+pub fn read_tour(path:&str) -> io::Result<Vec<usize>>{
+    parse_tour(&fs::read_to_string(path)?)
+}
+///parse the TOUR_SECTION of a TSPLIB tour: cities numbered from 1, terminated by -1
+pub fn parse_tour(content:&str) -> io::Result<Vec<usize>>{
+    let numbers = TSPLib::parse(content)?.numbers("TOUR_SECTION")?;
+    let end = numbers.iter().position(|&city| city < 0.0).unwrap_or(numbers.len());
+    numbers[..end].iter().map(|&city| {
+        if city >= 1.0 {Ok(city as usize - 1)} else {Err(invalid(format!("invalid city {}", city)))}
+    }).collect()
+}
+
+pub(crate) fn invalid(message:String)->io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+///specification entries ("KEY : value") and data sections of a TSPLIB file
+pub(crate) struct TSPLib<'a>{
+    specification:HashMap<&'a str,&'a str>,
+    sections:HashMap<&'a str,Vec<&'a str>>,
+}
+impl<'a> TSPLib<'a>{
+    pub(crate) fn parse(content:&'a str) -> io::Result<Self>{
+        let mut specification = HashMap::new();
+        let mut sections:HashMap<&str,Vec<&str>> = HashMap::new();
+        let mut section = None;
+        for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line == "EOF" {
+                break;
+            }
+            let keyword = line.split([' ', ':']).next().unwrap_or("");
+            if keyword.ends_with("_SECTION") {
+                section = Some(keyword);
+                sections.entry(keyword).or_default();
+                continue;
+            }
+            match (section, line.split_once(':')) {
+                (None, Some((key, value))) => {
+                    specification.insert(key.trim(), value.trim());
+                }
+                (Some(section), _) if !keyword.chars().next().is_some_and(|c| c.is_ascii_uppercase()) => {
+                    sections.get_mut(section).unwrap().extend(line.split_whitespace());
+                }
+                //specification entries may follow a section
+                (Some(_), Some((key, value))) => {
+                    section = None;
+                    specification.insert(key.trim(), value.trim());
+                }
+                _ => return Err(invalid(format!("unexpected line: {}", line))),
+            }
+        }
+        Ok(TSPLib{specification, sections})
+    }
+    pub(crate) fn value(&self, key:&str) -> io::Result<&'a str>{
+        self.specification.get(key).copied().ok_or_else(|| invalid(format!("missing {}", key)))
+    }
+    pub(crate) fn numbers(&self, section:&str) -> io::Result<Vec<f64>>{
+        self.sections.get(section).ok_or_else(|| invalid(format!("missing {}", section)))?
+            .iter().map(|field| field.parse::<f64>().map_err(|e| invalid(format!("{}: {}", section, e))))
+            .collect()
+    }
+}
+
+///full symmetric matrix from the weights of an EDGE_WEIGHT_SECTION
+fn explicit_matrix(weights:&[f64], dimension:usize, format:&str) -> io::Result<Vec<Vec<f64>>>{
+    //column formats list the same entries as the row formats of the other triangle
+    let (upper, diagonal) = match format {
+        "FULL_MATRIX" => {
+            if weights.len() != dimension * dimension {
+                return Err(invalid(format!("FULL_MATRIX needs {} weights", dimension * dimension)));
+            }
+            return Ok(weights.chunks(dimension).map(|row| row.to_vec()).collect());
+        }
+        "UPPER_ROW" | "LOWER_COL" => (true, false),
+        "LOWER_ROW" | "UPPER_COL" => (false, false),
+        "UPPER_DIAG_ROW" | "LOWER_DIAG_COL" => (true, true),
+        "LOWER_DIAG_ROW" | "UPPER_DIAG_COL" => (false, true),
+        other => return Err(invalid(format!("unsupported EDGE_WEIGHT_FORMAT {}", other))),
+    };
+    let entries:Vec<(usize,usize)> = (0..dimension).flat_map(|i| {
+        let columns = match (upper, diagonal) {
+            (true, true) => i..dimension,
+            (true, false) => i + 1..dimension,
+            (false, true) => 0..i + 1,
+            (false, false) => 0..i,
+        };
+        columns.map(move |j| (i, j))
+    }).collect();
+    if weights.len() != entries.len() {
+        return Err(invalid(format!("{} needs {} weights", format, entries.len())));
+    }
+    let mut matrix = vec![vec![0.0; dimension]; dimension];
+    for (&(i, j), &weight) in entries.iter().zip(weights) {
+        matrix[i][j] = weight;
+        matrix[j][i] = weight;
+    }
+    Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const BURMA14:&str = "NAME: burma14\nTYPE: TSP\nCOMMENT: 14-Staedte in Burma (Zaw Win)\nDIMENSION: 14\nEDGE_WEIGHT_TYPE: GEO\nEDGE_WEIGHT_FORMAT: FUNCTION\n\
+        DISPLAY_DATA_TYPE: COORD_DISPLAY\nNODE_COORD_SECTION\n   1  16.47       96.10\n   2  16.47       94.44\n   3  20.09       92.54\n\
+        4  22.39       93.37\n   5  25.23       97.24\n   6  22.00       96.05\n   7  20.47       97.02\n   8  17.20       96.29\n\
+        9  16.30       97.38\n  10  14.05       98.12\n  11  16.53       97.38\n  12  21.52       95.59\n  13  19.41       97.13\n  14  20.09       94.55\nEOF\n";
+
+    #[test]
+    fn burma14_optimal_tour_has_the_published_length(){
+        let tsp = TSP::parse_tsplib(BURMA14).unwrap();
+        assert_eq!(tsp.name, "burma14");
+        assert_eq!(tsp.num_cities(), 14);
+        let optimal = parse_tour("NAME: burma14.opt.tour\nTYPE: TOUR\nDIMENSION: 14\nTOUR_SECTION\n1\n2\n14\n3\n4\n5\n6\n12\n7\n13\n8\n11\n9\n10\n-1\nEOF\n").unwrap();
+        assert_eq!(optimal, vec![0, 1, 13, 2, 3, 4, 5, 11, 6, 12, 7, 10, 8, 9]);
+        assert_eq!(tsp.tour_length(&optimal), 3323.0);
+        assert_eq!(tsp.gap(&optimal, &optimal), 0.0);
+    }
+
+    #[test]
+    fn explicit_formats_give_the_same_matrix(){
+        let full = vec![vec![0.0, 1.0, 2.0, 3.0], vec![1.0, 0.0, 4.0, 5.0], vec![2.0, 4.0, 0.0, 6.0], vec![3.0, 5.0, 6.0, 0.0]];
+        for (format, weights) in [("FULL_MATRIX", "0 1 2 3 1 0 4 5 2 4 0 6 3 5 6 0"), ("UPPER_ROW", "1 2 3 4 5 6"), ("LOWER_COL", "1 2 3 4 5 6"),
+            ("LOWER_ROW", "1 2 4 3 5 6"), ("UPPER_COL", "1 2 4 3 5 6"), ("UPPER_DIAG_ROW", "0 1 2 3 0 4 5 0 6 0"), ("LOWER_DIAG_ROW", "0 1 0 2 4 0 3 5 6 0")] {
+            let content = format!("NAME: test\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n{}\nEOF\n", format, weights);
+            assert_eq!(TSP::parse_tsplib(&content).unwrap().distances, full, "{}", format);
+        }
+    }
+
+    #[test]
+    fn coordinate_distances_are_rounded_like_tsplib(){
+        assert_eq!(EdgeWeightType::Euc2D.distance((0.0, 0.0), (1.0, 1.0)), 1.0);
+        assert_eq!(EdgeWeightType::Ceil2D.distance((0.0, 0.0), (1.0, 1.0)), 2.0);
+        assert_eq!(EdgeWeightType::Euc2D.distance((0.0, 0.0), (3.0, 4.0)), 5.0);
+        //sqrt(100/10) = 3.16 is rounded to 3, then up to 4 as 3 < 3.16
+        assert_eq!(EdgeWeightType::Att.distance((0.0, 0.0), (6.0, 8.0)), 4.0);
+    }
+
+    #[test]
+    fn malformed_instances_are_rejected(){
+        let node_coord = |dimension:&str, kind:&str, nodes:&str| format!("NAME: t\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: {}\nNODE_COORD_SECTION\n{}\nEOF\n", dimension, kind, nodes);
+        for content in [node_coord("two", "EUC_2D", "1 0 0\n2 1 1"), node_coord("2", "MAN_2D", "1 0 0\n2 1 1"), node_coord("2", "EUC_2D", "1 0 0"),
+            node_coord("2", "EUC_2D", "1 0 0\n3 1 1"), node_coord("2", "EUC_2D", "1 0 0\n2 1 x"), "NAME: t\nDIMENSION: 2\nEOF\n".to_string(),
+            "NAME: t\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\nEOF\n".to_string()] {
+            assert_eq!(TSP::parse_tsplib(&content).unwrap_err().kind(), io::ErrorKind::InvalidData, "{}", content);
+        }
+        assert!(parse_tour("TOUR_SECTION\n1 0 2\n-1\n").is_err());
+    }
+}
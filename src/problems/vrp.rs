@@ -0,0 +1,444 @@
+use std::fs;
+use std::io;
+use std::sync::Arc;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::population::{Population, Variation, VariationStrategy};
+use crate::problems::tsp::{invalid, EdgeWeightType, TSPLib, TSP};
+
+///cost added per unit of lateness of a route holding a single customer that cannot be served in time<BR>
+/// (routes of several customers must respect time windows)
+const LATENESS_PENALTY:f64 = 1000.0;
+///cost added per route beyond VRP::num_vehicles, high enough that split only exceeds the fleet when the tour cannot be served otherwise
+const EXCESS_ROUTE_PENALTY:f64 = 1e6;
+
+///capacitated vehicle routing problem, optionally with time windows (VRPTW)<BR>
+/// node 0 is the depot and nodes 1..=n the customers<BR>
+/// individuals are giant tours: permutations of the n customers numbered from 0 (TSPInitialization with num_genes = n),
+/// split into routes by an optimal split decoder (see split)
+#[derive(Clone, Debug)]
+pub struct VRP{
+    pub name:String,
+    ///node coordinates (None for explicit distances)
+    pub coordinates:Option<Vec<(f64,f64)>>,
+    pub distances:Vec<Vec<f64>>,
+    ///demand of each node (0 for the depot)
+    pub demands:Vec<f64>,
+    pub capacity:f64,
+    ///earliest and latest start of service at each node (None for CVRP)
+    pub time_windows:Option<Vec<(f64,f64)>>,
+    ///service duration at each node
+    pub service_times:Vec<f64>,
+    ///maximum number of routes (None for an unlimited fleet)
+    pub num_vehicles:Option<usize>,
+    ///best known total distance (None if unknown)
+    pub optimum:Option<f64>,
+}
+
+///routes decoded from a giant tour
+#[derive(Clone, Debug)]
+pub struct Solution{
+    ///customers (node indices) of each route, depot excluded
+    pub routes:Vec<Vec<usize>>,
+    pub distance:f64,
+    ///total lateness of the routes that miss a time window (only single customer routes can be late)
+    pub lateness:f64,
+    ///number of routes beyond VRP::num_vehicles (0 for a feasible fleet)
+    pub excess_routes:usize,
+}
+impl Solution{
+    ///cost minimized by split: distance plus penalized lateness and excess routes
+    pub fn cost(&self)->f64{
+        self.distance + LATENESS_PENALTY * self.lateness + EXCESS_ROUTE_PENALTY * self.excess_routes as f64
+    }
+    ///giant tour (genes) visiting the routes in order
+    pub fn giant_tour(&self)->Vec<usize>{
+        self.routes.iter().flatten().map(|node| node - 1).collect()
+    }
+}
+
+impl VRP{
+    ///CVRP with distances computed from coordinates, the depot is the first node
+    pub fn new(tsp:TSP, demands:Vec<f64>, capacity:f64) -> Self{
+        assert_eq!(demands.len(), tsp.num_cities(), "One demand is needed per node");
+        let service_times = vec![0.0; demands.len()];
+        VRP{name:tsp.name, coordinates:tsp.coordinates, distances:tsp.distances, demands, capacity, time_windows:None, service_times, num_vehicles:None, optimum:None}
+    }
+    pub fn num_customers(&self)->usize{
+        self.demands.len() - 1
+    }
+    ///read a CVRPLIB .vrp file
+    pub fn from_cvrplib(path:&str) -> io::Result<Self>{
+        VRP::parse_cvrplib(&fs::read_to_string(path)?)
+    }
+    ///parse a CVRPLIB instance (TSPLIB format with TYPE CVRP, CAPACITY, DEMAND_SECTION and DEPOT_SECTION)<BR>
+    /// the number of vehicles is read from the name (e.g. A-n32-k5) and the optimum from the comment (e.g. "Optimal value: 784")
+    pub fn parse_cvrplib(content:&str) -> io::Result<Self>{
+        let tsp = TSP::parse_tsplib(content)?;
+        let tsplib = TSPLib::parse(content)?;
+        let capacity:f64 = tsplib.value("CAPACITY")?.parse().map_err(|_| invalid("CAPACITY must be a number".to_string()))?;
+        let mut demands = vec![0.0; tsp.num_cities()];
+        for node in tsplib.numbers("DEMAND_SECTION")?.chunks(2) {
+            match node {
+                &[index, demand] if index >= 1.0 && (index as usize) <= demands.len() => demands[index as usize - 1] = demand,
+                _ => return Err(invalid(format!("invalid demand {:?}", node))),
+            }
+        }
+        let depot = match tsplib.numbers("DEPOT_SECTION") {
+            Ok(depots) => match depots.first() {
+                Some(&depot) if depot >= 1.0 && (depot as usize) <= demands.len() => depot as usize - 1,
+                _ => return Err(invalid("invalid DEPOT_SECTION".to_string())),
+            },
+            Err(_) => 0,
+        };
+        let mut vrp = VRP::new(tsp, demands, capacity).with_depot(depot);
+        vrp.num_vehicles = vrp.name.rsplit_once("-k").and_then(|(_, vehicles)| vehicles.parse().ok());
+        vrp.optimum = tsplib.value("COMMENT").ok().and_then(|comment| {
+            let (_, value) = comment.split_once("value:")?;
+            value.trim().trim_end_matches(')').split([',', ')', ' ']).next()?.parse().ok()
+        });
+        Ok(vrp)
+    }
+    ///read a Solomon VRPTW instance
+    pub fn from_solomon(path:&str) -> io::Result<Self>{
+        VRP::parse_solomon(&fs::read_to_string(path)?)
+    }
+    ///parse a Solomon VRPTW instance: name, VEHICLE section (number and capacity)
+    /// and CUSTOMER section (number, x, y, demand, ready time, due date, service time), customer 0 being the depot<BR>
+    /// distances are exact Euclidean distances
+    pub fn parse_solomon(content:&str) -> io::Result<Self>{
+        let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty());
+        let name = lines.next().ok_or_else(|| invalid("empty file".to_string()))?.to_string();
+        let numeric = |line:&str| line.split_whitespace().map(|field| field.parse::<f64>()).collect::<Result<Vec<f64>,_>>().ok();
+        let mut vehicle = None;
+        let mut nodes = Vec::new();
+        for line in lines {
+            match numeric(line) {
+                Some(row) if row.len() == 2 && vehicle.is_none() => vehicle = Some((row[0] as usize, row[1])),
+                Some(row) if row.len() == 7 => {
+                    if row[0] as usize != nodes.len() {
+                        return Err(invalid(format!("customer {} out of order", row[0])));
+                    }
+                    nodes.push(row);
+                }
+                Some(row) => return Err(invalid(format!("unexpected row {:?}", row))),
+                //section titles and column headers
+                None => {}
+            }
+        }
+        let (num_vehicles, capacity) = vehicle.ok_or_else(|| invalid("missing VEHICLE section".to_string()))?;
+        if nodes.is_empty() {
+            return Err(invalid("missing CUSTOMER section".to_string()));
+        }
+        let mut tsp = TSP::from_coordinates(nodes.iter().map(|row| (row[1], row[2])).collect(), EdgeWeightType::Euclidean);
+        tsp.name = name;
+        let mut vrp = VRP::new(tsp, nodes.iter().map(|row| row[3]).collect(), capacity);
+        vrp.time_windows = Some(nodes.iter().map(|row| (row[4], row[5])).collect());
+        vrp.service_times = nodes.iter().map(|row| row[6]).collect();
+        vrp.num_vehicles = Some(num_vehicles);
+        Ok(vrp)
+    }
+    ///renumber nodes so that the given node becomes the depot (node 0), the others keep their order
+    fn with_depot(mut self, depot:usize) -> Self{
+        if depot == 0 {
+            return self;
+        }
+        let order:Vec<usize> = [depot].into_iter().chain((0..self.demands.len()).filter(|&i| i != depot)).collect();
+        let reorder = |values:&Vec<f64>| order.iter().map(|&i| values[i]).collect::<Vec<f64>>();
+        self.distances = order.iter().map(|&i| reorder(&self.distances[i])).collect();
+        self.demands = reorder(&self.demands);
+        self.service_times = reorder(&self.service_times);
+        self.coordinates = self.coordinates.map(|coordinates| order.iter().map(|&i| coordinates[i]).collect());
+        self
+    }
+
+    ///optimal split of a giant tour (customers numbered from 0) into routes respecting capacity, time windows
+    /// and the number of vehicles, minimizing the total distance (Prins' split, Bellman algorithm on the auxiliary graph)<BR>
+    /// if the fleet is too small for the tour, the extra routes are penalized and counted in Solution::excess_routes
+    pub fn split(&self, tour:&[usize])->Solution{
+        let n = tour.len();
+        //end, distance and lateness of each feasible route tour[i..j]
+        let mut arcs:Vec<Vec<(usize,f64,f64)>> = vec![Vec::new(); n];
+        for (i, arcs) in arcs.iter_mut().enumerate() {
+            let (mut load, mut distance, mut time, mut lateness) = (0.0, 0.0, 0.0, 0.0);
+            let mut previous = 0;
+            for (j, customer) in tour.iter().enumerate().skip(i) {
+                let node = customer + 1;
+                load += self.demands[node];
+                if load > self.capacity && j > i {
+                    break;
+                }
+                distance += self.distances[previous][node];
+                time += self.distances[previous][node];
+                if let Some(windows) = &self.time_windows {
+                    let (ready, due) = windows[node];
+                    lateness += (time - due).max(0.0);
+                    time = time.max(ready) + self.service_times[node];
+                    let back = (time + self.distances[node][0] - windows[0].1).max(0.0);
+                    if lateness + back > 0.0 && j > i {
+                        break;
+                    }
+                    arcs.push((j + 1, distance + self.distances[node][0], lateness + back));
+                } else {
+                    arcs.push((j + 1, distance + self.distances[node][0], 0.0));
+                }
+                previous = node;
+            }
+        }
+        let cost = |(_, distance, lateness):&(usize,f64,f64)| distance + LATENESS_PENALTY * lateness;
+        //labels[r][j]: cheapest split of tour[..j] into r routes, with the start of the last route
+        let mut labels:Vec<Vec<(f64,usize)>> = vec![vec![(f64::INFINITY, 0); n + 1]; n + 1];
+        labels[0][0] = (0.0, 0);
+        for routes in 0..n {
+            for i in 0..n {
+                let (value, _) = labels[routes][i];
+                if value.is_infinite() {
+                    continue;
+                }
+                for arc in &arcs[i] {
+                    let candidate = value + cost(arc);
+                    if candidate < labels[routes + 1][arc.0].0 {
+                        labels[routes + 1][arc.0] = (candidate, i);
+                    }
+                }
+            }
+        }
+        let total = |r:usize| labels[r][n].0 + EXCESS_ROUTE_PENALTY * self.excess_routes(r) as f64;
+        let best = (1..=n).filter(|&r| labels[r][n].0.is_finite()).min_by(|&a, &b| total(a).partial_cmp(&total(b)).unwrap());
+        //every customer fits in a route of its own, so only an empty tour has no split
+        let Some(mut routes_count) = best else {
+            return self.solution(Vec::new());
+        };
+        let mut routes = Vec::with_capacity(routes_count);
+        let mut end = n;
+        while routes_count > 0 {
+            let start = labels[routes_count][end].1;
+            routes.push(tour[start..end].iter().map(|customer| customer + 1).collect());
+            end = start;
+            routes_count -= 1;
+        }
+        routes.reverse();
+        self.solution(routes)
+    }
+    ///distance and lateness of routes of customers (node indices)
+    pub fn solution(&self, routes:Vec<Vec<usize>>)->Solution{
+        let (mut distance, mut lateness) = (0.0, 0.0);
+        for route in &routes {
+            let mut time = 0.0;
+            let mut previous = 0;
+            for &node in route.iter().chain([&0]) {
+                distance += self.distances[previous][node];
+                time += self.distances[previous][node];
+                if let Some(windows) = &self.time_windows {
+                    lateness += (time - windows[node].1).max(0.0);
+                    time = time.max(windows[node].0) + self.service_times[node];
+                }
+                previous = node;
+            }
+        }
+        let excess_routes = self.excess_routes(routes.len());
+        Solution{routes, distance, lateness, excess_routes}
+    }
+    ///number of routes beyond the fleet size
+    fn excess_routes(&self, routes:usize)->usize{
+        self.num_vehicles.map_or(0, |vehicles| routes.saturating_sub(vehicles))
+    }
+    ///load of a route of customers (node indices)
+    pub fn load(&self, route:&[usize])->f64{
+        route.iter().map(|&node| self.demands[node]).sum()
+    }
+    ///fitness function for GA::new with TSPInitialization: the cost of the split giant tour negated, as fitness is maximized
+    pub fn fitness(self)->Box<dyn Fn(Population)->f64 + Send + Sync>{
+        Box::new(move |individual| match individual {
+            Population::Usize(vec) => -self.split(&vec[0]).cost(),
+            _ => panic!("Expected Population::Usize"),
+        })
+    }
+    ///route-aware crossover and mutation for GA::with_variation (see RouteOperators)
+    pub fn operators(self)->VariationStrategy{
+        VariationStrategy::Usize(Box::new(RouteOperators{vrp:Arc::new(self)}))
+    }
+}
+
+///route-aware variation operators on giant tours<BR>
+/// crossover: route based crossover, the child keeps about half of the routes of a parent
+/// and visits the other customers in the order of the other parent<BR>
+/// mutation: 2-opt inside a route, relocation of a customer to its cheapest position in another route,
+/// or exchange of two customers of different routes
+#[derive(Clone, Debug)]
+pub struct RouteOperators{
+    pub vrp:Arc<VRP>,
+}
+impl RouteOperators{
+    fn route_crossover(&self, parent1:&[usize], parent2:&[usize])->Vec<usize>{
+        let mut rng = rand::thread_rng();
+        let routes = self.vrp.split(parent1).routes;
+        let keep = rng.gen_range(0..routes.len().max(1));
+        let mut child:Vec<usize> = routes.iter().enumerate()
+            .filter(|(i, _)| *i == keep || rng.gen::<f64>() < 0.5)
+            .flat_map(|(_, route)| route.iter().map(|node| node - 1))
+            .collect();
+        let mut kept = vec![false; parent1.len()];
+        child.iter().for_each(|&customer| kept[customer] = true);
+        child.extend(parent2.iter().filter(|&&customer| !kept[customer]));
+        child
+    }
+    ///extra distance of inserting node between a and b
+    fn insertion_cost(&self, a:usize, node:usize, b:usize)->f64{
+        let d = &self.vrp.distances;
+        d[a][node] + d[node][b] - d[a][b]
+    }
+}
+impl Variation<Vec<usize>> for RouteOperators{
+    fn crossover(&self, parent1:&Vec<usize>, parent2:&Vec<usize>)->(Vec<usize>,Vec<usize>){
+        (self.route_crossover(parent1, parent2), self.route_crossover(parent2, parent1))
+    }
+    fn mutate(&self, individual:&mut Vec<usize>, mutation_rate:f64)->bool{
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f64>() >= mutation_rate || individual.len() < 2 {
+            return false;
+        }
+        let mut routes = self.vrp.split(individual).routes;
+        let r1 = rng.gen_range(0..routes.len());
+        match rng.gen_range(0..3) {
+            0 if routes[r1].len() >= 2 => {
+                let i = rng.gen_range(0..routes[r1].len() - 1);
+                let j = rng.gen_range(i + 1..routes[r1].len());
+                routes[r1][i..=j].reverse();
+            }
+            1 => {
+                let position = rng.gen_range(0..routes[r1].len());
+                let node = routes[r1].remove(position);
+                //another route or a new one
+                let r2 = rng.gen_range(0..routes.len() + 1);
+                if r2 == routes.len() || r2 == r1 {
+                    routes.push(vec![node]);
+                } else {
+                    let route = &routes[r2];
+                    let position = (0..=route.len()).min_by(|&a, &b| {
+                        let cost = |p:usize| self.insertion_cost(if p == 0 {0} else {route[p - 1]}, node, route.get(p).copied().unwrap_or(0));
+                        cost(a).partial_cmp(&cost(b)).unwrap()
+                    }).unwrap();
+                    routes[r2].insert(position, node);
+                }
+            }
+            _ => {
+                let r2 = rng.gen_range(0..routes.len());
+                let (i, j) = (rng.gen_range(0..routes[r1].len()), rng.gen_range(0..routes[r2].len()));
+                let node = routes[r1][i];
+                routes[r1][i] = routes[r2][j];
+                routes[r2][j] = node;
+            }
+        }
+        routes.shuffle(&mut rng);
+        let mutated:Vec<usize> = routes.into_iter().flatten().map(|node| node - 1).collect();
+        let changed = mutated != *individual;
+        *individual = mutated;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    ///customers on a line at x = 1..=n from a depot at 0, all with the same demand
+    fn line(n:usize, demand:f64, capacity:f64)->VRP{
+        let coordinates = (0..=n).map(|x| (x as f64, 0.0)).collect();
+        VRP::new(TSP::from_coordinates(coordinates, EdgeWeightType::Euclidean), (0..=n).map(|i| if i == 0 {0.0} else {demand}).collect(), capacity)
+    }
+
+    ///cheapest split by enumerating every way of cutting the tour
+    fn brute_force(vrp:&VRP, tour:&[usize])->f64{
+        (0..1usize << (tour.len() - 1)).filter_map(|cuts| {
+            let mut routes = vec![vec![tour[0] + 1]];
+            for (i, customer) in tour.iter().enumerate().skip(1) {
+                if cuts >> (i - 1) & 1 == 1 {
+                    routes.push(Vec::new());
+                }
+                routes.last_mut().unwrap().push(customer + 1);
+            }
+            let feasible = routes.iter().all(|route| vrp.load(route) <= vrp.capacity);
+            feasible.then(|| vrp.solution(routes).cost())
+        }).fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn split_is_optimal_and_respects_capacity(){
+        let mut rng = rand::thread_rng();
+        let mut vrp = line(8, 1.0, 3.0);
+        vrp.demands = vec![0.0, 1.0, 2.0, 1.0, 3.0, 1.0, 2.0, 2.0, 1.0];
+        for _ in 0..50 {
+            let mut tour:Vec<usize> = (0..8).collect();
+            tour.shuffle(&mut rng);
+            let solution = vrp.split(&tour);
+            assert!(solution.routes.iter().all(|route| vrp.load(route) <= vrp.capacity));
+            assert_eq!(solution.giant_tour(), tour);
+            assert!((solution.cost() - brute_force(&vrp, &tour)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn split_reports_routes_beyond_the_fleet(){
+        let mut vrp = line(4, 2.0, 3.0);
+        vrp.num_vehicles = Some(2);
+        let solution = vrp.split(&[0, 1, 2, 3]);
+        assert_eq!(solution.routes.len(), 4);
+        assert_eq!(solution.excess_routes, 2);
+        assert!(solution.cost() > solution.distance);
+        //the fleet is enough once two customers fit in a vehicle
+        vrp.capacity = 4.0;
+        let solution = vrp.split(&[0, 1, 2, 3]);
+        assert_eq!(solution.routes, vec![vec![1, 2], vec![3, 4]]);
+        assert_eq!(solution.excess_routes, 0);
+        assert_eq!(solution.cost(), 12.0);
+    }
+
+    #[test]
+    fn split_prefers_fewer_routes_than_the_fleet_limit(){
+        let mut vrp = line(4, 1.0, 2.0);
+        //without a limit each customer of the tour 3, 0, 2, 1 is cheaper to serve alone
+        let unlimited = vrp.split(&[3, 0, 2, 1]);
+        vrp.num_vehicles = Some(2);
+        let limited = vrp.split(&[3, 0, 2, 1]);
+        assert_eq!(limited.routes.len(), 2);
+        assert_eq!(limited.excess_routes, 0);
+        assert!(limited.distance >= unlimited.distance);
+    }
+
+    #[test]
+    fn cvrplib_instance_is_parsed(){
+        let content = "NAME : P-n4-k2\nCOMMENT : (Augerat et al, Optimal value: 18)\nTYPE : CVRP\nDIMENSION : 4\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 10\n\
+            NODE_COORD_SECTION\n1 3 0\n2 0 0\n3 6 0\n4 3 4\nDEMAND_SECTION\n1 4\n2 0\n3 5\n4 6\nDEPOT_SECTION\n2\n-1\nEOF\n";
+        let vrp = VRP::parse_cvrplib(content).unwrap();
+        assert_eq!(vrp.name, "P-n4-k2");
+        assert_eq!(vrp.num_vehicles, Some(2));
+        assert_eq!(vrp.optimum, Some(18.0));
+        assert_eq!(vrp.capacity, 10.0);
+        //node 2 became the depot, the others keep their order
+        assert_eq!(vrp.demands, vec![0.0, 4.0, 5.0, 6.0]);
+        assert_eq!(vrp.coordinates.as_ref().unwrap()[0], (0.0, 0.0));
+        assert_eq!(vrp.distances[0], vec![0.0, 3.0, 6.0, 5.0]);
+        assert_eq!(vrp.distances[1][3], 4.0);
+        assert!(VRP::parse_cvrplib(&content.replace("CAPACITY : 10", "CAPACITY : ten")).is_err());
+        assert!(VRP::parse_cvrplib(&content.replace("4 6\nDEPOT", "5 6\nDEPOT")).is_err());
+        assert!(VRP::parse_cvrplib(&content.replace("DEPOT_SECTION\n2", "DEPOT_SECTION\n7")).is_err());
+    }
+
+    #[test]
+    fn solomon_instance_is_parsed(){
+        let content = "C101\n\nVEHICLE\nNUMBER     CAPACITY\n  25         200\n\nCUSTOMER\nCUST NO.  XCOORD.   YCOORD.    DEMAND   READY TIME  DUE DATE   SERVICE   TIME\n\n\
+            0      40         50          0          0       1236          0\n\
+            1      45         68         10        912        967         90\n\
+            2      45         70         30        825        870         90\n";
+        let vrp = VRP::parse_solomon(content).unwrap();
+        assert_eq!(vrp.name, "C101");
+        assert_eq!((vrp.num_vehicles, vrp.capacity), (Some(25), 200.0));
+        assert_eq!(vrp.demands, vec![0.0, 10.0, 30.0]);
+        assert_eq!(vrp.time_windows.as_ref().unwrap()[1], (912.0, 967.0));
+        assert_eq!(vrp.service_times, vec![0.0, 90.0, 90.0]);
+        assert!((vrp.distances[0][1] - (25.0f64 + 324.0).sqrt()).abs() < 1e-12);
+        assert!(VRP::parse_solomon(&content.replace("\n2      45", "\n3      45")).is_err());
+        assert!(VRP::parse_solomon("C101\nVEHICLE\n25 200\n").is_err());
+    }
+}
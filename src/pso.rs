@@ -0,0 +1,195 @@
+use rand::Rng;
+use std::time::Instant;
+use crate::population::{Config, Initialization, Population, RandomInitialization};
+
+///neighbourhood a particle looks at to find its social best
+#[derive(Clone, Debug)]
+pub enum Topology{
+    ///every particle follows the best of the whole swarm
+    Global,
+    ///every particle follows the best among the given number of neighbours on each side of a ring
+    Ring(usize),
+    ///particles are laid out row by row on a toroidal grid of ceil(sqrt(n)) columns and follow the best of their up/down/left/right neighbours
+    VonNeumann,
+}
+///rule used to update velocities
+#[derive(Clone, Debug)]
+pub enum VelocityUpdate{
+    ///v = w*v + c1*r1*(pbest-x) + c2*r2*(lbest-x)
+    Inertia{weight:f64, cognitive:f64, social:f64},
+    ///Clerc-Kennedy constriction, chi is derived from phi = c1 + c2 (must be > 4)
+    Constriction{cognitive:f64, social:f64},
+}
+///PSO specific parameters<BR>
+/// swarm size, dimensions and bounds are taken from Config (num_individuals, num_genes, range)
+#[derive(Clone, Debug)]
+pub struct PSOConfig{
+    pub topology:Topology,
+    pub velocity_update:VelocityUpdate,
+    ///maximum absolute velocity on each dimension, None to disable clamping
+    pub max_velocity:Option<f64>,
+}
+///default PSO configuration
+impl Default for PSOConfig{
+    fn default() -> Self {
+        PSOConfig{
+            topology:Topology::Global,
+            velocity_update:VelocityUpdate::Inertia{weight:0.729, cognitive:1.49445, social:1.49445},
+            max_velocity:None,
+        }
+    }
+}
+
+pub struct PSO<F>
+where F:Fn(Population)->f64{
+    pub population: Population,
+    velocities: Vec<Vec<f64>>,
+    personal_best: Vec<(Vec<f64>,f64)>,
+    fitness:F,
+    config: Config,
+    pso_config: PSOConfig,
+}
+impl<F> PSO<F>
+where F:Fn(Population)->f64{
+    ///initialize a swarm with random positions and velocities within Config::range
+    pub fn new(fitness:F, config: Config, pso_config: PSOConfig) -> Self{
+        let positions = RandomInitialization.initialize(config.clone());
+        let mut rng = rand::thread_rng();
+        let width = config.range.end - config.range.start;
+        let velocities = (0..config.num_individuals).map(|_| (0..config.num_genes).map(|_| rng.gen_range(-width..width)*0.1).collect()).collect();
+        let personal_best = positions.iter().map(|position| (position.clone(), fitness(Population::F64(vec![position.clone()])))).collect();
+        PSO{population:Population::F64(positions), velocities, personal_best, fitness, config, pso_config}
+    }
+    ///return best position found so far by the swarm and its fitness
+    pub fn best(&self)->(Vec<f64>,f64){
+        self.personal_best.iter().max_by(|a,b| a.1.partial_cmp(&b.1).unwrap()).cloned().unwrap()
+    }
+    ///indices of the particles a given particle is informed by (including itself)
+    fn neighbours(&self, index:usize)->Vec<usize>{
+        let n = self.personal_best.len();
+        match self.pso_config.topology {
+            Topology::Global => (0..n).collect(),
+            Topology::Ring(k) => (0..=2*k).map(|offset| (index + n * (k + 1) + offset - k) % n).collect(),
+            Topology::VonNeumann => {
+                //particles fill a grid of cols columns row by row, the last row may be incomplete:
+                //left/right wrap around within the row, up/down within the column
+                let cols = (n as f64).sqrt().ceil() as usize;
+                let rows = n.div_ceil(cols);
+                let (row, col) = (index / cols, index % cols);
+                let width = if row == rows - 1 {n - row * cols} else {cols};
+                let height = if (rows - 1) * cols + col < n {rows} else {rows - 1};
+                vec![index,
+                    row * cols + (col + 1) % width,
+                    row * cols + (col + width - 1) % width,
+                    (row + 1) % height * cols + col,
+                    (row + height - 1) % height * cols + col]
+            }
+        }
+    }
+    ///position of the best personal best within the neighbourhood of a particle
+    fn local_best(&self, index:usize)->Vec<f64>{
+        let best = self.neighbours(index).into_iter()
+            .max_by(|&a,&b| self.personal_best[a].1.partial_cmp(&self.personal_best[b].1).unwrap())
+            .unwrap();
+        self.personal_best[best].0.clone()
+    }
+    ///move the swarm forward by one step<BR>
+    /// in particular:<BR>
+    /// update velocities from personal and neighbourhood bests<BR>
+    /// clamp velocities and move particles within Config::range<BR>
+    /// evaluate particles and update personal bests<BR>
+    /// returns best fitness found so far
+    pub fn step(&mut self)->f64{
+        let local_bests:Vec<Vec<f64>> = (0..self.personal_best.len()).map(|i| self.local_best(i)).collect();
+        let (inertia, cognitive, social) = match self.pso_config.velocity_update {
+            VelocityUpdate::Inertia{weight, cognitive, social} => (weight, cognitive, social),
+            VelocityUpdate::Constriction{cognitive, social} => {
+                let phi = cognitive + social;
+                assert!(phi > 4.0, "Constriction requires cognitive + social > 4");
+                let chi = 2.0 / (2.0 - phi - (phi * phi - 4.0 * phi).sqrt()).abs();
+                (chi, chi * cognitive, chi * social)
+            }
+        };
+        let range = self.config.range.clone();
+        let max_velocity = self.pso_config.max_velocity;
+        let mut rng = rand::thread_rng();
+        let positions = match &mut self.population {
+            Population::F64(vec) => vec,
+            _ => panic!("Expected Population::F64"),
+        };
+        for (i, position) in positions.iter_mut().enumerate() {
+            let velocity = &mut self.velocities[i];
+            for j in 0..position.len() {
+                let (r1, r2) = (rng.gen::<f64>(), rng.gen::<f64>());
+                velocity[j] = inertia * velocity[j]
+                    + cognitive * r1 * (self.personal_best[i].0[j] - position[j])
+                    + social * r2 * (local_bests[i][j] - position[j]);
+                if let Some(max) = max_velocity {
+                    velocity[j] = velocity[j].clamp(-max, max);
+                }
+                position[j] += velocity[j];
+                if position[j] < range.start || position[j] > range.end {
+                    position[j] = position[j].clamp(range.start, range.end);
+                    velocity[j] = 0.0;
+                }
+            }
+            let score = (self.fitness)(Population::F64(vec![position.clone()]));
+            if score > self.personal_best[i].1 {
+                self.personal_best[i] = (position.clone(), score);
+            }
+        }
+        let best = self.best().1;
+        if self.config.verbose {
+            print!("Best score = {}",best);
+        }
+        best
+    }
+    ///execute num_steps forward, return a vector of best scores (curve of fitness)
+    pub fn evolve(&mut self, num_steps:usize) ->Vec<f64>{
+        let start_time = Instant::now();
+        let mut hist=Vec::new();
+        for i in 0..num_steps{
+            if self.config.verbose {
+                println!();
+                println!("Step {}",i);
+            }
+            hist.push(self.step());
+        }
+        let duration = Instant::now().duration_since(start_time);
+        if self.config.verbose {
+            println!();
+            println!("Elapsed_time = {:?}",duration);
+        }
+        hist
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn von_neumann_neighbours_are_symmetric(){
+        for n in 1..40 {
+            let config = Config{num_individuals:n, num_genes:2, ..Config::default()};
+            let pso_config = PSOConfig{topology:Topology::VonNeumann, ..PSOConfig::default()};
+            let pso = PSO::new(|_| 0.0, config, pso_config);
+            for index in 0..n {
+                let neighbours = pso.neighbours(index);
+                assert_eq!(neighbours[0], index);
+                //right/left and down/up are inverse moves
+                assert_eq!(pso.neighbours(neighbours[1])[2], index, "n = {}, index = {}", n, index);
+                assert_eq!(pso.neighbours(neighbours[3])[4], index, "n = {}, index = {}", n, index);
+                assert!(neighbours.iter().all(|&neighbour| neighbour < n));
+            }
+        }
+    }
+
+    #[test]
+    fn von_neumann_rows_do_not_wrap_onto_the_next_row(){
+        let config = Config{num_individuals:9, num_genes:2, ..Config::default()};
+        let pso = PSO::new(|_| 0.0, config, PSOConfig{topology:Topology::VonNeumann, ..PSOConfig::default()});
+        //3x3 grid: 2 is on the right edge, its right neighbour is 0 and it sits below 8
+        assert_eq!(pso.neighbours(2), vec![2, 0, 1, 5, 8]);
+    }
+}
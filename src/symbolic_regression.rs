@@ -0,0 +1,391 @@
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+use crate::gp::{Function, GPConfig, Node, PrimitiveSet, RampedHalfAndHalf, Terminal, Tree};
+use crate::memetic::{ConstantTuning, LocalSearchStrategy};
+use crate::population::{Config, GA, InitializationStrategy, Population};
+
+///inputs and targets of a regression problem
+#[derive(Clone, Debug)]
+pub struct Dataset{
+    ///name of each input variable, used to print expressions
+    pub names:Vec<String>,
+    ///one row of inputs per sample
+    pub inputs:Vec<Vec<f64>>,
+    pub targets:Vec<f64>,
+}
+impl Dataset{
+    ///build a dataset from rows of inputs, variables are named x0, x1, ...
+    pub fn new(inputs:Vec<Vec<f64>>, targets:Vec<f64>) -> Self{
+        assert_eq!(inputs.len(), targets.len(), "Inputs and targets must have the same number of samples");
+        let names = (0..inputs.first().map_or(0, |row| row.len())).map(|i| format!("x{}", i)).collect();
+        Dataset{names, inputs, targets}
+    }
+    ///read a comma separated file, the target_column holds targets and the others inputs<BR>
+    /// if the first line is not numeric it is used as header for variable names
+    pub fn from_csv(path:&str, target_column:usize) -> io::Result<Self>{
+        Self::parse_csv(&fs::read_to_string(path)?, target_column)
+    }
+    ///parse the content of a comma separated file (see from_csv)
+    pub fn parse_csv(content:&str, target_column:usize) -> io::Result<Self>{
+        //line numbers in errors are those of the file
+        let mut lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).peekable();
+        let header:Option<Vec<String>> = match lines.peek() {
+            Some((_, line)) if line.split(',').any(|field| field.trim().parse::<f64>().is_err()) =>
+                lines.next().map(|(_, line)| line.split(',').map(|field| field.trim().to_string()).collect()),
+            _ => None,
+        };
+        let invalid = |number:usize, message:String| io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, message));
+        let mut columns = header.as_ref().map(|header| header.len());
+        let mut inputs = Vec::new();
+        let mut targets = Vec::new();
+        for (number, line) in lines {
+            let row = line.split(',').map(|field| field.trim().parse::<f64>())
+                .collect::<Result<Vec<f64>,_>>()
+                .map_err(|e| invalid(number, e.to_string()))?;
+            if target_column >= row.len() {
+                return Err(invalid(number, "missing target column".to_string()));
+            }
+            match columns {
+                Some(columns) if columns != row.len() => return Err(invalid(number, format!("expected {} columns, found {}", columns, row.len()))),
+                _ => columns = Some(row.len()),
+            }
+            targets.push(row[target_column]);
+            inputs.push(row.iter().enumerate().filter(|(i, _)| *i != target_column).map(|(_, &value)| value).collect());
+        }
+        let mut dataset = Dataset::new(inputs, targets);
+        if let Some(header) = header {
+            dataset.names = header.into_iter().enumerate().filter(|(i, _)| *i != target_column).map(|(_, name)| name).collect();
+        }
+        Ok(dataset)
+    }
+}
+
+///error minimized by symbolic regression
+#[derive(Clone, Debug)]
+pub enum Loss{
+    ///mean squared error
+    MSE,
+    ///mean absolute error
+    MAE,
+}
+///protected division: returns 1 when the denominator is (almost) zero
+pub fn protected_div(args:&[f64])->f64{
+    if args[1].abs() < 1e-9 {1.0} else {args[0] / args[1]}
+}
+///add, sub, mul and protected div
+pub fn arithmetic() -> Vec<Function>{
+    vec![
+        Function::new("add", 2, |args| args[0] + args[1]),
+        Function::new("sub", 2, |args| args[0] - args[1]),
+        Function::new("mul", 2, |args| args[0] * args[1]),
+        Function::new("div", 2, protected_div),
+    ]
+}
+///struct used to configure symbolic regression<BR>
+/// there is a Default configuration
+#[derive(Clone, Debug)]
+pub struct RegressionConfig{
+    pub loss:Loss,
+    ///functions expressions are built from (see arithmetic)
+    pub functions:Vec<Function>,
+    ///range of ephemeral random constants, None to only use the input variables
+    pub constants:Option<Range<f64>>,
+    ///tune constants of offspring with local search (see Config::local_search_rate and Config::local_search_budget)
+    pub tune_constants:bool,
+    ///penalty per node added to the loss, favours smaller expressions (0 to disable)
+    pub parsimony:f64,
+    pub gp_config:GPConfig,
+}
+///default regression configuration
+impl Default for RegressionConfig{
+    fn default() -> Self {
+        RegressionConfig{
+            loss:Loss::MSE,
+            functions:arithmetic(),
+            constants:Some(-1.0..1.0),
+            tune_constants:false,
+            parsimony:0.0,
+            gp_config:GPConfig::default(),
+        }
+    }
+}
+
+///error of a tree on a dataset, f64::MAX if the tree produces non finite values
+pub fn loss(tree:&Tree, dataset:&Dataset, loss:&Loss)->f64{
+    let errors = dataset.inputs.iter().zip(&dataset.targets).map(|(inputs, target)| tree.evaluate(inputs) - target);
+    let error = match loss {
+        Loss::MSE => errors.map(|e| e * e).sum::<f64>(),
+        Loss::MAE => errors.map(|e| e.abs()).sum::<f64>(),
+    } / dataset.targets.len().max(1) as f64;
+    if error.is_finite() {error} else {f64::MAX}
+}
+///fitness maximized by symbolic regression: opposite of the loss plus parsimony times the number of nodes
+pub fn fitness(tree:&Tree, dataset:&Dataset, loss_kind:&Loss, parsimony:f64)->f64{
+    -(loss(tree, dataset, loss_kind) + parsimony * tree.size() as f64)
+}
+
+///symbolic regression solver: a GA over trees whose fitness is the opposite of the loss on a dataset (see fitness)
+pub struct SymbolicRegression{
+    pub ga:GA<Box<dyn Fn(Population)->f64 + Send + Sync>>,
+    dataset:Arc<Dataset>,
+    loss:Loss,
+}
+impl SymbolicRegression{
+    pub fn new(dataset:Dataset, config:Config, regression_config:RegressionConfig) -> Self{
+        let mut terminals:Vec<Terminal> = dataset.names.iter().enumerate().map(|(i, name)| Terminal::variable(name, i)).collect();
+        if let Some(range) = regression_config.constants.clone() {
+            terminals.push(Terminal::ephemeral(range));
+        }
+        let mut set = PrimitiveSet::new(regression_config.functions.clone(), terminals);
+        set.config = regression_config.gp_config.clone();
+        let set = Arc::new(set);
+        let dataset = Arc::new(dataset);
+        let fitness_dataset = dataset.clone();
+        let fitness_loss = regression_config.loss.clone();
+        let parsimony = regression_config.parsimony;
+        let fitness:Box<dyn Fn(Population)->f64 + Send + Sync> = Box::new(move |program| match program {
+            Population::Tree(trees) => fitness(&trees[0], &fitness_dataset, &fitness_loss, parsimony),
+            _ => panic!("Expected Population::Tree"),
+        });
+        let mut ga = GA::new(InitializationStrategy::Tree(Box::new(RampedHalfAndHalf{set})), fitness, config);
+        if regression_config.tune_constants {
+            ga = ga.with_local_search(LocalSearchStrategy::Tree(Box::new(ConstantTuning{step_size:0.1})));
+        }
+        SymbolicRegression{ga, dataset, loss:regression_config.loss}
+    }
+    ///execute num_steps forward of evolution, return a vector of scores (opposite of the best loss)
+    pub fn evolve(&mut self, num_steps:usize)->Vec<f64>{
+        self.ga.evolve(num_steps)
+    }
+    ///return the tree with the best fitness in the population (the lowest loss without parsimony)
+    pub fn best(&self)->Tree{
+        let evals = self.ga.evaluate();
+        let best = (0..evals.len()).max_by(|&a, &b| evals[a].partial_cmp(&evals[b]).unwrap()).unwrap();
+        self.ga.population().get_tree(best).unwrap()
+    }
+    ///loss of a tree on the dataset
+    pub fn loss(&self, tree:&Tree)->f64{
+        loss(tree, &self.dataset, &self.loss)
+    }
+    ///best expression as simplified infix string
+    pub fn expression(&self)->String{
+        to_infix(&self.best())
+    }
+}
+
+///expression built from a tree to simplify and print it
+#[derive(Clone, Debug, PartialEq)]
+enum Expr{
+    Const(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    ///binary operator among + - * /
+    Op(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+impl Expr{
+    fn precedence(&self)->u8{
+        match self {
+            Expr::Op('+', _, _) | Expr::Op('-', _, _) => 1,
+            Expr::Op(_, _, _) => 2,
+            Expr::Neg(_) => 3,
+            _ => 4,
+        }
+    }
+    ///apply algebraic identities to an operator whose operands are already simplified
+    fn simplify(op:char, a:Expr, b:Expr)->Expr{
+        match (op, a, b) {
+            ('+', Expr::Const(z), x) | ('+', x, Expr::Const(z)) | ('-', x, Expr::Const(z)) if z == 0.0 => x,
+            ('-', Expr::Const(0.0), x) => Expr::Neg(Box::new(x)),
+            ('-', x, y) if x == y => Expr::Const(0.0),
+            ('+', x, Expr::Const(c)) if c < 0.0 => Expr::Op('-', Box::new(x), Box::new(Expr::Const(-c))),
+            ('-', x, Expr::Const(c)) if c < 0.0 => Expr::Op('+', Box::new(x), Box::new(Expr::Const(-c))),
+            ('*', Expr::Const(z), _) | ('*', _, Expr::Const(z)) if z == 0.0 => Expr::Const(0.0),
+            ('*', Expr::Const(o), x) | ('*', x, Expr::Const(o)) | ('/', x, Expr::Const(o)) if o == 1.0 => x,
+            ('/', x, y) if x == y => Expr::Const(1.0),
+            (op, a, b) => Expr::Op(op, Box::new(a), Box::new(b)),
+        }
+    }
+    fn write(&self, out:&mut String){
+        match self {
+            Expr::Const(value) if *value != 0.0 && value.abs() < 1e-3 => out.push_str(&format!("{:.3e}", value)),
+            Expr::Const(value) => {
+                let text = format!("{:.4}", value);
+                out.push_str(text.trim_end_matches('0').trim_end_matches('.'));
+            }
+            Expr::Var(name) => out.push_str(name),
+            Expr::Neg(x) => {
+                out.push('-');
+                x.write_operand(out, x.precedence() < 3);
+            }
+            Expr::Op(op, a, b) => {
+                a.write_operand(out, a.precedence() < self.precedence());
+                out.push_str(&format!(" {} ", op));
+                b.write_operand(out, b.precedence() < self.precedence() || (b.precedence() == self.precedence() && (*op == '-' || *op == '/')));
+            }
+            Expr::Call(name, args) => {
+                out.push_str(name);
+                out.push('(');
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    arg.write(out);
+                }
+                out.push(')');
+            }
+        }
+    }
+    fn write_operand(&self, out:&mut String, parenthesis:bool){
+        if parenthesis {
+            out.push('(');
+            self.write(out);
+            out.push(')');
+        } else {
+            self.write(out);
+        }
+    }
+}
+///build the simplified expression of the subtree starting at index, return it with the index one past its end<BR>
+/// subtrees without variables are folded into constants
+fn build(tree:&Tree, index:usize)->(Expr,usize){
+    match tree.nodes[index] {
+        Node::Function(f) => {
+            let function = &tree.set.functions[f];
+            let mut args = Vec::with_capacity(function.arity());
+            let mut next = index + 1;
+            for _ in 0..function.arity() {
+                let (arg, end) = build(tree, next);
+                args.push(arg);
+                next = end;
+            }
+            if args.iter().all(|arg| matches!(arg, Expr::Const(_))) {
+                let values:Vec<f64> = args.iter().map(|arg| if let Expr::Const(value) = arg {*value} else {0.0}).collect();
+                return (Expr::Const((function.op)(&values)), next);
+            }
+            let op = match function.name.as_str() {
+                "add" => Some('+'),
+                "sub" => Some('-'),
+                "mul" => Some('*'),
+                "div" => Some('/'),
+                _ => None,
+            };
+            match op {
+                Some(op) if args.len() == 2 => {
+                    let b = args.pop().unwrap();
+                    let a = args.pop().unwrap();
+                    (Expr::simplify(op, a, b), next)
+                }
+                _ => (Expr::Call(function.name.clone(), args), next),
+            }
+        }
+        Node::Terminal(t) => match &tree.set.terminals[t] {
+            Terminal::Variable{name, ..} => (Expr::Var(name.clone()), index + 1),
+            Terminal::Constant{value, ..} => (Expr::Const(*value), index + 1),
+            Terminal::Ephemeral{..} => unreachable!("ephemeral constants are stored as Node::Constant"),
+        },
+        Node::Constant(value, _) => (Expr::Const(value), index + 1),
+    }
+}
+///print a tree as a simplified infix expression, e.g. x0 * x0 + 2.5<BR>
+/// functions named add, sub, mul and div are printed as operators, other functions as calls
+pub fn to_infix(tree:&Tree)->String{
+    let mut out = String::new();
+    build(tree, 0).0.write(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn set()->Arc<PrimitiveSet>{
+        Arc::new(PrimitiveSet::new(arithmetic(), vec![Terminal::variable("x0", 0), Terminal::variable("x1", 1)]))
+    }
+
+    //functions: add 0, sub 1, mul 2, div 3, terminals: x0 0, x1 1
+    fn infix(nodes:Vec<Node>)->String{
+        to_infix(&Tree::new(nodes, set()))
+    }
+
+    #[test]
+    fn parse_csv_with_header(){
+        let dataset = Dataset::parse_csv("a, y, b\n1, 2, 3\n\n4, 5, 6\n", 1).unwrap();
+        assert_eq!(dataset.names, vec!["a", "b"]);
+        assert_eq!(dataset.inputs, vec![vec![1.0, 3.0], vec![4.0, 6.0]]);
+        assert_eq!(dataset.targets, vec![2.0, 5.0]);
+    }
+
+    #[test]
+    fn parse_csv_without_header(){
+        let dataset = Dataset::parse_csv("1,2,3\n4,5,6", 2).unwrap();
+        assert_eq!(dataset.names, vec!["x0", "x1"]);
+        assert_eq!(dataset.inputs, vec![vec![1.0, 2.0], vec![4.0, 5.0]]);
+        assert_eq!(dataset.targets, vec![3.0, 6.0]);
+    }
+
+    #[test]
+    fn parse_csv_rejects_malformed_rows(){
+        let error = Dataset::parse_csv("x,y\n\n1,2\n3,abc\n", 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("line 4:"), "{}", error);
+        assert!(Dataset::parse_csv("1,2\n3\n", 1).is_err());
+        assert!(Dataset::parse_csv("x,y\n1,2,3\n", 1).is_err());
+        assert!(Dataset::parse_csv("1,2\n3,4,5\n", 1).is_err());
+    }
+
+    #[test]
+    fn from_csv_reads_a_file(){
+        let path = std::env::temp_dir().join("rs_genetics_symbolic_regression.csv");
+        fs::write(&path, "x,y\n1,2\n").unwrap();
+        let dataset = Dataset::from_csv(path.to_str().unwrap(), 1).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(dataset.names, vec!["x"]);
+        assert_eq!(dataset.targets, vec![2.0]);
+        assert!(Dataset::from_csv("missing.csv", 0).is_err());
+    }
+
+    #[test]
+    fn infix_respects_precedence(){
+        use Node::{Function as F, Terminal as T};
+        assert_eq!(infix(vec![F(2), F(0), T(0), T(1), T(0)]), "(x0 + x1) * x0");
+        assert_eq!(infix(vec![F(0), F(2), T(0), T(1), T(0)]), "x0 * x1 + x0");
+        assert_eq!(infix(vec![F(1), T(0), F(1), T(1), T(0)]), "x0 - (x1 - x0)");
+        assert_eq!(infix(vec![F(1), F(1), T(0), T(1), T(0)]), "x0 - x1 - x0");
+        assert_eq!(infix(vec![F(3), T(0), F(2), T(1), T(0)]), "x0 / (x1 * x0)");
+        assert_eq!(infix(vec![F(1), Node::Constant(0.0, 0), F(0), T(0), T(1)]), "-(x0 + x1)");
+    }
+
+    #[test]
+    fn simplify_folds_constants_and_removes_identities(){
+        use Node::{Constant as C, Function as F, Terminal as T};
+        assert_eq!(infix(vec![F(2), F(0), C(2.0, 0), C(3.0, 0), T(0)]), "5 * x0");
+        assert_eq!(infix(vec![F(3), C(1.0, 0), C(0.0, 0)]), "1");
+        assert_eq!(infix(vec![F(0), T(0), C(0.0, 0)]), "x0");
+        assert_eq!(infix(vec![F(2), C(1.0, 0), T(1)]), "x1");
+        assert_eq!(infix(vec![F(3), T(1), C(1.0, 0)]), "x1");
+        assert_eq!(infix(vec![F(2), T(0), C(0.0, 0)]), "0");
+        assert_eq!(infix(vec![F(1), T(0), T(0)]), "0");
+        assert_eq!(infix(vec![F(3), T(1), T(1)]), "1");
+        assert_eq!(infix(vec![F(0), T(0), C(-2.0, 0)]), "x0 - 2");
+    }
+
+    #[test]
+    fn fitness_is_opposite_of_loss_plus_parsimony(){
+        use Node::{Constant as C, Function as F, Terminal as T};
+        let dataset = Dataset::new(vec![vec![1.0, 0.0], vec![2.0, 0.0], vec![3.0, 0.0]], vec![1.0, 2.0, 3.0]);
+        let identity = Tree::new(vec![T(0)], set());
+        let shifted = Tree::new(vec![F(0), T(0), C(1.0, 0)], set());
+        let square = Tree::new(vec![F(2), T(0), T(0)], set());
+        assert_eq!(fitness(&identity, &dataset, &Loss::MSE, 0.0), 0.0);
+        assert_eq!(loss(&shifted, &dataset, &Loss::MSE), 1.0);
+        assert_eq!(loss(&square, &dataset, &Loss::MSE), 40.0 / 3.0);
+        assert_eq!(loss(&square, &dataset, &Loss::MAE), 8.0 / 3.0);
+        assert!((fitness(&shifted, &dataset, &Loss::MSE, 0.1) + 1.3).abs() < 1e-12);
+        //with the same loss, the smaller tree is fitter
+        let padded = Tree::new(vec![F(2), T(0), C(1.0, 0)], set());
+        assert!(fitness(&identity, &dataset, &Loss::MSE, 0.01) > fitness(&padded, &dataset, &Loss::MSE, 0.01));
+    }
+}